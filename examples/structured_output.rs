@@ -106,5 +106,7 @@ fn main() {
 
     println!("\nTo send this request, set ANTHROPIC_API_KEY and use:");
     println!("  let response = client.send_message(request).await?;");
+    println!("\nOr parse it straight into a Rust type, with automatic repair turns:");
+    println!("  let (analysis, _) = client.send_structured::<Analysis>(request).await?;");
     println!("\nThe response will be guaranteed JSON matching the schema above.");
 }
@@ -0,0 +1,440 @@
+//! Procedural macros for `claude-sdk`
+//!
+//! Use through the SDK's `macros` feature rather than depending on this
+//! crate directly; the generated code refers to `::claude_sdk`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{Expr, ExprLit, FnArg, ItemFn, Lit, Meta, Pat, ReturnType, Token, Type};
+
+/// Turn an `async fn` into a `claude_sdk::tools::Tool`
+///
+/// Generates a unit struct named after the function in PascalCase
+/// (`get_weather` → `GetWeather`) that implements `Tool`:
+///
+/// - the tool name is the function name
+/// - the description is the doc comment, up to any `# Arguments` section
+/// - `input_schema` has one property per argument, typed through
+///   `claude_sdk::tools::ToolParam`; `Option<T>` arguments are optional,
+///   and `` * `arg` - text `` lines under `# Arguments` become property
+///   descriptions
+/// - `call` deserializes each argument from the `tool_use` input, awaits
+///   the function and serializes the return value; an `Err` from a
+///   `Result` return becomes an `is_error` result
+///
+/// An argument of type `&ToolContext` receives the call context and is
+/// left out of the schema. The function itself is kept unchanged.
+///
+/// Override the name or description with
+/// `#[tool(name = "...", description = "...")]`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use claude_sdk::tool;
+///
+/// /// Get the current weather for a city
+/// ///
+/// /// # Arguments
+/// /// * `city` - City name, e.g. "Paris"
+/// /// * `unit` - "celsius" or "fahrenheit"
+/// #[tool]
+/// async fn get_weather(city: String, unit: Option<String>) -> Result<String, String> {
+///     Ok(format!("Sunny in {}", city))
+/// }
+///
+/// let registry = claude_sdk::ToolRegistry::new().with_tool(GetWeather);
+/// ```
+#[proc_macro_attribute]
+pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function = syn::parse_macro_input!(item as ItemFn);
+    match expand(attr.into(), function) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Options from `#[tool(...)]`
+#[derive(Debug, Default)]
+struct ToolArgs {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+fn parse_args(attr: TokenStream2) -> syn::Result<ToolArgs> {
+    let mut args = ToolArgs::default();
+    let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(attr)?;
+    for meta in metas {
+        let Meta::NameValue(pair) = &meta else {
+            return Err(syn::Error::new_spanned(meta, "expected `key = \"value\"`"));
+        };
+        let value = match &pair.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(s), ..
+            }) => s.value(),
+            other => return Err(syn::Error::new_spanned(other, "expected a string literal")),
+        };
+        if pair.path.is_ident("name") {
+            args.name = Some(value);
+        } else if pair.path.is_ident("description") {
+            args.description = Some(value);
+        } else {
+            return Err(syn::Error::new_spanned(
+                &pair.path,
+                "unknown option; expected `name` or `description`",
+            ));
+        }
+    }
+    Ok(args)
+}
+
+/// One schema-visible function argument
+struct Param {
+    ident: syn::Ident,
+    ty: Type,
+}
+
+fn expand(attr: TokenStream2, function: ItemFn) -> syn::Result<TokenStream2> {
+    let args = parse_args(attr)?;
+    let sig = &function.sig;
+
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "#[tool] functions cannot be generic",
+        ));
+    }
+
+    let docs = doc_lines(&function.attrs);
+    let (summary, arg_docs) = split_docs(&docs);
+    let description = args.description.unwrap_or(summary);
+    if description.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.ident,
+            "#[tool] needs a doc comment or `description = \"...\"` so Claude knows what the tool does",
+        ));
+    }
+    let tool_name = args.name.unwrap_or_else(|| sig.ident.to_string());
+    let struct_ident = syn::Ident::new(&to_pascal_case(&sig.ident.to_string()), sig.ident.span());
+
+    let mut params = Vec::new();
+    let mut call_args = Vec::new();
+    let mut uses_context = false;
+    for input in &sig.inputs {
+        let FnArg::Typed(typed) = input else {
+            return Err(syn::Error::new_spanned(
+                input,
+                "#[tool] functions cannot take `self`",
+            ));
+        };
+        if is_context(&typed.ty) {
+            uses_context = true;
+            call_args.push(quote! { __claude_ctx });
+            continue;
+        }
+        let Pat::Ident(pat) = typed.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                &typed.pat,
+                "#[tool] arguments must be plain identifiers",
+            ));
+        };
+        let ident = pat.ident.clone();
+        call_args.push(quote! { #ident });
+        params.push(Param {
+            ident,
+            ty: (*typed.ty).clone(),
+        });
+    }
+
+    let fn_ident = &sig.ident;
+    let vis = &function.vis;
+    let call = if sig.asyncness.is_some() {
+        quote! { #fn_ident(#(#call_args),*).await }
+    } else {
+        quote! { #fn_ident(#(#call_args),*) }
+    };
+    let output = if returns_result(&sig.output) {
+        quote! {
+            match #call {
+                Ok(value) => ::claude_sdk::tools::__private::serialize_output(&value),
+                Err(error) => ::claude_sdk::tools::ToolOutput::error(error.to_string()),
+            }
+        }
+    } else {
+        quote! { ::claude_sdk::tools::__private::serialize_output(&#call) }
+    };
+
+    let properties = params.iter().map(|param| {
+        let key = param.ident.to_string();
+        let ty = &param.ty;
+        let describe = arg_docs
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, text)| {
+                quote! {
+                    schema["description"] = ::claude_sdk::tools::__private::serde_json::Value::from(#text);
+                }
+            });
+        quote! {
+            {
+                let mut schema = <#ty as ::claude_sdk::tools::ToolParam>::schema();
+                #describe
+                properties.insert(#key.to_string(), schema);
+                if <#ty as ::claude_sdk::tools::ToolParam>::REQUIRED {
+                    required.push(::claude_sdk::tools::__private::serde_json::Value::from(#key));
+                }
+            }
+        }
+    });
+
+    let extract = params.iter().map(|param| {
+        let ident = &param.ident;
+        let ty = &param.ty;
+        let key = ident.to_string();
+        quote! {
+            let #ident: #ty = match ::claude_sdk::tools::__private::argument(&__claude_input, #key) {
+                Ok(value) => value,
+                Err(output) => return output,
+            };
+        }
+    });
+
+    // Prefixed so they cannot clash with the function's own argument names
+    let ctx_ident = if uses_context {
+        format_ident!("__claude_ctx")
+    } else {
+        format_ident!("_ctx")
+    };
+    let input_ident = if params.is_empty() {
+        format_ident!("_input")
+    } else {
+        format_ident!("__claude_input")
+    };
+    let doc = format!("Tool generated by `#[tool]` from [`{}`]", fn_ident);
+
+    Ok(quote! {
+        #function
+
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, Default)]
+        #vis struct #struct_ident;
+
+        #[::claude_sdk::tools::async_trait]
+        impl ::claude_sdk::tools::Tool for #struct_ident {
+            fn name(&self) -> &str {
+                #tool_name
+            }
+
+            fn description(&self) -> &str {
+                #description
+            }
+
+            fn input_schema(&self) -> ::claude_sdk::tools::__private::serde_json::Value {
+                #[allow(unused_mut)]
+                let mut properties = ::claude_sdk::tools::__private::serde_json::Map::new();
+                #[allow(unused_mut)]
+                let mut required: Vec<::claude_sdk::tools::__private::serde_json::Value> = Vec::new();
+                #(#properties)*
+                ::claude_sdk::tools::__private::serde_json::json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                })
+            }
+
+            async fn call(
+                &self,
+                #input_ident: ::claude_sdk::tools::__private::serde_json::Value,
+                #ctx_ident: &::claude_sdk::tools::ToolContext,
+            ) -> ::claude_sdk::tools::ToolOutput {
+                #(#extract)*
+                #output
+            }
+        }
+    })
+}
+
+/// Doc comment lines, with the single leading space rustdoc adds removed
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(pair) => match &pair.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect()
+}
+
+/// Split docs into the description and `(argument, text)` pairs
+///
+/// The description is everything before a `# Arguments` heading.
+/// Argument lines look like `` * `name` - text `` (or with `-` bullets
+/// and `:` separators).
+fn split_docs(lines: &[String]) -> (String, Vec<(String, String)>) {
+    let mut summary = Vec::new();
+    let mut arguments = Vec::new();
+    let mut in_arguments = false;
+    let mut seen_heading = false;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            seen_heading = true;
+            in_arguments = heading
+                .trim_start_matches('#')
+                .trim()
+                .eq_ignore_ascii_case("arguments");
+            continue;
+        }
+        if in_arguments {
+            if let Some(pair) = parse_argument_line(trimmed) {
+                arguments.push(pair);
+            }
+        } else if !seen_heading {
+            summary.push(trimmed);
+        }
+    }
+
+    (summary.join("\n").trim().to_string(), arguments)
+}
+
+fn parse_argument_line(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix(['*', '-'])?.trim_start();
+    let rest = rest.strip_prefix('`')?;
+    let end = rest.find('`')?;
+    let name = rest[..end].to_string();
+    let text = rest[end + 1..]
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '-' || c == ':' || c == '–')
+        .trim()
+        .to_string();
+    Some((name, text))
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Whether a type is `&ToolContext` (any path ending in `ToolContext`)
+fn is_context(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => match reference.elem.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "ToolContext"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether the return type is spelled `Result<...>` (any path ending in `Result`)
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_to_pascal_case() {
+        assert_eq!(to_pascal_case("get_weather"), "GetWeather");
+        assert_eq!(to_pascal_case("search"), "Search");
+        assert_eq!(to_pascal_case("r#type"), "Type");
+        assert_eq!(to_pascal_case("__private_tool"), "PrivateTool");
+    }
+
+    #[test]
+    fn test_split_docs_with_arguments() {
+        let (summary, arguments) = split_docs(&lines(
+            "Get the weather\nfor a city\n\n# Arguments\n* `city` - City name\n- `unit`: Temperature unit\n\n# Errors\n* `ignored` - not an argument",
+        ));
+        assert_eq!(summary, "Get the weather\nfor a city");
+        assert_eq!(
+            arguments,
+            vec![
+                ("city".to_string(), "City name".to_string()),
+                ("unit".to_string(), "Temperature unit".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_docs_without_arguments() {
+        let (summary, arguments) = split_docs(&lines("  Roll a die  \n"));
+        assert_eq!(summary, "Roll a die");
+        assert!(arguments.is_empty());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse_args(quote! { name = "weather", description = "Weather lookup" }).unwrap();
+        assert_eq!(args.name.as_deref(), Some("weather"));
+        assert_eq!(args.description.as_deref(), Some("Weather lookup"));
+
+        assert!(parse_args(quote! { strict = "yes" }).is_err());
+        assert!(parse_args(quote! { name = 5 }).is_err());
+    }
+
+    #[test]
+    fn test_type_detection() {
+        let context: Type = syn::parse_quote!(&claude_sdk::tools::ToolContext);
+        let owned: Type = syn::parse_quote!(ToolContext);
+        assert!(is_context(&context));
+        assert!(!is_context(&owned));
+
+        let result: ReturnType = syn::parse_quote!(-> std::result::Result<String, String>);
+        let plain: ReturnType = syn::parse_quote!(-> String);
+        assert!(returns_result(&result));
+        assert!(!returns_result(&plain));
+        assert!(!returns_result(&ReturnType::Default));
+    }
+
+    #[test]
+    fn test_expand_rejects_bad_signatures() {
+        let undocumented: ItemFn = syn::parse_quote! { async fn nothing() {} };
+        assert!(expand(TokenStream2::new(), undocumented).is_err());
+
+        let generic: ItemFn = syn::parse_quote! {
+            /// Generic
+            async fn generic<T>(value: T) {}
+        };
+        assert!(expand(TokenStream2::new(), generic).is_err());
+    }
+}
@@ -0,0 +1,547 @@
+//! Agent loop that drives multi-turn tool use to completion
+//!
+//! [`Agent`] bundles a client, model, system prompt and [`ToolRegistry`],
+//! and runs the standard loop on top of [`ConversationBuilder`]: send the
+//! request, execute any requested tools, append the results and repeat
+//! until Claude ends its turn or a limit is hit.
+//!
+//! - `tool_use`: every `tool_use` block is answered before the next request
+//! - `pause_turn`: the paused assistant content is sent back so the server
+//!   tool can resume
+//! - `max_tokens`: the run stops with [`AgentStop::MaxTokens`] instead of
+//!   executing a possibly truncated tool call
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::agent::Agent;
+//! use claude_sdk::{ClaudeClient, ToolRegistry};
+//!
+//! # async fn example(tools: ToolRegistry) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ClaudeClient::anthropic(std::env::var("ANTHROPIC_API_KEY")?);
+//!
+//! let agent = Agent::new(client, "claude-sonnet-4-5-20250929")
+//!     .with_system("You are a helpful assistant.")
+//!     .with_tools(tools)
+//!     .with_max_turns(8)
+//!     .with_token_budget(100_000);
+//!
+//! let run = agent.run("What's the weather in Paris and Tokyo?").await?;
+//! println!("{}", run.text());
+//! println!("{:?} after {} turns, {} tokens", run.stop, run.turns, run.usage.total_tokens());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::ClaudeClient;
+use crate::conversation::ConversationBuilder;
+use crate::error::Result;
+use crate::executor::ToolExecutor;
+use crate::tools::{Tool, ToolRegistry};
+use crate::types::{ContentBlock, Message, MessagesRequest, MessagesResponse, StopReason, Usage};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Default `max_tokens` per request
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Default maximum number of requests per run
+const DEFAULT_MAX_TURNS: usize = 10;
+
+type RequestHook = Arc<dyn Fn(MessagesRequest) -> MessagesRequest + Send + Sync>;
+
+/// Why an agent run ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentStop {
+    /// Claude finished its turn (`end_turn`, `stop_sequence` or `refusal`)
+    Finished(Option<StopReason>),
+    /// The last response hit `max_tokens`
+    MaxTokens,
+    /// The turn limit was reached with work still pending
+    MaxTurns,
+    /// Cumulative input + output tokens reached the budget
+    TokenBudget,
+}
+
+/// Result of an agent run
+#[derive(Debug, Clone)]
+pub struct AgentRun {
+    /// Full transcript, including tool calls and results
+    ///
+    /// Add a user message and pass it to [`Agent::run_conversation`] to continue.
+    pub conversation: ConversationBuilder,
+
+    /// Every response received, in order
+    pub responses: Vec<MessagesResponse>,
+
+    /// Usage summed over all responses
+    pub usage: Usage,
+
+    /// Number of requests sent
+    pub turns: usize,
+
+    /// Why the run ended
+    pub stop: AgentStop,
+}
+
+impl AgentRun {
+    /// Text of the final response
+    pub fn text(&self) -> String {
+        self.responses
+            .last()
+            .map(|response| {
+                response
+                    .content
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text, .. } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Transcript messages
+    pub fn messages(&self) -> &[Message] {
+        self.conversation.messages()
+    }
+}
+
+/// Runs the tool-use loop against Claude
+#[derive(Clone)]
+pub struct Agent {
+    client: ClaudeClient,
+    model: String,
+    max_tokens: u32,
+    system: Option<String>,
+    tools: ToolRegistry,
+    executor: Option<ToolExecutor>,
+    max_turns: usize,
+    token_budget: Option<u64>,
+    request_hook: Option<RequestHook>,
+}
+
+impl Agent {
+    /// Create an agent for a model
+    pub fn new(client: ClaudeClient, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            system: None,
+            tools: ToolRegistry::new(),
+            executor: None,
+            max_turns: DEFAULT_MAX_TURNS,
+            token_budget: None,
+            request_hook: None,
+        }
+    }
+
+    /// Set the system prompt
+    pub fn with_system(mut self, prompt: impl Into<String>) -> Self {
+        self.system = Some(prompt.into());
+        self
+    }
+
+    /// Add a tool
+    pub fn with_tool(mut self, tool: impl Tool + 'static) -> Self {
+        self.tools.register(Arc::new(tool));
+        self.sync_executor();
+        self
+    }
+
+    /// Replace the tool registry
+    ///
+    /// An executor set with [`with_executor`](Self::with_executor) keeps
+    /// its limits and runs the new tools.
+    pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = tools;
+        self.sync_executor();
+        self
+    }
+
+    /// Run tools through a [`ToolExecutor`] (parallel calls, timeouts)
+    ///
+    /// The executor's tools are merged into the agent's (see
+    /// [`ToolRegistry::merge`]), and the executor's limits apply to every
+    /// tool, whether added before or after this call.
+    pub fn with_executor(mut self, executor: ToolExecutor) -> Self {
+        self.tools.merge(executor.registry());
+        self.executor = Some(executor);
+        self.sync_executor();
+        self
+    }
+
+    /// Point the executor at the agent's registry after it changes
+    fn sync_executor(&mut self) {
+        if let Some(executor) = self.executor.take() {
+            self.executor = Some(executor.with_registry(self.tools.clone()));
+        }
+    }
+
+    /// Set `max_tokens` for each request (default: 4096)
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Set the maximum number of requests per run (default: 10)
+    pub fn with_max_turns(mut self, max_turns: usize) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// Stop once cumulative input + output tokens reach `tokens`
+    pub fn with_token_budget(mut self, tokens: u64) -> Self {
+        self.token_budget = Some(tokens);
+        self
+    }
+
+    /// Adjust every request before it is sent (thinking, tool choice, metadata...)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use claude_sdk::agent::Agent;
+    /// use claude_sdk::ClaudeClient;
+    ///
+    /// let agent = Agent::new(ClaudeClient::anthropic("key"), "claude-sonnet-4-5-20250929")
+    ///     .with_request_hook(|request| request.with_thinking(2048));
+    /// ```
+    pub fn with_request_hook(
+        mut self,
+        hook: impl Fn(MessagesRequest) -> MessagesRequest + Send + Sync + 'static,
+    ) -> Self {
+        self.request_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// The agent's tools
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tools
+    }
+
+    /// A fresh conversation with the agent's system prompt and tool definitions
+    pub fn conversation(&self) -> ConversationBuilder {
+        let mut conversation = ConversationBuilder::new().with_tools(self.tools.definitions());
+        if let Some(system) = &self.system {
+            conversation = conversation.with_system(system.clone());
+        }
+        conversation
+    }
+
+    /// Run the loop for a single user prompt
+    pub async fn run(&self, prompt: impl Into<String>) -> Result<AgentRun> {
+        let mut conversation = self.conversation();
+        conversation.add_user_message(prompt);
+        self.run_conversation(conversation).await
+    }
+
+    /// Run the loop on an existing conversation
+    ///
+    /// The conversation's own system prompt and tool definitions are used
+    /// as-is; start from [`Agent::conversation`] to get the agent's.
+    pub async fn run_conversation(
+        &self,
+        mut conversation: ConversationBuilder,
+    ) -> Result<AgentRun> {
+        let mut responses = Vec::new();
+        let mut usage = Usage::default();
+        let mut turns = 0;
+        let mut container: Option<String> = None;
+
+        let stop = loop {
+            if turns >= self.max_turns {
+                break AgentStop::MaxTurns;
+            }
+            if self
+                .token_budget
+                .is_some_and(|budget| usage.total_tokens() >= budget)
+            {
+                break AgentStop::TokenBudget;
+            }
+
+            let mut request = conversation.build(&self.model, self.max_tokens);
+            // Code execution (and code waiting on programmatic tool calls)
+            // resumes only in the same container
+            if let Some(id) = &container {
+                request = request.with_container(id.clone());
+            }
+            if let Some(hook) = &self.request_hook {
+                request = hook(request);
+            }
+
+            turns += 1;
+            debug!("Agent turn {}", turns);
+            let response = self.client.send_message(request).await?;
+            usage.accumulate(&response.usage);
+            if let Some(used) = &response.container {
+                container = Some(used.id.clone());
+            }
+            conversation.add_assistant_with_blocks(response.content.clone());
+
+            let stop_reason = response.stop_reason;
+            match stop_reason {
+                Some(StopReason::ToolUse) => {
+                    let results = match &self.executor {
+                        Some(executor) => executor.execute(&response).await,
+                        None => self.tools.execute(&response).await,
+                    };
+                    responses.push(response);
+                    conversation.add_tool_results(results);
+                }
+                Some(StopReason::PauseTurn) => {
+                    // The assistant content is already in the transcript;
+                    // resending it lets the server tool resume.
+                    responses.push(response);
+                }
+                Some(StopReason::MaxTokens) => {
+                    responses.push(response);
+                    break AgentStop::MaxTokens;
+                }
+                other => {
+                    responses.push(response);
+                    break AgentStop::Finished(other);
+                }
+            }
+        };
+
+        info!(
+            "Agent finished after {} turns ({:?}, {} tokens)",
+            turns,
+            stop,
+            usage.total_tokens()
+        );
+
+        Ok(AgentRun {
+            conversation,
+            responses,
+            usage,
+            turns,
+            stop,
+        })
+    }
+}
+
+impl std::fmt::Debug for Agent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Agent")
+            .field("model", &self.model)
+            .field("max_tokens", &self.max_tokens)
+            .field("tools", &self.tools)
+            .field("max_turns", &self.max_turns)
+            .field("token_budget", &self.token_budget)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use crate::tools::{async_trait, ToolContext, ToolOutput};
+    use crate::types::{Role, ToolResultContent};
+    use serde_json::{json, Value};
+
+    struct Echo;
+
+    #[async_trait]
+    impl Tool for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the input back"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object", "properties": {"text": {"type": "string"}}})
+        }
+
+        async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+            ToolOutput::text(input["text"].as_str().unwrap_or_default())
+        }
+    }
+
+    /// Answers with its own name
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl Tool for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "Say the tool's name"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn call(&self, _input: Value, _ctx: &ToolContext) -> ToolOutput {
+            ToolOutput::text(self.0)
+        }
+    }
+
+    fn agent(server: &MockServer) -> Agent {
+        Agent::new(server.client(), "claude-sonnet-4-5-20250929")
+            .with_system("Be brief")
+            .with_tool(Echo)
+    }
+
+    #[tokio::test]
+    async fn test_runs_tools_until_end_turn() {
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::tool_use(
+                "toolu_1",
+                "echo",
+                json!({"text": "hi"}),
+            ))
+            .enqueue_message(MockResponse::text("Done"));
+
+        let run = agent(&server).run("Echo hi").await.unwrap();
+
+        assert_eq!(run.stop, AgentStop::Finished(Some(StopReason::EndTurn)));
+        assert_eq!(run.turns, 2);
+        assert_eq!(run.text(), "Done");
+        assert_eq!(run.usage.input_tokens, 20);
+        assert_eq!(run.usage.output_tokens, 20);
+        // user, assistant tool_use, user tool_result, assistant text
+        assert_eq!(run.messages().len(), 4);
+
+        let requests = server.message_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].tools.as_ref().unwrap().len(), 1);
+        let last = requests[1].messages.last().unwrap();
+        assert_eq!(last.role, Role::User);
+        match &last.content[0] {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content: Some(ToolResultContent::Text(text)),
+                ..
+            } => {
+                assert_eq!(tool_use_id, "toolu_1");
+                assert_eq!(text, "hi");
+            }
+            other => panic!("Expected ToolResult, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executor_runs_tools_added_before_and_after() {
+        let server = MockServer::start().await;
+        let calls: MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": (["before", "executor", "after"].map(|name| json!({
+                "type": "tool_use", "id": format!("toolu_{}", name), "name": name, "input": {}
+            }))),
+            "model": "claude-sonnet-4-5-20250929",
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 10, "output_tokens": 10}
+        }))
+        .unwrap();
+        server
+            .enqueue_message(MockResponse::message(calls))
+            .enqueue_message(MockResponse::text("Done"));
+
+        let agent = Agent::new(server.client(), "claude-sonnet-4-5-20250929")
+            .with_tool(Named("before"))
+            .with_executor(
+                ToolExecutor::new(ToolRegistry::new().with_tool(Named("executor")))
+                    .with_max_concurrency(2),
+            )
+            .with_tool(Named("after"));
+        assert_eq!(agent.tools().names(), ["before", "executor", "after"]);
+        agent.run("Call them all").await.unwrap();
+
+        let requests = server.message_requests();
+        assert_eq!(requests[0].tools.as_ref().unwrap().len(), 3);
+        let results = &requests[1].messages.last().unwrap().content;
+        let texts: Vec<&str> = results
+            .iter()
+            .map(|block| match block {
+                ContentBlock::ToolResult {
+                    content: Some(ToolResultContent::Text(text)),
+                    is_error,
+                    ..
+                } => {
+                    assert_ne!(*is_error, Some(true), "{}", text);
+                    text.as_str()
+                }
+                other => panic!("Expected ToolResult, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(texts, ["before", "executor", "after"]);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_max_turns() {
+        let server = MockServer::start().await;
+        for i in 0..3 {
+            server.enqueue_message(MockResponse::tool_use(
+                format!("toolu_{}", i),
+                "echo",
+                json!({"text": "again"}),
+            ));
+        }
+
+        let run = agent(&server).with_max_turns(2).run("Loop").await.unwrap();
+
+        assert_eq!(run.stop, AgentStop::MaxTurns);
+        assert_eq!(run.turns, 2);
+        assert_eq!(server.pending(crate::testing::MockRoute::Messages), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_token_budget() {
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::tool_use("toolu_1", "echo", json!({})))
+            .enqueue_message(MockResponse::text("unreachable"));
+
+        let run = agent(&server)
+            .with_token_budget(20)
+            .run("Spend")
+            .await
+            .unwrap();
+
+        assert_eq!(run.stop, AgentStop::TokenBudget);
+        assert_eq!(run.turns, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pause_turn_and_max_tokens() {
+        let server = MockServer::start().await;
+        let mut paused: MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_paused",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Searching..."}],
+            "model": "claude-sonnet-4-5-20250929",
+            "stop_reason": "pause_turn",
+            "container": {"id": "ctr_1", "expires_at": "2026-01-01T00:00:00Z"},
+            "usage": {"input_tokens": 5, "output_tokens": 5}
+        }))
+        .unwrap();
+        server.enqueue_message(MockResponse::message(paused.clone()));
+        paused.stop_reason = Some(StopReason::MaxTokens);
+        server.enqueue_message(MockResponse::message(paused));
+
+        let run = agent(&server).run("Search").await.unwrap();
+
+        assert_eq!(run.stop, AgentStop::MaxTokens);
+        assert_eq!(run.turns, 2);
+        // The paused assistant turn is sent back unchanged
+        let resumed = &server.message_requests()[1];
+        assert_eq!(resumed.messages.len(), 2);
+        assert_eq!(resumed.messages[1].role, Role::Assistant);
+        assert_eq!(resumed.container.as_deref(), Some("ctr_1"));
+    }
+}
@@ -0,0 +1,534 @@
+//! Local executor for the `bash` client tool
+//!
+//! [`BashTool`] advertises Anthropic's `bash_20250124` tool, but its calls
+//! come back as `tool_use` blocks that the client must run. [`BashExecutor`]
+//! is a [`Tool`] that runs them in a persistent local shell:
+//!
+//! - one `bash` process per executor, so `cd`, exported variables and shell
+//!   functions carry over between calls; use one executor per conversation
+//! - `{"restart": true}` replaces the shell with a fresh one
+//! - the shell starts in a working directory and is moved back into it if a
+//!   command leaves it
+//! - the environment is cleared except for `PATH`, `HOME` (the working
+//!   directory), `TERM=dumb` and variables you pass through explicitly
+//! - commands time out (default 60s); the shell is then restarted, as it
+//!   is when a call is cancelled mid-command
+//! - the shell runs in its own process group, which is killed with it on
+//!   timeout, restart or drop, so background jobs and other children do
+//!   not outlive it (Unix)
+//! - output (stdout and stderr, interleaved) is truncated past a limit
+//!
+//! The working-directory check is a guard rail, not a sandbox: commands can
+//! still read and write absolute paths. Combine it with a
+//! [`PermissionPolicy`](crate::permissions::PermissionPolicy), and run
+//! untrusted workloads in a container.
+//!
+//! Requires the `local-tools` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::bash::BashExecutor;
+//! use claude_sdk::agent::Agent;
+//! use claude_sdk::{ClaudeClient, ToolRegistry};
+//! use std::time::Duration;
+//!
+//! # async fn example(client: ClaudeClient) -> claude_sdk::Result<()> {
+//! let bash = BashExecutor::new("/tmp/workspace")
+//!     .with_timeout(Duration::from_secs(30))
+//!     .with_env_passthrough("CARGO_HOME");
+//!
+//! let run = Agent::new(client, "claude-sonnet-4-5-20250929")
+//!     .with_tools(ToolRegistry::new().with_tool(bash))
+//!     .run("How many Rust files are in this project?")
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::server_tools::BashTool;
+use crate::tools::{async_trait, Tool, ToolContext, ToolOutput};
+use crate::types::ToolDefinition;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Default time limit for one command
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default maximum number of output bytes returned to Claude
+const DEFAULT_MAX_OUTPUT: usize = 30_000;
+
+/// `PATH` used when the parent process has none
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+/// Marks the end of a command's output; followed by the exit code and cwd
+const SENTINEL: &str = "__claude_sdk_command_done__";
+
+/// Bytes of output kept past the limit so the sentinel can still be found
+const TAIL_BYTES: usize = 4096;
+
+/// Runs `bash` tool calls in a persistent local shell
+#[derive(Debug)]
+pub struct BashExecutor {
+    working_dir: PathBuf,
+    shell: PathBuf,
+    timeout: Duration,
+    max_output: usize,
+    env: Vec<(String, String)>,
+    session: Mutex<Option<Session>>,
+}
+
+impl BashExecutor {
+    /// Create an executor whose shell starts in `working_dir`
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            shell: PathBuf::from("bash"),
+            timeout: DEFAULT_TIMEOUT,
+            max_output: DEFAULT_MAX_OUTPUT,
+            env: Vec::new(),
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Set the time limit per command (default: 60s)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the maximum output returned per command, in bytes (default: 30,000)
+    pub fn with_max_output(mut self, max_output: usize) -> Self {
+        self.max_output = max_output;
+        self
+    }
+
+    /// Use a different shell binary (default: `bash` from `PATH`)
+    pub fn with_shell(mut self, shell: impl Into<PathBuf>) -> Self {
+        self.shell = shell.into();
+        self
+    }
+
+    /// Set an environment variable in the shell
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
+    /// Pass a variable from this process's environment through to the shell
+    ///
+    /// Does nothing if the variable is not set.
+    pub fn with_env_passthrough(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if let Ok(value) = std::env::var(&name) {
+            self.env.push((name, value));
+        }
+        self
+    }
+
+    /// The directory the shell is confined to
+    pub fn working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    fn spawn(&self) -> std::io::Result<Session> {
+        let path = std::env::var("PATH").unwrap_or_else(|_| DEFAULT_PATH.into());
+        let mut command = Command::new(&self.shell);
+        // Lead a new process group so every child can be killed with the shell
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command
+            .args(["--noprofile", "--norc"])
+            .current_dir(&self.working_dir)
+            .env_clear()
+            .env("PATH", path)
+            .env("HOME", &self.working_dir)
+            .env("TERM", "dumb")
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        debug!("Started bash session in {}", self.working_dir.display());
+        Ok(Session {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    async fn run(&self, command: &str) -> ToolOutput {
+        let mut session = self.session.lock().await;
+        // The shell is out of `session` until its sentinel has been read, so
+        // if this future is dropped mid-command the shell (and its process
+        // group) is dropped with it instead of answering the next call
+        let mut shell = match session.take() {
+            Some(shell) => shell,
+            None => match self.spawn() {
+                Ok(spawned) => spawned,
+                Err(e) => return ToolOutput::error(format!("Failed to start bash: {}", e)),
+            },
+        };
+
+        let result = tokio::time::timeout(self.timeout, shell.run(command, self.max_output)).await;
+        let finished = match result {
+            Ok(Ok(finished)) => finished,
+            Ok(Err(e)) => {
+                return ToolOutput::error(format!(
+                    "The shell exited ({}); it will be restarted on the next command",
+                    e
+                ));
+            }
+            Err(_) => {
+                warn!("bash command timed out after {:?}", self.timeout);
+                return ToolOutput::error(format!(
+                    "Command timed out after {:?}; the shell was restarted",
+                    self.timeout
+                ));
+            }
+        };
+
+        let mut text = finished.output;
+        if finished.omitted > 0 {
+            text.push_str(&format!(
+                "\n\n[output truncated: {} bytes omitted]",
+                finished.omitted
+            ));
+        }
+        let root = self
+            .working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.working_dir.clone());
+        if !Path::new(&finished.cwd).starts_with(&root) {
+            let reset = format!("cd -- {}\n", shell_quote(&root.to_string_lossy()));
+            if shell.stdin.write_all(reset.as_bytes()).await.is_ok() {
+                *session = Some(shell);
+            }
+            text.push_str(&format!(
+                "\n\n[working directory reset to {}; commands must stay inside it]",
+                self.working_dir.display()
+            ));
+        } else {
+            *session = Some(shell);
+        }
+        if finished.exit_code != 0 {
+            text.push_str(&format!("\n\nExit code: {}", finished.exit_code));
+        }
+        if text.is_empty() {
+            text = "(no output)".into();
+        }
+        ToolOutput::text(text)
+    }
+}
+
+#[async_trait]
+impl Tool for BashExecutor {
+    fn name(&self) -> &str {
+        "bash"
+    }
+
+    fn description(&self) -> &str {
+        "Run commands in a persistent bash shell"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {"type": "string", "description": "The bash command to run"},
+                "restart": {"type": "boolean", "description": "Restart the shell"}
+            }
+        })
+    }
+
+    fn tool_definition(&self) -> ToolDefinition {
+        BashTool::new().into()
+    }
+
+    async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+        if input["restart"].as_bool() == Some(true) {
+            *self.session.lock().await = None;
+            return ToolOutput::text("tool has been restarted.");
+        }
+        match input["command"].as_str() {
+            Some(command) if !command.trim().is_empty() => self.run(command).await,
+            _ => ToolOutput::error("Missing `command` (or `restart: true`)"),
+        }
+    }
+}
+
+/// A running shell
+#[derive(Debug)]
+struct Session {
+    // Held so the process is killed when the session is dropped
+    #[allow(dead_code)]
+    child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // The shell leads its process group, so its id is the group id.
+        // It has not been reaped yet, so the id cannot have been reused.
+        #[cfg(unix)]
+        if let Some(pid) = self.child.id() {
+            // SAFETY: killpg only sends a signal; it has no memory effects
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+/// Result of one command
+struct Finished {
+    output: String,
+    omitted: usize,
+    exit_code: i32,
+    cwd: String,
+}
+
+impl Session {
+    /// Run a command and read its output up to the sentinel
+    async fn run(&mut self, command: &str, max_output: usize) -> std::io::Result<Finished> {
+        // The group keeps `cd` and variables in this shell; stdin is closed
+        // so the command cannot swallow the sentinel.
+        let script = format!(
+            "{{\n{}\n}} < /dev/null 2>&1\n__claude_sdk_ec=$?; printf '\\n%s %s %s\\n' '{}' \"$__claude_sdk_ec\" \"$PWD\"\n",
+            command, SENTINEL
+        );
+        self.stdin.write_all(script.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        let marker = format!("\n{} ", SENTINEL);
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut omitted = 0;
+        let mut chunk = [0u8; 8192];
+        loop {
+            let read = self.stdout.read(&mut chunk).await?;
+            if read == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "end of output",
+                ));
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+
+            if let Some(at) = find(&buffer, marker.as_bytes()) {
+                let rest = &buffer[at + marker.len()..];
+                if let Some(end) = rest.iter().position(|&b| b == b'\n') {
+                    let status = String::from_utf8_lossy(&rest[..end]).into_owned();
+                    let (code, cwd) = status.split_once(' ').unwrap_or((&status, ""));
+                    buffer.truncate(at);
+                    if buffer.len() > max_output {
+                        omitted += buffer.len() - max_output;
+                        buffer.truncate(max_output);
+                    }
+                    return Ok(Finished {
+                        output: String::from_utf8_lossy(&buffer).into_owned(),
+                        omitted,
+                        exit_code: code.parse().unwrap_or(-1),
+                        cwd: cwd.to_string(),
+                    });
+                }
+            }
+
+            // Drop the middle of very long output, keeping enough of the
+            // end to find the sentinel
+            if buffer.len() > max_output + 2 * TAIL_BYTES {
+                let drop_end = buffer.len() - TAIL_BYTES;
+                omitted += drop_end - max_output;
+                buffer.drain(max_output..drop_end);
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Quote a string for use as a single shell word
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolResultContent;
+
+    fn text(output: &ToolOutput) -> &str {
+        match &output.content {
+            ToolResultContent::Text(text) => text,
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    fn workspace(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("claude_sdk_bash_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    async fn run(bash: &BashExecutor, command: &str) -> ToolOutput {
+        bash.call(
+            json!({"command": command}),
+            &ToolContext::new("toolu_1", "bash"),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_dropped_call_does_not_leak_output() {
+        let bash = BashExecutor::new(workspace("dropped"));
+        run(&bash, "export MARK=1").await;
+
+        // Cancel a call mid-command, as an executor timeout or abort would
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(200),
+            run(&bash, "sleep 1; echo stale"),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+
+        let output = run(&bash, "echo fresh ${MARK:-restarted}").await;
+        assert_eq!(text(&output), "fresh restarted\n");
+    }
+
+    #[tokio::test]
+    async fn test_session_persists_and_restarts() {
+        let dir = workspace("persist");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let bash = BashExecutor::new(&dir);
+
+        run(&bash, "cd sub && export GREETING=hi").await;
+        let output = run(&bash, "echo $GREETING; pwd").await;
+        assert_eq!(text(&output), format!("hi\n{}/sub\n", dir.display()));
+
+        let output = run(&bash, "echo partial >&2; false").await;
+        assert!(!output.is_error);
+        assert_eq!(text(&output), "partial\n\n\nExit code: 1");
+
+        let output = bash
+            .call(
+                json!({"restart": true}),
+                &ToolContext::new("toolu_2", "bash"),
+            )
+            .await;
+        assert_eq!(text(&output), "tool has been restarted.");
+        let output = run(&bash, "echo ${GREETING:-unset}; pwd").await;
+        assert_eq!(text(&output), format!("unset\n{}\n", dir.display()));
+    }
+
+    #[tokio::test]
+    async fn test_scrubs_environment() {
+        // Cargo sets CARGO_* variables for test processes; reading them
+        // avoids mutating the environment of this multi-threaded binary
+        let shared = std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "unset".into());
+        let dir = workspace("env");
+        let bash = BashExecutor::new(&dir)
+            .with_env("EXTRA", "1")
+            .with_env_passthrough("CARGO_PKG_NAME");
+
+        let output = run(
+            &bash,
+            "echo ${CARGO_MANIFEST_DIR:-none} ${CARGO_PKG_NAME:-unset} $EXTRA $HOME",
+        )
+        .await;
+        assert_eq!(
+            text(&output),
+            format!("none {} 1 {}\n", shared, dir.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resets_working_directory() {
+        let dir = workspace("confine");
+        let bash = BashExecutor::new(&dir);
+
+        let output = run(&bash, "cd /").await;
+        assert!(text(&output).contains("[working directory reset to"));
+        let output = run(&bash, "pwd").await;
+        assert_eq!(text(&output), format!("{}\n", dir.display()));
+    }
+
+    #[tokio::test]
+    async fn test_truncates_output() {
+        let bash = BashExecutor::new(workspace("truncate")).with_max_output(10);
+
+        let output = run(&bash, "seq 1 20000").await;
+        let text = text(&output);
+        assert!(text.starts_with("1\n2\n3\n4\n5\n"));
+        let omitted = text
+            .split("[output truncated: ")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .unwrap();
+        // seq 1 20000 prints 108,894 bytes
+        assert_eq!(omitted.parse::<usize>().unwrap(), 108_894 - 10);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_restarts_shell() {
+        let bash = BashExecutor::new(workspace("timeout")).with_timeout(Duration::from_millis(200));
+
+        run(&bash, "export MARK=1").await;
+        let output = run(&bash, "sleep 600 & echo $!").await;
+        let pid = text(&output).trim().to_string();
+        let output = run(&bash, "sleep 5").await;
+        assert!(output.is_error);
+        assert!(text(&output).contains("timed out"));
+
+        let output = run(&bash, "echo ${MARK:-fresh}").await;
+        assert_eq!(text(&output), "fresh\n");
+
+        // The background job went down with the shell's process group;
+        // SIGKILL is delivered asynchronously, so allow it a moment
+        #[cfg(target_os = "linux")]
+        {
+            let mut alive = running(&pid);
+            for _ in 0..50 {
+                if !alive {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                alive = running(&pid);
+            }
+            assert!(!alive, "background job {} outlived the shell", pid);
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = pid;
+    }
+
+    /// Whether a process exists and is not a zombie
+    #[cfg(target_os = "linux")]
+    fn running(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| {
+                stat.rsplit_once(") ")
+                    .map(|(_, rest)| !rest.starts_with('Z'))
+            })
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_advertises_bash_tool() {
+        let definition = serde_json::to_value(BashExecutor::new(".").tool_definition()).unwrap();
+        assert_eq!(definition, json!({"type": "bash_20250124", "name": "bash"}));
+    }
+}
@@ -181,8 +181,8 @@ use std::pin::Pin;
 use std::time::Duration;
 use tracing::{debug, info};
 
-/// Batch API endpoint
-const BATCH_API_URL: &str = "https://api.anthropic.com/v1/messages/batches";
+/// Batch API endpoint path
+const BATCH_API_PATH: &str = "/v1/messages/batches";
 
 /// API version
 const API_VERSION: &str = "2023-06-01";
@@ -288,6 +288,7 @@ pub struct BatchClient {
     http: Client,
     api_key: String,
     api_version: String,
+    base_url: String,
 }
 
 impl BatchClient {
@@ -297,9 +298,21 @@ impl BatchClient {
             http: Client::new(),
             api_key: api_key.into(),
             api_version: API_VERSION.to_string(),
+            base_url: crate::client::DEFAULT_BASE_URL.to_string(),
         }
     }
 
+    /// Override the API base URL (default: `https://api.anthropic.com`)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Endpoint URL for `suffix` (empty for the collection itself)
+    fn url(&self, suffix: &str) -> String {
+        format!("{}{}{}", self.base_url, BATCH_API_PATH, suffix)
+    }
+
     /// Create a new message batch
     ///
     /// # Limits
@@ -316,7 +329,7 @@ impl BatchClient {
 
         let response = self
             .http
-            .post(BATCH_API_URL)
+            .post(self.url(""))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", &self.api_version)
             .header("content-type", "application/json")
@@ -342,7 +355,7 @@ impl BatchClient {
     pub async fn retrieve(&self, batch_id: &str) -> Result<MessageBatch> {
         debug!("Retrieving batch: {}", batch_id);
 
-        let url = format!("{}/{}", BATCH_API_URL, batch_id);
+        let url = self.url(&format!("/{}", batch_id));
 
         let response = self
             .http
@@ -373,7 +386,7 @@ impl BatchClient {
     pub async fn list(&self, limit: Option<u32>) -> Result<Vec<MessageBatch>> {
         debug!("Listing batches");
 
-        let mut url = BATCH_API_URL.to_string();
+        let mut url = self.url("");
         if let Some(lim) = limit {
             url.push_str(&format!("?limit={}", lim));
         }
@@ -409,7 +422,7 @@ impl BatchClient {
     pub async fn cancel(&self, batch_id: &str) -> Result<MessageBatch> {
         info!("Canceling batch: {}", batch_id);
 
-        let url = format!("{}/{}/cancel", BATCH_API_URL, batch_id);
+        let url = self.url(&format!("/{}/cancel", batch_id));
 
         let response = self
             .http
@@ -575,6 +588,16 @@ mod tests {
     fn test_batch_client_creation() {
         let client = BatchClient::new("test-key");
         assert_eq!(client.api_key, "test-key");
+        assert_eq!(
+            client.url(""),
+            "https://api.anthropic.com/v1/messages/batches"
+        );
+
+        let client = client.with_base_url("http://127.0.0.1:8080/");
+        assert_eq!(
+            client.url("/abc"),
+            "http://127.0.0.1:8080/v1/messages/batches/abc"
+        );
     }
 
     #[test]
@@ -0,0 +1,655 @@
+//! Stream recording and deterministic replay (cassettes)
+//!
+//! A [`Cassette`] records the raw responses [`ClaudeClient`](crate::ClaudeClient)
+//! receives from the Anthropic API (JSON bodies, raw SSE bytes, and error
+//! responses) into a JSON file, keyed by a hash of the normalized request.
+//! In replay mode the client serves responses from the cassette instead of
+//! the network, feeding recorded SSE bytes through the same parser used for
+//! live streams. Tests can therefore exercise real stream shapes offline.
+//!
+//! Cassettes apply to the Anthropic backend only.
+//!
+//! # Recording
+//!
+//! ```rust,no_run
+//! use claude_sdk::cassette::{Cassette, Redaction};
+//! use claude_sdk::{ClaudeClient, Message, MessagesRequest};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let cassette = Arc::new(
+//!     Cassette::record("tests/cassettes/hello.json")
+//!         .with_redaction(Redaction::new().with_key("user_id").with_pattern("ACME Corp")),
+//! );
+//!
+//! let client = ClaudeClient::anthropic(std::env::var("ANTHROPIC_API_KEY")?)
+//!     .with_cassette(cassette);
+//!
+//! let request = MessagesRequest::new(
+//!     "claude-sonnet-4-5-20250929",
+//!     256,
+//!     vec![Message::user("Hello!")],
+//! );
+//! client.send_message(request).await?; // saved to hello.json
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Replaying
+//!
+//! ```rust,no_run
+//! use claude_sdk::cassette::Cassette;
+//! use claude_sdk::{ClaudeClient, Message, MessagesRequest};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let cassette = Arc::new(Cassette::replay("tests/cassettes/hello.json")?);
+//!
+//! // No API key or network access needed
+//! let client = ClaudeClient::anthropic("unused").with_cassette(cassette);
+//!
+//! let request = MessagesRequest::new(
+//!     "claude-sonnet-4-5-20250929",
+//!     256,
+//!     vec![Message::user("Hello!")],
+//! );
+//! let response = client.send_message(request).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Request Matching
+//!
+//! Requests are matched by [`Cassette::request_hash`]: the request is serialized,
+//! redacted, and hashed with its object keys in sorted order, together with the
+//! endpoint it was sent to. When the same request is recorded several times,
+//! replay returns the recordings in order and then keeps returning the last one.
+
+use crate::error::{Error, Result};
+use crate::types::MessagesRequest;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Whether a cassette records live traffic or replays recorded traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests to the API and save every response to the cassette file
+    Record,
+    /// Serve responses from the cassette file without network access
+    Replay,
+}
+
+/// API endpoint an interaction was recorded against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    /// `POST /v1/messages`
+    Messages,
+    /// `POST /v1/messages/count_tokens`
+    CountTokens,
+}
+
+impl Endpoint {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Endpoint::Messages => "messages",
+            Endpoint::CountTokens => "count_tokens",
+        }
+    }
+}
+
+/// Recorded response body
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", content = "body", rename_all = "snake_case")]
+pub enum RecordedBody {
+    /// JSON response body
+    Json(Value),
+    /// Raw server-sent event stream
+    Sse(String),
+    /// Any other body (e.g. a non-JSON error message)
+    Text(String),
+}
+
+/// A recorded HTTP response
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedResponse {
+    /// HTTP status code
+    pub status: u16,
+
+    /// Value of the `retry-after` header, if present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+
+    /// Response body
+    #[serde(flatten)]
+    pub body: RecordedBody,
+}
+
+impl RecordedResponse {
+    /// Body as text, as it was received on the wire
+    pub fn body_text(&self) -> String {
+        match &self.body {
+            RecordedBody::Json(value) => value.to_string(),
+            RecordedBody::Sse(text) | RecordedBody::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// A single recorded request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Interaction {
+    /// Hash of the normalized request (see [`Cassette::request_hash`])
+    pub request_hash: String,
+
+    /// Endpoint the request was sent to
+    pub endpoint: Endpoint,
+
+    /// The normalized, redacted request (for humans reading the cassette)
+    pub request: Value,
+
+    /// The recorded response
+    pub response: RecordedResponse,
+}
+
+/// On-disk cassette format
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// Redaction rules applied to requests and responses before they are recorded
+///
+/// Redaction is also applied before hashing, so a cassette recorded with one
+/// secret still matches requests that use a different one.
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::cassette::Redaction;
+///
+/// let redaction = Redaction::new()
+///     .with_key("user_id")          // redact values of any "user_id" field
+///     .with_pattern("sk-ant-")      // redact occurrences of this text
+///     .with_replacement("<hidden>");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    keys: Vec<String>,
+    patterns: Vec<String>,
+    replacement: String,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            patterns: Vec::new(),
+            replacement: "[REDACTED]".to_string(),
+        }
+    }
+}
+
+impl Redaction {
+    /// Create an empty set of redaction rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the value of every JSON object field named `key`
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.keys.push(key.into());
+        self
+    }
+
+    /// Replace every occurrence of `pattern` in string values and SSE bodies
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Set the replacement text (default: `[REDACTED]`)
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+
+    /// Apply the rules to a JSON value
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.keys.iter().any(|k| k == key) {
+                        *field = Value::String(self.replacement.clone());
+                    } else {
+                        self.redact_value(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::String(text) => *text = self.redact_text(text),
+            _ => {}
+        }
+    }
+
+    /// Apply the content patterns to a string
+    pub fn redact_text(&self, text: &str) -> String {
+        self.patterns
+            .iter()
+            .filter(|p| !p.is_empty())
+            .fold(text.to_string(), |text, pattern| {
+                text.replace(pattern.as_str(), &self.replacement)
+            })
+    }
+
+    /// Apply the rules to a raw SSE body, redacting each JSON `data:` line
+    fn redact_sse(&self, body: &str) -> String {
+        body.split_inclusive('\n')
+            .map(|line| {
+                let content = line.trim_end_matches(['\r', '\n']);
+                let ending = &line[content.len()..];
+                match content.strip_prefix("data:") {
+                    Some(data) => match serde_json::from_str::<Value>(data.trim_start()) {
+                        Ok(mut value) => {
+                            self.redact_value(&mut value);
+                            format!("data: {}{}", value, ending)
+                        }
+                        Err(_) => format!("{}{}", self.redact_text(content), ending),
+                    },
+                    None => format!("{}{}", self.redact_text(content), ending),
+                }
+            })
+            .collect()
+    }
+
+    fn redact_body(&self, body: RecordedBody) -> RecordedBody {
+        match body {
+            RecordedBody::Json(mut value) => {
+                self.redact_value(&mut value);
+                RecordedBody::Json(value)
+            }
+            RecordedBody::Sse(text) => RecordedBody::Sse(self.redact_sse(&text)),
+            RecordedBody::Text(text) => RecordedBody::Text(self.redact_text(&text)),
+        }
+    }
+}
+
+/// Records or replays API traffic for a [`ClaudeClient`](crate::ClaudeClient)
+///
+/// Attach a cassette with
+/// [`ClaudeClient::with_cassette`](crate::ClaudeClient::with_cassette).
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    redaction: Redaction,
+    interactions: Mutex<Vec<Interaction>>,
+    /// Replay position per `(request_hash)` key
+    cursors: Mutex<HashMap<String, usize>>,
+}
+
+impl Cassette {
+    /// Create a cassette that records to `path`
+    ///
+    /// The file is (over)written after every recorded interaction. Streaming
+    /// responses are recorded once their `message_stop` event has been read
+    /// (or the stream ends), so consumers may stop at `MessageStop`; a stream
+    /// dropped before that is not recorded, and a warning is logged.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            redaction: Redaction::default(),
+            interactions: Mutex::new(Vec::new()),
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Load a cassette from `path` for replay
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| cassette_error(&path, format!("Failed to read: {}", e)))?;
+        let file: CassetteFile = serde_json::from_str(&contents)
+            .map_err(|e| cassette_error(&path, format!("Failed to parse: {}", e)))?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            redaction: Redaction::default(),
+            interactions: Mutex::new(file.interactions),
+            cursors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Set the redaction rules
+    ///
+    /// Use the same rules for recording and replaying so request hashes match.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// The cassette mode
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Path of the cassette file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Snapshot of the interactions recorded (or loaded) so far
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    /// Hash of a request after normalization and redaction
+    ///
+    /// Returns 16 lowercase hex characters. The hash is stable across runs and
+    /// platforms: object keys are hashed in sorted order.
+    pub fn request_hash(&self, endpoint: Endpoint, request: &MessagesRequest) -> Result<String> {
+        let normalized = self.normalize(request)?;
+        Ok(hash_request(endpoint, &normalized))
+    }
+
+    fn normalize(&self, request: &MessagesRequest) -> Result<Value> {
+        let mut value = serde_json::to_value(request)?;
+        self.redaction.redact_value(&mut value);
+        Ok(value)
+    }
+
+    /// Record a response for `request` and save the cassette file
+    pub(crate) fn record_interaction(
+        &self,
+        endpoint: Endpoint,
+        request: &MessagesRequest,
+        response: RecordedResponse,
+    ) -> Result<()> {
+        let normalized = self.normalize(request)?;
+        let interaction = Interaction {
+            request_hash: hash_request(endpoint, &normalized),
+            endpoint,
+            request: normalized,
+            response: RecordedResponse {
+                body: self.redaction.redact_body(response.body),
+                ..response
+            },
+        };
+        debug!(
+            "Recording {} interaction {}",
+            endpoint.as_str(),
+            interaction.request_hash
+        );
+
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(interaction);
+        self.save(&interactions)
+    }
+
+    /// Find the next recorded response for `request`
+    pub(crate) fn replay_interaction(
+        &self,
+        endpoint: Endpoint,
+        request: &MessagesRequest,
+    ) -> Result<RecordedResponse> {
+        let hash = self.request_hash(endpoint, request)?;
+        let interactions = self.interactions.lock().unwrap();
+        let matches: Vec<&Interaction> = interactions
+            .iter()
+            .filter(|i| i.endpoint == endpoint && i.request_hash == hash)
+            .collect();
+
+        if matches.is_empty() {
+            return Err(cassette_error(
+                &self.path,
+                format!(
+                    "No recorded {} interaction for request {}",
+                    endpoint.as_str(),
+                    hash
+                ),
+            ));
+        }
+
+        let mut cursors = self.cursors.lock().unwrap();
+        let cursor = cursors.entry(hash.clone()).or_insert(0);
+        let interaction = matches[(*cursor).min(matches.len() - 1)];
+        *cursor += 1;
+
+        debug!("Replaying {} interaction {}", endpoint.as_str(), hash);
+        Ok(interaction.response.clone())
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                cassette_error(&self.path, format!("Failed to create {:?}: {}", parent, e))
+            })?;
+        }
+
+        let file = CassetteFile {
+            interactions: interactions.to_vec(),
+        };
+        std::fs::write(&self.path, serde_json::to_string_pretty(&file)?)
+            .map_err(|e| cassette_error(&self.path, format!("Failed to write: {}", e)))
+    }
+}
+
+pub(crate) fn cassette_error(path: &Path, message: String) -> Error {
+    Error::Cassette {
+        path: path.to_path_buf(),
+        message,
+    }
+}
+
+/// FNV-1a over the endpoint and the canonical JSON encoding of the request
+fn hash_request(endpoint: Endpoint, request: &Value) -> String {
+    let mut canonical = String::from(endpoint.as_str());
+    canonical.push('\n');
+    write_canonical(request, &mut canonical);
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in canonical.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// JSON encoding with object keys in sorted order
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Message;
+    use serde_json::json;
+
+    fn request(text: &str) -> MessagesRequest {
+        MessagesRequest::new("claude-sonnet-4-5-20250929", 256, vec![Message::user(text)])
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "claude-sdk-cassette-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_request_hash_is_stable_and_key_order_independent() {
+        let cassette = Cassette::record(temp_path("hash"));
+        let a = cassette
+            .request_hash(Endpoint::Messages, &request("Hello"))
+            .unwrap();
+        let b = cassette
+            .request_hash(Endpoint::Messages, &request("Hello"))
+            .unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 16);
+
+        let other = cassette
+            .request_hash(Endpoint::Messages, &request("Goodbye"))
+            .unwrap();
+        assert_ne!(a, other);
+
+        let count = cassette
+            .request_hash(Endpoint::CountTokens, &request("Hello"))
+            .unwrap();
+        assert_ne!(a, count);
+
+        let mut x = String::new();
+        let mut y = String::new();
+        write_canonical(&json!({"b": 1, "a": [{"d": 2, "c": 3}]}), &mut x);
+        write_canonical(&json!({"a": [{"c": 3, "d": 2}], "b": 1}), &mut y);
+        assert_eq!(x, y);
+        assert_eq!(x, r#"{"a":[{"c":3,"d":2}],"b":1}"#);
+    }
+
+    #[test]
+    fn test_redaction_ignores_secret_values_in_hash() {
+        let cassette = Cassette::record(temp_path("redact-hash")).with_redaction(
+            Redaction::new()
+                .with_pattern("secret-1")
+                .with_pattern("secret-2"),
+        );
+        let a = cassette
+            .request_hash(Endpoint::Messages, &request("token secret-1"))
+            .unwrap();
+        let b = cassette
+            .request_hash(Endpoint::Messages, &request("token secret-2"))
+            .unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_redact_value_and_sse() {
+        let redaction = Redaction::new().with_key("user_id").with_pattern("ACME");
+
+        let mut value = json!({
+            "metadata": {"user_id": "u-123"},
+            "messages": [{"content": "Report for ACME Corp"}]
+        });
+        redaction.redact_value(&mut value);
+        assert_eq!(value["metadata"]["user_id"], "[REDACTED]");
+        assert_eq!(
+            value["messages"][0]["content"],
+            "Report for [REDACTED] Corp"
+        );
+
+        let sse = "event: content_block_delta\r\n\
+                   data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"ACME rocks\"}}\r\n\r\n";
+        let redacted = redaction.redact_sse(sse);
+        assert!(redacted.starts_with("event: content_block_delta\r\n"));
+        assert!(redacted.contains("[REDACTED] rocks"));
+        assert!(redacted.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_record_then_replay_roundtrip() {
+        let path = temp_path("roundtrip");
+        let recorder = Cassette::record(&path).with_redaction(Redaction::new().with_key("id"));
+
+        let response = RecordedResponse {
+            status: 200,
+            retry_after: None,
+            body: RecordedBody::Json(json!({"id": "msg_1", "content": []})),
+        };
+        recorder
+            .record_interaction(Endpoint::Messages, &request("Hi"), response)
+            .unwrap();
+        recorder
+            .record_interaction(
+                Endpoint::Messages,
+                &request("Hi"),
+                RecordedResponse {
+                    status: 429,
+                    retry_after: Some(3),
+                    body: RecordedBody::Text("slow down".into()),
+                },
+            )
+            .unwrap();
+
+        let player = Cassette::replay(&path)
+            .unwrap()
+            .with_redaction(Redaction::new().with_key("id"));
+        assert_eq!(player.mode(), CassetteMode::Replay);
+        assert_eq!(player.interactions().len(), 2);
+
+        // Recordings of the same request are replayed in order, then the last repeats
+        let first = player
+            .replay_interaction(Endpoint::Messages, &request("Hi"))
+            .unwrap();
+        assert_eq!(first.status, 200);
+        assert_eq!(
+            first.body,
+            RecordedBody::Json(json!({"id": "[REDACTED]", "content": []}))
+        );
+
+        let second = player
+            .replay_interaction(Endpoint::Messages, &request("Hi"))
+            .unwrap();
+        assert_eq!(second.status, 429);
+        assert_eq!(second.retry_after, Some(3));
+        let third = player
+            .replay_interaction(Endpoint::Messages, &request("Hi"))
+            .unwrap();
+        assert_eq!(third.status, 429);
+
+        let missing = player.replay_interaction(Endpoint::Messages, &request("Other"));
+        assert!(matches!(missing, Err(Error::Cassette { .. })));
+
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(
+            Cassette::replay(&path),
+            Err(Error::Cassette { path: p, .. }) if p == path
+        ));
+    }
+
+    #[test]
+    fn test_recorded_response_format() {
+        let response = RecordedResponse {
+            status: 200,
+            retry_after: None,
+            body: RecordedBody::Sse("event: ping\ndata: {\"type\":\"ping\"}\n\n".into()),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["status"], 200);
+        assert_eq!(json["kind"], "sse");
+        assert!(json["body"].as_str().unwrap().starts_with("event: ping"));
+        assert!(json.get("retry_after").is_none());
+    }
+}
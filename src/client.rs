@@ -1,27 +1,39 @@
 //! Claude API client implementation
 
+use crate::cassette::{
+    cassette_error, Cassette, CassetteMode, Endpoint, RecordedBody, RecordedResponse,
+};
 use crate::error::{ApiErrorResponse, Error, Result};
-use crate::streaming::StreamEvent;
-use crate::types::{MessagesRequest, MessagesResponse, RateLimitInfo};
+use crate::streaming::{StreamError, StreamEvent};
+use crate::types::{Message, MessagesRequest, MessagesResponse, RateLimitInfo, Role, StopReason};
 use eventsource_stream::Eventsource;
 use futures::{Stream, StreamExt, TryStreamExt};
 use reqwest::{Client, StatusCode};
 use std::pin::Pin;
-use tracing::{debug, instrument};
+use std::sync::Arc;
+use tracing::{debug, instrument, warn};
 
 #[cfg(feature = "bedrock")]
 use aws_sdk_bedrockruntime::Client as BedrockClient;
 
-/// API endpoint for Anthropic
-const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
+/// Default base URL for the Anthropic API
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
 
-/// Token counting endpoint
-const TOKEN_COUNT_URL: &str = "https://api.anthropic.com/v1/messages/count_tokens";
+/// Messages endpoint path
+const MESSAGES_PATH: &str = "/v1/messages";
+
+/// Token counting endpoint path
+const TOKEN_COUNT_PATH: &str = "/v1/messages/count_tokens";
 
 /// Current API version
 const API_VERSION: &str = "2023-06-01";
 
+/// Default number of `pause_turn` continuations for
+/// [`ClaudeClient::send_message_continuing`]
+pub const DEFAULT_MAX_CONTINUATIONS: u32 = 10;
+
 /// Backend for Claude API
+#[derive(Clone)]
 pub enum ClaudeBackend {
     /// Anthropic API with API key
     Anthropic { api_key: String },
@@ -64,10 +76,13 @@ pub enum ClaudeBackend {
 ///     Ok(())
 /// }
 /// ```
+#[derive(Clone)]
 pub struct ClaudeClient {
     http: Client,
     backend: ClaudeBackend,
     api_version: String,
+    base_url: String,
+    cassette: Option<Arc<Cassette>>,
 }
 
 fn parse_rate_limit_headers(headers: &reqwest::header::HeaderMap) -> RateLimitInfo {
@@ -91,6 +106,266 @@ fn parse_rate_limit_headers(headers: &reqwest::header::HeaderMap) -> RateLimitIn
     }
 }
 
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get("retry-after")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse().ok())
+}
+
+/// Add the `anthropic-beta` header features in `request` depend on
+fn with_beta_header(
+    builder: reqwest::RequestBuilder,
+    request: &MessagesRequest,
+) -> reqwest::RequestBuilder {
+    match request.beta_header() {
+        Some(beta) => builder.header("anthropic-beta", beta),
+        None => builder,
+    }
+}
+
+/// Map a non-success status and its body onto an SDK error
+fn error_from_parts(status: StatusCode, retry_after: Option<u64>, body: String) -> Error {
+    if status == StatusCode::BAD_REQUEST {
+        return match serde_json::from_str::<ApiErrorResponse>(&body) {
+            Ok(api_error) => error_from_status(
+                status,
+                retry_after,
+                api_error.error.message,
+                Some(api_error.error.error_type),
+            ),
+            Err(_) => Error::InvalidRequest(body),
+        };
+    }
+    error_from_status(status, retry_after, body, None)
+}
+
+/// Map an error status onto the matching [`Error`] variant
+fn error_from_status(
+    status: StatusCode,
+    retry_after: Option<u64>,
+    message: String,
+    error_type: Option<String>,
+) -> Error {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => Error::RateLimit {
+            retry_after,
+            message,
+        },
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Authentication(message),
+        _ if status.is_server_error() => Error::Server {
+            status: status.as_u16(),
+            message,
+        },
+        _ => Error::Api {
+            status: status.as_u16(),
+            message,
+            error_type,
+        },
+    }
+}
+
+/// Map an in-stream `error` event onto the error its HTTP equivalent gives
+///
+/// The response itself succeeded, so the status comes from the error type;
+/// an `overloaded_error` mid-stream is as retryable as a 529 response.
+pub(crate) fn error_from_stream(error: StreamError) -> Error {
+    let status = match error.error_type.as_str() {
+        "invalid_request_error" => StatusCode::BAD_REQUEST,
+        "authentication_error" => StatusCode::UNAUTHORIZED,
+        "permission_error" => StatusCode::FORBIDDEN,
+        "not_found_error" => StatusCode::NOT_FOUND,
+        "request_too_large" => StatusCode::PAYLOAD_TOO_LARGE,
+        "rate_limit_error" => StatusCode::TOO_MANY_REQUESTS,
+        "api_error" => StatusCode::INTERNAL_SERVER_ERROR,
+        "overloaded_error" => StatusCode::from_u16(529).expect("529 is a valid status"),
+        _ => {
+            return Error::Api {
+                status: 0,
+                message: error.message,
+                error_type: Some(error.error_type),
+            }
+        }
+    };
+    error_from_status(status, None, error.message, Some(error.error_type))
+}
+
+/// Read a complete (non-streaming) response into its recorded form
+async fn recorded_response(response: reqwest::Response) -> Result<RecordedResponse> {
+    let status = response.status().as_u16();
+    let retry_after = parse_retry_after(response.headers());
+    let text = response.text().await?;
+    let body = match serde_json::from_str(&text) {
+        Ok(value) => RecordedBody::Json(value),
+        Err(_) => RecordedBody::Text(text),
+    };
+
+    Ok(RecordedResponse {
+        status,
+        retry_after,
+        body,
+    })
+}
+
+/// Warns when a recorded stream is dropped before its end
+///
+/// A partial stream would replay as a truncated response, so it is not
+/// written to the cassette.
+struct PendingRecording<'a> {
+    cassette: &'a Cassette,
+    finished: bool,
+}
+
+/// Finds the end of the `message_stop` frame in a growing SSE byte buffer
+///
+/// Most consumers stop pulling once they see `MessageStop`, so a recording
+/// cannot wait for the byte stream to end.
+#[derive(Default)]
+struct MessageStopScanner {
+    /// Start of the first frame not yet checked
+    frame_start: usize,
+}
+
+impl MessageStopScanner {
+    /// Whether `raw` now holds a complete `message_stop` frame
+    fn scan(&mut self, raw: &[u8]) -> bool {
+        while let Some(len) = frame_len(&raw[self.frame_start..]) {
+            let frame = &raw[self.frame_start..self.frame_start + len];
+            self.frame_start += len;
+            let is_stop = String::from_utf8_lossy(frame)
+                .lines()
+                .any(|line| line.strip_prefix("event:").map(str::trim) == Some("message_stop"));
+            if is_stop {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Length of the first complete SSE frame in `bytes`, including its blank line
+fn frame_len(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().enumerate().find_map(|(i, &b)| {
+        if b != b'\n' {
+            return None;
+        }
+        match bytes.get(i + 1..) {
+            Some([b'\n', ..]) => Some(i + 2),
+            Some([b'\r', b'\n', ..]) => Some(i + 3),
+            _ => None,
+        }
+    })
+}
+
+impl Drop for PendingRecording<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            warn!(
+                "Streaming response dropped before it finished; not recorded to cassette {:?}",
+                self.cassette.path()
+            );
+        }
+    }
+}
+
+fn recorded_status(cassette: &Cassette, recorded: &RecordedResponse) -> Result<StatusCode> {
+    StatusCode::from_u16(recorded.status).map_err(|e| {
+        cassette_error(
+            cassette.path(),
+            format!("Invalid recorded status {}: {}", recorded.status, e),
+        )
+    })
+}
+
+/// Turn a recorded `/v1/messages` response into a message or an error
+fn message_from_recording(
+    cassette: &Cassette,
+    recorded: RecordedResponse,
+) -> Result<MessagesResponse> {
+    let status = recorded_status(cassette, &recorded)?;
+    if status != StatusCode::OK {
+        return Err(error_from_parts(
+            status,
+            recorded.retry_after,
+            recorded.body_text(),
+        ));
+    }
+
+    match recorded.body {
+        RecordedBody::Json(value) => Ok(serde_json::from_value(value)?),
+        RecordedBody::Sse(text) | RecordedBody::Text(text) => Ok(serde_json::from_str(&text)?),
+    }
+}
+
+/// Turn a recorded token counting response into a count or an error
+fn token_count_from_recording(
+    cassette: &Cassette,
+    recorded: RecordedResponse,
+) -> Result<crate::types::TokenCount> {
+    let status = recorded_status(cassette, &recorded)?;
+    if !status.is_success() {
+        return Err(error_from_parts(
+            status,
+            recorded.retry_after,
+            recorded.body_text(),
+        ));
+    }
+    Ok(serde_json::from_str(&recorded.body_text())?)
+}
+
+/// Parse a raw SSE byte stream into typed stream events
+///
+/// Shared by live streams and cassette replay so both go through the same parser.
+fn parse_event_stream<S, B, E>(
+    byte_stream: S,
+) -> Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>
+where
+    S: Stream<Item = std::result::Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]> + Send + 'static,
+    E: std::fmt::Display + Send + 'static,
+{
+    let event_stream = byte_stream.eventsource();
+
+    // Map SSE events to our StreamEvent type
+    let stream = event_stream.map(|result| {
+        let event = result.map_err(|e| Error::StreamParse(e.to_string()))?;
+
+        // Skip empty data
+        if event.data.is_empty() {
+            return Ok(None);
+        }
+
+        // Parse based on event type
+        let stream_event = match event.event.as_str() {
+            "ping" => Some(StreamEvent::Ping),
+            "error" => {
+                let error: StreamError = serde_json::from_str(&event.data)
+                    .map_err(|e| Error::StreamParse(e.to_string()))?;
+                Some(StreamEvent::Error { error })
+            }
+            _ => {
+                // All other events (message_start, content_block_start, etc.)
+                // follow the standard format with type field
+                Some(
+                    serde_json::from_str::<StreamEvent>(&event.data).map_err(|e| {
+                        Error::StreamParse(format!(
+                            "Failed to parse event '{}': {}",
+                            event.event, e
+                        ))
+                    })?,
+                )
+            }
+        };
+
+        Ok(stream_event)
+    });
+
+    // Filter out None values
+    let filtered_stream = stream.try_filter_map(|opt| async move { Ok(opt) });
+
+    Box::pin(filtered_stream)
+}
+
 impl ClaudeClient {
     /// Create a new client for the Anthropic API
     ///
@@ -108,9 +383,67 @@ impl ClaudeClient {
                 api_key: api_key.into(),
             },
             api_version: API_VERSION.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            cassette: None,
         }
     }
 
+    /// Override the API base URL (default: `https://api.anthropic.com`)
+    ///
+    /// Useful for proxies, gateways, and the mock server in the
+    /// `testing` module. Only the Anthropic backend uses the base URL.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use claude_sdk::ClaudeClient;
+    ///
+    /// let client = ClaudeClient::anthropic("your-api-key")
+    ///     .with_base_url("http://127.0.0.1:8080");
+    /// ```
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Messages endpoint URL
+    fn messages_url(&self) -> String {
+        format!("{}{}", self.base_url, MESSAGES_PATH)
+    }
+
+    /// Token counting endpoint URL
+    fn token_count_url(&self) -> String {
+        format!("{}{}", self.base_url, TOKEN_COUNT_PATH)
+    }
+
+    /// Record or replay API traffic with a cassette
+    ///
+    /// See the [`cassette`](crate::cassette) module for details. Only the
+    /// Anthropic backend uses cassettes.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use claude_sdk::cassette::Cassette;
+    /// use claude_sdk::ClaudeClient;
+    /// use std::sync::Arc;
+    ///
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let cassette = Arc::new(Cassette::replay("tests/cassettes/chat.json")?);
+    /// let client = ClaudeClient::anthropic("unused").with_cassette(cassette);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// The attached cassette, if it is in the given mode
+    fn cassette_in(&self, mode: CassetteMode) -> Option<&Arc<Cassette>> {
+        self.cassette.as_ref().filter(|c| c.mode() == mode)
+    }
+
     /// Create a new client for AWS Bedrock
     ///
     /// This loads AWS credentials from the environment (AWS_PROFILE, AWS_ACCESS_KEY_ID, etc.)
@@ -150,6 +483,8 @@ impl ClaudeClient {
                 bedrock_client,
             },
             api_version: API_VERSION.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            cassette: None,
         })
     }
 
@@ -199,19 +534,35 @@ impl ClaudeClient {
         let mut request = request;
         request.stream = Some(false);
 
-        let response = self
-            .http
-            .post(ANTHROPIC_API_URL)
-            .header("x-api-key", api_key)
-            .header("anthropic-version", &self.api_version)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        if let Some(cassette) = self.cassette_in(CassetteMode::Replay) {
+            let recorded = cassette.replay_interaction(Endpoint::Messages, &request)?;
+            return message_from_recording(cassette, recorded);
+        }
+
+        let response = with_beta_header(
+            self.http
+                .post(self.messages_url())
+                .header("x-api-key", api_key)
+                .header("anthropic-version", &self.api_version)
+                .header("content-type", "application/json"),
+            &request,
+        )
+        .json(&request)
+        .send()
+        .await?;
 
         let status = response.status();
         debug!("Received response with status: {}", status);
 
+        if let Some(cassette) = self.cassette_in(CassetteMode::Record) {
+            let rate_limit_info = parse_rate_limit_headers(response.headers());
+            let recorded = recorded_response(response).await?;
+            cassette.record_interaction(Endpoint::Messages, &request, recorded.clone())?;
+            let mut messages_response = message_from_recording(cassette, recorded)?;
+            messages_response.rate_limit_info = Some(rate_limit_info);
+            return Ok(messages_response);
+        }
+
         // Handle different status codes
         match status {
             StatusCode::OK => {
@@ -220,52 +571,7 @@ impl ClaudeClient {
                 messages_response.rate_limit_info = Some(parse_rate_limit_headers(&headers));
                 Ok(messages_response)
             }
-            StatusCode::TOO_MANY_REQUESTS => {
-                // Parse retry-after header if present
-                let retry_after = response
-                    .headers()
-                    .get("retry-after")
-                    .and_then(|h| h.to_str().ok())
-                    .and_then(|s| s.parse().ok());
-
-                let error_body = response.text().await.unwrap_or_default();
-                Err(Error::RateLimit {
-                    retry_after,
-                    message: error_body,
-                })
-            }
-            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
-                let error_body = response.text().await.unwrap_or_default();
-                Err(Error::Authentication(error_body))
-            }
-            StatusCode::BAD_REQUEST => {
-                // Try to parse structured error
-                let error_text = response.text().await.unwrap_or_default();
-                if let Ok(api_error) = serde_json::from_str::<ApiErrorResponse>(&error_text) {
-                    Err(Error::Api {
-                        status: status.as_u16(),
-                        message: api_error.error.message,
-                        error_type: Some(api_error.error.error_type),
-                    })
-                } else {
-                    Err(Error::InvalidRequest(error_text))
-                }
-            }
-            _ if status.is_server_error() => {
-                let error_body = response.text().await.unwrap_or_default();
-                Err(Error::Server {
-                    status: status.as_u16(),
-                    message: error_body,
-                })
-            }
-            _ => {
-                let error_body = response.text().await.unwrap_or_default();
-                Err(Error::Api {
-                    status: status.as_u16(),
-                    message: error_body,
-                    error_type: None,
-                })
-            }
+            _ => Err(self.handle_error_response(status, response).await),
         }
     }
 
@@ -392,66 +698,101 @@ impl ClaudeClient {
         let mut request = request;
         request.stream = Some(true);
 
-        let response = self
-            .http
-            .post(ANTHROPIC_API_URL)
-            .header("x-api-key", api_key)
-            .header("anthropic-version", &self.api_version)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        if let Some(cassette) = self.cassette_in(CassetteMode::Replay) {
+            let recorded = cassette.replay_interaction(Endpoint::Messages, &request)?;
+            let status = recorded_status(cassette, &recorded)?;
+            if !status.is_success() {
+                return Err(error_from_parts(
+                    status,
+                    recorded.retry_after,
+                    recorded.body_text(),
+                ));
+            }
+
+            let body = bytes::Bytes::from(recorded.body_text());
+            let byte_stream = futures::stream::once(async move { Ok::<_, Error>(body) });
+            return Ok(parse_event_stream(byte_stream));
+        }
+
+        let response = with_beta_header(
+            self.http
+                .post(self.messages_url())
+                .header("x-api-key", api_key)
+                .header("anthropic-version", &self.api_version)
+                .header("content-type", "application/json"),
+            &request,
+        )
+        .json(&request)
+        .send()
+        .await?;
 
         let status = response.status();
         debug!("Received streaming response with status: {}", status);
 
+        let recorder = self.cassette_in(CassetteMode::Record).cloned();
+
         // Handle non-OK status codes
         if !status.is_success() {
+            if let Some(cassette) = recorder {
+                let recorded = recorded_response(response).await?;
+                cassette.record_interaction(Endpoint::Messages, &request, recorded.clone())?;
+                return Err(error_from_parts(
+                    status,
+                    recorded.retry_after,
+                    recorded.body_text(),
+                ));
+            }
             return Err(self.handle_error_response(status, response).await);
         }
 
         // Convert the response into an SSE stream
         let byte_stream = response.bytes_stream();
-        let event_stream = byte_stream.eventsource();
 
-        // Map SSE events to our StreamEvent type
-        let stream = event_stream.map(|result| {
-            let event = result.map_err(|e| Error::StreamParse(e.to_string()))?;
-
-            // Skip empty data
-            if event.data.is_empty() {
-                return Ok(None);
-            }
+        let Some(cassette) = recorder else {
+            return Ok(parse_event_stream(byte_stream));
+        };
 
-            // Parse based on event type
-            let stream_event = match event.event.as_str() {
-                "ping" => Some(StreamEvent::Ping),
-                "error" => {
-                    let error: crate::streaming::StreamError = serde_json::from_str(&event.data)
-                        .map_err(|e| Error::StreamParse(e.to_string()))?;
-                    Some(StreamEvent::Error { error })
-                }
-                _ => {
-                    // All other events (message_start, content_block_start, etc.)
-                    // follow the standard format with type field
-                    Some(
-                        serde_json::from_str::<StreamEvent>(&event.data).map_err(|e| {
-                            Error::StreamParse(format!(
-                                "Failed to parse event '{}': {}",
-                                event.event, e
-                            ))
-                        })?,
-                    )
+        // Tee the raw bytes into the cassette. The recording is written once
+        // the `message_stop` frame arrives, before it is passed on, since
+        // consumers usually stop pulling there; streams that end without one
+        // are recorded at their end.
+        let recording = async_stream::stream! {
+            let mut byte_stream = byte_stream;
+            let mut raw = Vec::new();
+            let mut scanner = MessageStopScanner::default();
+            let mut pending = PendingRecording {
+                cassette: &cassette,
+                finished: false,
+            };
+            let record = |raw: &[u8]| {
+                let recorded = RecordedResponse {
+                    status: status.as_u16(),
+                    retry_after: None,
+                    body: RecordedBody::Sse(String::from_utf8_lossy(raw).into_owned()),
+                };
+                if let Err(e) = cassette.record_interaction(Endpoint::Messages, &request, recorded) {
+                    warn!("Failed to record streaming interaction: {}", e);
                 }
             };
+            while let Some(chunk) = byte_stream.next().await {
+                if let Ok(bytes) = &chunk {
+                    if !pending.finished {
+                        raw.extend_from_slice(bytes);
+                        if scanner.scan(&raw) {
+                            pending.finished = true;
+                            record(&raw);
+                        }
+                    }
+                }
+                yield chunk;
+            }
+            if !pending.finished {
+                pending.finished = true;
+                record(&raw);
+            }
+        };
 
-            Ok(stream_event)
-        });
-
-        // Filter out None values
-        let filtered_stream = stream.try_filter_map(|opt| async move { Ok(opt) });
-
-        Ok(Box::pin(filtered_stream))
+        Ok(parse_event_stream(recording))
     }
 
     /// Send streaming message to AWS Bedrock
@@ -532,52 +873,9 @@ impl ClaudeClient {
         status: StatusCode,
         response: reqwest::Response,
     ) -> Error {
-        match status {
-            StatusCode::TOO_MANY_REQUESTS => {
-                let retry_after = response
-                    .headers()
-                    .get("retry-after")
-                    .and_then(|h| h.to_str().ok())
-                    .and_then(|s| s.parse().ok());
-
-                let error_body = response.text().await.unwrap_or_default();
-                Error::RateLimit {
-                    retry_after,
-                    message: error_body,
-                }
-            }
-            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
-                let error_body = response.text().await.unwrap_or_default();
-                Error::Authentication(error_body)
-            }
-            StatusCode::BAD_REQUEST => {
-                let error_text = response.text().await.unwrap_or_default();
-                if let Ok(api_error) = serde_json::from_str::<ApiErrorResponse>(&error_text) {
-                    Error::Api {
-                        status: status.as_u16(),
-                        message: api_error.error.message,
-                        error_type: Some(api_error.error.error_type),
-                    }
-                } else {
-                    Error::InvalidRequest(error_text)
-                }
-            }
-            _ if status.is_server_error() => {
-                let error_body = response.text().await.unwrap_or_default();
-                Error::Server {
-                    status: status.as_u16(),
-                    message: error_body,
-                }
-            }
-            _ => {
-                let error_body = response.text().await.unwrap_or_default();
-                Error::Api {
-                    status: status.as_u16(),
-                    message: error_body,
-                    error_type: None,
-                }
-            }
-        }
+        let retry_after = parse_retry_after(response.headers());
+        let error_body = response.text().await.unwrap_or_default();
+        error_from_parts(status, retry_after, error_body)
     }
 
     /// Count tokens for a request without sending it
@@ -607,15 +905,32 @@ impl ClaudeClient {
     pub async fn count_tokens(&self, request: MessagesRequest) -> Result<crate::types::TokenCount> {
         match &self.backend {
             ClaudeBackend::Anthropic { api_key } => {
-                let response = self
-                    .http
-                    .post(TOKEN_COUNT_URL)
-                    .header("x-api-key", api_key)
-                    .header("anthropic-version", &self.api_version)
-                    .header("content-type", "application/json")
-                    .json(&request)
-                    .send()
-                    .await?;
+                if let Some(cassette) = self.cassette_in(CassetteMode::Replay) {
+                    let recorded = cassette.replay_interaction(Endpoint::CountTokens, &request)?;
+                    return token_count_from_recording(cassette, recorded);
+                }
+
+                let response = with_beta_header(
+                    self.http
+                        .post(self.token_count_url())
+                        .header("x-api-key", api_key)
+                        .header("anthropic-version", &self.api_version)
+                        .header("content-type", "application/json"),
+                    &request,
+                )
+                .json(&request)
+                .send()
+                .await?;
+
+                if let Some(cassette) = self.cassette_in(CassetteMode::Record) {
+                    let recorded = recorded_response(response).await?;
+                    cassette.record_interaction(
+                        Endpoint::CountTokens,
+                        &request,
+                        recorded.clone(),
+                    )?;
+                    return token_count_from_recording(cassette, recorded);
+                }
 
                 let status = response.status();
                 if !status.is_success() {
@@ -668,6 +983,89 @@ impl ClaudeClient {
         .await
     }
 
+    /// Send a message, resuming it while the API returns `pause_turn`
+    ///
+    /// Long-running server tools (web search, code execution) can pause a
+    /// turn before it finishes. This sends the paused assistant content back
+    /// and re-issues the request, up to [`DEFAULT_MAX_CONTINUATIONS`] times,
+    /// and returns a single response with the content of every segment and
+    /// their combined [`Usage`](crate::types::Usage).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use claude_sdk::{ClaudeClient, MessagesRequest, Message};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ClaudeClient::anthropic("your-api-key");
+    ///
+    /// let request = MessagesRequest::new(
+    ///     "claude-sonnet-4-5-20250929",
+    ///     4096,
+    ///     vec![Message::user("Research this week's Rust releases")],
+    /// );
+    ///
+    /// let response = client.send_message_continuing(request).await?;
+    /// println!("Searches: {:?}", response.usage.server_tool_use);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_message_continuing(
+        &self,
+        request: MessagesRequest,
+    ) -> Result<MessagesResponse> {
+        self.send_message_with_continuations(request, DEFAULT_MAX_CONTINUATIONS)
+            .await
+    }
+
+    /// Send a message, resuming at most `max_continuations` paused turns
+    ///
+    /// If the limit is reached the merged response still has
+    /// `stop_reason: pause_turn`, so the caller can resume it later.
+    pub async fn send_message_with_continuations(
+        &self,
+        mut request: MessagesRequest,
+        max_continuations: u32,
+    ) -> Result<MessagesResponse> {
+        let mut merged = self.send_message(request.clone()).await?;
+        let mut continuations = 0;
+
+        while merged.stop_reason == Some(StopReason::PauseTurn) && continuations < max_continuations
+        {
+            continuations += 1;
+            debug!(continuations, "Resuming paused turn");
+
+            // The paused content so far is sent back as one assistant turn
+            match request.messages.last_mut() {
+                Some(last) if continuations > 1 && last.role == Role::Assistant => {
+                    last.content = merged.content.clone();
+                }
+                _ => request.messages.push(Message {
+                    role: Role::Assistant,
+                    content: merged.content.clone(),
+                }),
+            }
+
+            // Code execution must resume in the same container
+            if let Some(container) = &merged.container {
+                request.container = Some(container.id.clone());
+            }
+
+            let next = self.send_message(request.clone()).await?;
+            merged.usage.accumulate(&next.usage);
+            merged.content.extend(next.content);
+            merged.id = next.id;
+            merged.model = next.model;
+            merged.stop_reason = next.stop_reason;
+            merged.stop_sequence = next.stop_sequence;
+            merged.stop_details = next.stop_details;
+            merged.container = next.container.or(merged.container);
+            merged.rate_limit_info = next.rate_limit_info.or(merged.rate_limit_info);
+        }
+
+        Ok(merged)
+    }
+
     /// Send a streaming message with automatic retry on transient failures
     ///
     /// Note: Retries create a new stream, so partial results from failed attempts are lost.
@@ -726,10 +1124,18 @@ mod tests {
 
     #[test]
     fn test_token_count_url() {
+        let client = ClaudeClient::anthropic("test-key");
         assert_eq!(
-            TOKEN_COUNT_URL,
+            client.token_count_url(),
             "https://api.anthropic.com/v1/messages/count_tokens"
         );
+
+        let client = client.with_base_url("http://127.0.0.1:8080/");
+        assert_eq!(client.messages_url(), "http://127.0.0.1:8080/v1/messages");
+        assert_eq!(
+            client.token_count_url(),
+            "http://127.0.0.1:8080/v1/messages/count_tokens"
+        );
     }
 
     #[test]
@@ -747,6 +1153,367 @@ mod tests {
         assert_eq!(client.api_version, API_VERSION);
     }
 
+    fn cassette_request() -> MessagesRequest {
+        MessagesRequest::new(
+            "claude-sonnet-4-5-20250929",
+            256,
+            vec![crate::types::Message::user("Hello!")],
+        )
+    }
+
+    /// Write a cassette with one recorded response for `request` and load it for replay
+    fn replay_cassette(
+        name: &str,
+        request: MessagesRequest,
+        response: RecordedResponse,
+    ) -> Arc<Cassette> {
+        let path = std::env::temp_dir().join(format!(
+            "claude-sdk-client-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let recorder = Cassette::record(&path);
+        recorder
+            .record_interaction(Endpoint::Messages, &request, response)
+            .unwrap();
+        let cassette = Cassette::replay(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        Arc::new(cassette)
+    }
+
+    #[tokio::test]
+    async fn test_replay_streaming_from_cassette() {
+        let sse = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"type\":\"message\",\"role\":\"assistant\",\"content\":[],\"model\":\"claude-sonnet-4-5-20250929\",\"usage\":{\"input_tokens\":5,\"output_tokens\":0}}}\n\n",
+            "event: ping\n",
+            "data: {\"type\":\"ping\"}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi there\"}}\n\n",
+            "event: content_block_stop\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+
+        // Streaming requests are recorded with `stream: true`
+        let mut recorded_request = cassette_request();
+        recorded_request.stream = Some(true);
+        let cassette = replay_cassette(
+            "stream",
+            recorded_request,
+            RecordedResponse {
+                status: 200,
+                retry_after: None,
+                body: RecordedBody::Sse(sse.into()),
+            },
+        );
+
+        let client = ClaudeClient::anthropic("unused").with_cassette(cassette);
+        let events: Vec<StreamEvent> = client
+            .send_streaming(cassette_request())
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 6);
+        assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+        assert!(matches!(events[1], StreamEvent::Ping));
+        match &events[3] {
+            StreamEvent::ContentBlockDelta { delta, .. } => {
+                assert_eq!(delta.text(), Some("Hi there"))
+            }
+            other => panic!("Expected ContentBlockDelta, got {:?}", other),
+        }
+        assert!(matches!(events[5], StreamEvent::MessageStop));
+
+        // The non-streaming variant of the same request was never recorded
+        assert!(client.send_message(cassette_request()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_message_and_rate_limit_from_cassette() {
+        let mut request = cassette_request();
+        request.stream = Some(false);
+        let cassette = replay_cassette(
+            "message",
+            request.clone(),
+            RecordedResponse {
+                status: 200,
+                retry_after: None,
+                body: RecordedBody::Json(serde_json::json!({
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "text", "text": "Hello!"}],
+                    "model": "claude-sonnet-4-5-20250929",
+                    "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 5, "output_tokens": 2}
+                })),
+            },
+        );
+        let client = ClaudeClient::anthropic("unused").with_cassette(cassette);
+        let response = client.send_message(cassette_request()).await.unwrap();
+        assert_eq!(response.id, "msg_1");
+
+        let cassette = replay_cassette(
+            "rate-limit",
+            request,
+            RecordedResponse {
+                status: 429,
+                retry_after: Some(7),
+                body: RecordedBody::Text("rate limited".into()),
+            },
+        );
+        let client = ClaudeClient::anthropic("unused").with_cassette(cassette);
+        match client.send_message(cassette_request()).await {
+            Err(Error::RateLimit { retry_after, .. }) => assert_eq!(retry_after, Some(7)),
+            other => panic!("Expected RateLimit, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_records_only_finished_streams() {
+        use crate::testing::{MockResponse, MockServer};
+
+        let path = std::env::temp_dir().join(format!(
+            "claude-sdk-stream-recording-{}.json",
+            std::process::id()
+        ));
+        let cassette = Arc::new(Cassette::record(&path));
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::text("dropped"))
+            .enqueue_message(MockResponse::text("finished"));
+        let client = server.client().with_cassette(cassette.clone());
+
+        let mut stream = client.send_streaming(cassette_request()).await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        drop(stream);
+        assert!(cassette.interactions().is_empty());
+
+        // Stop pulling at `MessageStop`, as most consumers do
+        let mut stream = client.send_streaming(cassette_request()).await.unwrap();
+        while let Some(event) = stream.next().await {
+            if matches!(event.unwrap(), StreamEvent::MessageStop) {
+                break;
+            }
+        }
+        drop(stream);
+        assert_eq!(cassette.interactions().len(), 1);
+
+        let replay = Arc::new(Cassette::replay(&path).unwrap());
+        let client = ClaudeClient::anthropic("unused").with_cassette(replay);
+        let events: Vec<StreamEvent> = client
+            .send_streaming(cassette_request())
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        let text: String = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::ContentBlockDelta { delta, .. } => delta.text(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "finished");
+        assert!(matches!(events.last(), Some(StreamEvent::MessageStop)));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_stream_errors_map_like_http_errors() {
+        let error = |error_type: &str| {
+            error_from_stream(StreamError {
+                error_type: error_type.into(),
+                message: "failed".into(),
+            })
+        };
+
+        assert!(matches!(
+            error("overloaded_error"),
+            Error::Server { status: 529, .. }
+        ));
+        assert!(matches!(
+            error("api_error"),
+            Error::Server { status: 500, .. }
+        ));
+        assert!(matches!(error("rate_limit_error"), Error::RateLimit { .. }));
+        assert!(error("overloaded_error").is_retryable());
+        match error("invalid_request_error") {
+            Error::Api {
+                status, error_type, ..
+            } => {
+                assert_eq!(status, 400);
+                assert_eq!(error_type.as_deref(), Some("invalid_request_error"));
+            }
+            other => panic!("Expected Api, got {:?}", other),
+        }
+        match error("new_error") {
+            Error::Api { status, .. } => assert_eq!(status, 0),
+            other => panic!("Expected Api, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scans_for_message_stop_across_chunks() {
+        let mut scanner = MessageStopScanner::default();
+        let mut raw = b"event: ping\ndata: {}\n\nevent: message_st".to_vec();
+        assert!(!scanner.scan(&raw));
+        raw.extend_from_slice(b"op\r\ndata: {\"type\":\"message_stop\"}\r\n");
+        assert!(!scanner.scan(&raw));
+        raw.extend_from_slice(b"\r\n");
+        assert!(scanner.scan(&raw));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_recorded_status_is_a_cassette_error() {
+        let mut request = cassette_request();
+        request.stream = Some(false);
+        let cassette = replay_cassette(
+            "bad-status",
+            request,
+            RecordedResponse {
+                status: 42,
+                retry_after: None,
+                body: RecordedBody::Text(String::new()),
+            },
+        );
+        let client = ClaudeClient::anthropic("unused").with_cassette(cassette);
+        match client.send_message(cassette_request()).await {
+            Err(Error::Cassette { message, .. }) => {
+                assert!(
+                    message.contains("Invalid recorded status 42"),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!("Expected Cassette error, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_mcp_servers_send_beta_header() {
+        use crate::testing::{MockResponse, MockRoute, MockServer};
+
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::text("plain"))
+            .enqueue_message(MockResponse::text("with mcp"))
+            .enqueue(MockRoute::CountTokens, MockResponse::token_count(7));
+        let client = server.client();
+        let mcp_request = || {
+            cassette_request().with_mcp_server(crate::types::McpServerDefinition::new(
+                "docs",
+                "https://mcp.example.com/mcp",
+            ))
+        };
+
+        client.send_message(cassette_request()).await.unwrap();
+        client.send_message(mcp_request()).await.unwrap();
+        client.count_tokens(mcp_request()).await.unwrap();
+
+        let requests = server.requests();
+        assert!(!requests[0].headers.contains_key("anthropic-beta"));
+        for received in &requests[1..] {
+            assert_eq!(
+                received.headers.get("anthropic-beta").unwrap(),
+                crate::types::MCP_CLIENT_BETA
+            );
+        }
+        let sent = &server.message_requests()[1];
+        assert_eq!(sent.mcp_servers.as_ref().unwrap()[0].name, "docs");
+    }
+
+    #[cfg(feature = "testing")]
+    mod continuation {
+        use super::*;
+        use crate::testing::{MockResponse, MockServer};
+        use serde_json::json;
+
+        fn segment(id: &str, text: &str, stop_reason: &str, searches: u32) -> MessagesResponse {
+            serde_json::from_value(json!({
+                "id": id,
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "text", "text": text}],
+                "model": "claude-sonnet-4-5-20250929",
+                "stop_reason": stop_reason,
+                "usage": {
+                    "input_tokens": 10,
+                    "output_tokens": 5,
+                    "server_tool_use": {"web_search_requests": searches}
+                }
+            }))
+            .unwrap()
+        }
+
+        fn request() -> MessagesRequest {
+            MessagesRequest::new(
+                "claude-sonnet-4-5-20250929",
+                1024,
+                vec![Message::user("Research")],
+            )
+        }
+
+        #[tokio::test]
+        async fn test_resumes_paused_turns() {
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::message(segment("a", "one", "pause_turn", 2)));
+            server.enqueue_message(MockResponse::message(segment("b", "two", "pause_turn", 1)));
+            server.enqueue_message(MockResponse::message(segment("c", "three", "end_turn", 0)));
+
+            let response = server
+                .client()
+                .send_message_continuing(request())
+                .await
+                .unwrap();
+
+            assert_eq!(response.id, "c");
+            assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+            assert_eq!(response.content.len(), 3);
+            assert_eq!(response.usage.input_tokens, 30);
+            assert_eq!(response.usage.output_tokens, 15);
+            let server_usage = response.usage.server_tool_use.unwrap();
+            assert_eq!(server_usage.web_search_requests, Some(3));
+
+            // Each resumption sends all paused content as one assistant turn
+            let requests = server.message_requests();
+            assert_eq!(requests.len(), 3);
+            assert_eq!(requests[1].messages.len(), 2);
+            assert_eq!(requests[2].messages.len(), 2);
+            assert_eq!(requests[2].messages[1].role, Role::Assistant);
+            assert_eq!(requests[2].messages[1].content.len(), 2);
+        }
+
+        #[tokio::test]
+        async fn test_stops_at_continuation_limit() {
+            let server = MockServer::start().await;
+            for id in ["a", "b", "c"] {
+                server.enqueue_message(MockResponse::message(segment(id, id, "pause_turn", 1)));
+            }
+
+            let response = server
+                .client()
+                .send_message_with_continuations(request(), 1)
+                .await
+                .unwrap();
+
+            assert_eq!(response.stop_reason, Some(StopReason::PauseTurn));
+            assert_eq!(response.content.len(), 2);
+            assert_eq!(server.message_requests().len(), 2);
+        }
+    }
+
     #[tokio::test]
     #[cfg(feature = "bedrock")]
     #[ignore] // Requires AWS credentials
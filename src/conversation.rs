@@ -327,6 +327,41 @@ impl ConversationBuilder {
         self
     }
 
+    /// Add a rich tool result (images, documents, search results, errors)
+    ///
+    /// ```rust
+    /// use claude_sdk::{ConversationBuilder, ToolOutput};
+    ///
+    /// let mut conversation = ConversationBuilder::new();
+    /// conversation.add_tool_output(
+    ///     "toolu_1",
+    ///     ToolOutput::text("Screenshot of the login page:")
+    ///         .with(ToolOutput::image("image/png", [0x89, b'P', b'N', b'G'])),
+    /// );
+    /// ```
+    pub fn add_tool_output(
+        &mut self,
+        tool_use_id: impl Into<String>,
+        output: crate::tools::ToolOutput,
+    ) -> &mut Self {
+        self.messages
+            .push(Message::tool_output(tool_use_id, output));
+        self
+    }
+
+    /// Add several tool results in a single user message
+    ///
+    /// Every `tool_use` block in an assistant turn must be answered in the
+    /// next user message; pass the output of
+    /// [`ToolRegistry::execute`](crate::tools::ToolRegistry::execute) here.
+    pub fn add_tool_results(&mut self, results: Vec<ContentBlock>) -> &mut Self {
+        self.messages.push(Message {
+            role: Role::User,
+            content: results,
+        });
+        self
+    }
+
     /// Add a tool result with error flag
     pub fn add_tool_error(
         &mut self,
@@ -529,6 +564,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_add_tool_output() {
+        let mut conv = ConversationBuilder::new();
+        conv.add_tool_output(
+            "toolu_1",
+            crate::ToolOutput::document_text("log").into_error(),
+        );
+
+        let json = serde_json::to_value(&conv.messages()[0]).unwrap();
+        assert_eq!(json["content"][0]["is_error"], true);
+        assert_eq!(json["content"][0]["content"][0]["type"], "document");
+    }
+
+    #[test]
+    fn test_add_tool_results() {
+        let mut conv = ConversationBuilder::new();
+        conv.add_tool_results(vec![
+            crate::tools::ToolOutput::text("one").into_block("toolu_1"),
+            crate::tools::ToolOutput::error("two").into_block("toolu_2"),
+        ]);
+
+        assert_eq!(conv.messages().len(), 1);
+        assert_eq!(conv.messages()[0].role, Role::User);
+        assert_eq!(conv.messages()[0].content.len(), 2);
+    }
+
     #[test]
     fn test_build_request() {
         let mut conv = ConversationBuilder::new().with_system("Test system");
@@ -0,0 +1,167 @@
+//! Text editing shared by the file-editing tool executors
+//!
+//! [`TextEditorExecutor`](crate::text_editor::TextEditorExecutor) and
+//! [`MemoryExecutor`](crate::memory::MemoryExecutor) implement the same
+//! `view`, `str_replace` and `insert` commands over different storage. The
+//! argument parsing, line numbering and edits live here; each executor words
+//! its own messages around them.
+
+use serde_json::Value;
+
+/// Lines of context shown around an edit
+const SNIPPET_LINES: usize = 4;
+
+/// Result of a successful edit
+#[derive(Debug)]
+pub(crate) struct Edit {
+    /// The new contents
+    pub updated: String,
+    /// First changed line, 1-indexed
+    pub first: usize,
+    /// Last changed line, 1-indexed
+    pub last: usize,
+}
+
+/// Why `str_replace` made no change
+#[derive(Debug)]
+pub(crate) enum ReplaceError {
+    /// `old_str` does not occur
+    NotFound,
+    /// `old_str` occurs on each of these 1-indexed lines
+    Ambiguous(Vec<usize>),
+}
+
+/// A required string argument
+pub(crate) fn string_arg<'a>(input: &'a Value, name: &str) -> Result<&'a str, String> {
+    input
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing `{}` parameter", name))
+}
+
+/// Validate a 1-indexed `[start, end]` range; `end = -1` means the last line
+pub(crate) fn view_range(range: &[Value], len: usize) -> Result<(usize, usize), String> {
+    let (Some(start), Some(end)) = (
+        range.first().and_then(Value::as_i64),
+        range.get(1).and_then(Value::as_i64),
+    ) else {
+        return Err("`view_range` must be two integers".into());
+    };
+    if range.len() != 2 || start < 1 || start as usize > len.max(1) {
+        return Err(format!(
+            "Invalid `view_range` [{}, {}]: start must be between 1 and {}",
+            start, end, len
+        ));
+    }
+    let end = if end == -1 { len } else { end.max(0) as usize };
+    if end < start as usize || end > len {
+        return Err(format!(
+            "Invalid `view_range`: end must be -1 or between {} and {}",
+            start, len
+        ));
+    }
+    Ok((start as usize, end))
+}
+
+/// Lines prefixed with their numbers, counting from `first`
+pub(crate) fn number_lines(lines: &[&str], first: usize) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:6}\t{}", first + i, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Numbered lines `first..=last` of `content` with some context
+pub(crate) fn snippet(content: &str, first: usize, last: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = first.saturating_sub(SNIPPET_LINES).max(1);
+    let end = (last + SNIPPET_LINES).min(lines.len());
+    if start > end {
+        return String::new();
+    }
+    number_lines(&lines[start - 1..end], start)
+}
+
+/// Replace `old` with `new`, which must occur exactly once in `content`
+pub(crate) fn replace_once(content: &str, old: &str, new: &str) -> Result<Edit, ReplaceError> {
+    let line_of = |at: usize| content[..at].matches('\n').count() + 1;
+    let matches: Vec<usize> = content.match_indices(old).map(|(i, _)| i).collect();
+    let at = match matches.as_slice() {
+        [] => return Err(ReplaceError::NotFound),
+        [at] => *at,
+        many => {
+            return Err(ReplaceError::Ambiguous(
+                many.iter().map(|&i| line_of(i)).collect(),
+            ))
+        }
+    };
+
+    let first = line_of(at);
+    Ok(Edit {
+        updated: format!("{}{}{}", &content[..at], new, &content[at + old.len()..]),
+        first,
+        last: first + new.matches('\n').count(),
+    })
+}
+
+/// Insert `text` after line `after` (`0` for the top)
+///
+/// The inserted lines take the file's line ending, and the rest of the file
+/// is left byte for byte as it was. Returns `None` if `content` has fewer
+/// than `after` lines.
+pub(crate) fn insert_after(content: &str, after: usize, text: &str) -> Option<Edit> {
+    if after > content.lines().count() {
+        return None;
+    }
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut inserted: String = text.lines().flat_map(|line| [line, newline]).collect();
+
+    // Byte offset just past line `after`
+    let at = match after.checked_sub(1) {
+        None => 0,
+        Some(line) => content
+            .match_indices('\n')
+            .nth(line)
+            .map_or(content.len(), |(i, _)| i + 1),
+    };
+    // The last line had no line ending; move it before the new lines
+    if !inserted.is_empty()
+        && at == content.len()
+        && !content.is_empty()
+        && !content.ends_with('\n')
+    {
+        inserted.truncate(inserted.len() - newline.len());
+        inserted.insert_str(0, newline);
+    }
+
+    Some(Edit {
+        updated: format!("{}{}{}", &content[..at], inserted, &content[at..]),
+        first: after + 1,
+        last: after + text.lines().count().max(1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_after_keeps_line_endings() {
+        let insert = |content: &str, after: usize, text: &str| {
+            insert_after(content, after, text).unwrap().updated
+        };
+
+        assert_eq!(insert("a\r\nb\r\n", 1, "x\ny"), "a\r\nx\r\ny\r\nb\r\n");
+        assert_eq!(insert("a\nb\n", 0, "x"), "x\na\nb\n");
+        assert_eq!(insert("a\nb", 2, "x"), "a\nb\nx");
+        assert_eq!(insert("", 0, "x\ny"), "x\ny\n");
+        assert_eq!(insert("a\nb", 2, ""), "a\nb");
+        assert!(insert_after("a\n", 2, "x").is_none());
+    }
+}
@@ -18,6 +18,8 @@
 //! | [`Error::Http`] | HTTP client error | Depends |
 //! | [`Error::Json`] | JSON serialization error | No |
 //! | [`Error::StreamParse`] | SSE parsing error | No |
+//! | [`Error::Mcp`] | MCP server or transport failure | No |
+//! | [`Error::Cassette`] | Cassette file or replay failure | No |
 //!
 //! # Example: Basic Error Handling
 //!
@@ -181,7 +183,8 @@ pub enum Error {
     /// Check `error_type` for specific error categories like "invalid_request_error".
     #[error("API error ({status}): {message}")]
     Api {
-        /// HTTP status code
+        /// HTTP status code; `0` for an `error` event of an unknown type
+        /// inside a stream, which has no status of its own
         status: u16,
         /// Error message from the API
         message: String,
@@ -240,6 +243,42 @@ pub enum Error {
     /// May indicate a malformed response or connection issue.
     #[error("Stream parsing error: {0}")]
     StreamParse(String),
+
+    /// Structured output did not match the requested type.
+    ///
+    /// Returned by [`ClaudeClient::send_structured`](crate::ClaudeClient::send_structured)
+    /// once every repair attempt has been used up.
+    #[error("Structured output invalid after {attempts} attempt(s): {message}")]
+    StructuredOutput {
+        /// Number of requests sent, including repairs
+        attempts: u32,
+        /// The last parse error
+        message: String,
+    },
+
+    /// MCP server or transport failure.
+    ///
+    /// `code` is the JSON-RPC error code when the server answered with an
+    /// error, and `None` when the connection failed or timed out.
+    #[error("MCP error: {message}")]
+    Mcp {
+        /// JSON-RPC error code
+        code: Option<i64>,
+        /// Error message
+        message: String,
+    },
+
+    /// Cassette file or replay failure.
+    ///
+    /// The cassette file could not be read, parsed or written, or a replayed
+    /// request has no recorded interaction.
+    #[error("Cassette {path:?}: {message}")]
+    Cassette {
+        /// Path of the cassette file
+        path: std::path::PathBuf,
+        /// Error message
+        message: String,
+    },
 }
 
 impl Error {
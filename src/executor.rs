@@ -0,0 +1,558 @@
+//! Parallel tool execution
+//!
+//! Claude can request several tools in one turn (see
+//! [`prompts::with_parallel_tools`](crate::prompts::with_parallel_tools)).
+//! [`ToolRegistry::execute`] runs them one after another; [`ToolExecutor`]
+//! runs them concurrently while keeping the guarantees the API needs:
+//!
+//! - at most `max_concurrency` tools run at once
+//! - each call can have a timeout; a timed-out call is cancelled and
+//!   answered with an `is_error` result. The timeout starts once the
+//!   call's [permissions](crate::permissions) are resolved, so time spent
+//!   waiting for an approver does not count, and the
+//!   [output policy](crate::output_policy) runs after it, so summarizing
+//!   or uploading a long output is not timed either
+//! - tools marked serial run alone, after every earlier call has finished
+//!   and before any later call starts
+//! - results come back in `tool_use` order, one per call
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::executor::ToolExecutor;
+//! use claude_sdk::ToolRegistry;
+//! use std::time::Duration;
+//!
+//! # async fn example(registry: ToolRegistry, response: claude_sdk::MessagesResponse) {
+//! let executor = ToolExecutor::new(registry)
+//!     .with_max_concurrency(4)
+//!     .with_timeout(Duration::from_secs(30))
+//!     .with_tool_timeout("run_tests", Duration::from_secs(300))
+//!     .with_serial_tool("write_file");
+//!
+//! let results = executor.execute(&response).await;
+//! # }
+//! ```
+
+use crate::tools::{ToolOutput, ToolRegistry};
+use crate::types::{ContentBlock, MessagesResponse};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+/// Default number of tools run at once
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// One `tool_use` block to execute
+struct ToolCall {
+    id: String,
+    name: String,
+    input: Value,
+}
+
+/// Runs a turn's tool calls concurrently
+#[derive(Debug, Clone)]
+pub struct ToolExecutor {
+    registry: Arc<ToolRegistry>,
+    max_concurrency: usize,
+    timeout: Option<Duration>,
+    tool_timeouts: HashMap<String, Duration>,
+    serial_tools: HashSet<String>,
+}
+
+impl ToolExecutor {
+    /// Create an executor for the tools in `registry`
+    pub fn new(registry: ToolRegistry) -> Self {
+        Self {
+            registry: Arc::new(registry),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            timeout: None,
+            tool_timeouts: HashMap::new(),
+            serial_tools: HashSet::new(),
+        }
+    }
+
+    /// Set how many tools may run at once (default: 4, minimum: 1)
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Set a timeout for every tool call
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for one tool, overriding [`with_timeout`](Self::with_timeout)
+    pub fn with_tool_timeout(mut self, name: impl Into<String>, timeout: Duration) -> Self {
+        self.tool_timeouts.insert(name.into(), timeout);
+        self
+    }
+
+    /// Run this tool alone, never alongside other calls
+    ///
+    /// Use for tools with side effects that must not interleave, such as
+    /// file writes or database migrations.
+    pub fn with_serial_tool(mut self, name: impl Into<String>) -> Self {
+        self.serial_tools.insert(name.into());
+        self
+    }
+
+    /// Run the same limits over a different registry
+    pub fn with_registry(mut self, registry: ToolRegistry) -> Self {
+        self.registry = Arc::new(registry);
+        self
+    }
+
+    /// The tools this executor runs
+    pub fn registry(&self) -> &ToolRegistry {
+        &self.registry
+    }
+
+    fn timeout_for(&self, name: &str) -> Option<Duration> {
+        self.tool_timeouts.get(name).copied().or(self.timeout)
+    }
+
+    /// Execute every `tool_use` block in a response
+    ///
+    /// Returns one `tool_result` block per `tool_use`, in the same order.
+    pub async fn execute(&self, response: &MessagesResponse) -> Vec<ContentBlock> {
+        self.execute_until(response, std::future::pending()).await
+    }
+
+    /// Execute every `tool_use` block until `cancel` resolves
+    ///
+    /// When `cancel` resolves, running calls are aborted and every call
+    /// without a result is answered with an `is_error` result, so the
+    /// conversation stays valid.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use claude_sdk::executor::ToolExecutor;
+    /// # async fn example(executor: ToolExecutor, response: claude_sdk::MessagesResponse) {
+    /// let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<()>();
+    /// // Hand `cancel_tx` to whatever can interrupt the turn (Ctrl-C, client disconnect...)
+    /// let results = executor
+    ///     .execute_until(&response, async {
+    ///         let _ = cancel_rx.await;
+    ///     })
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn execute_until(
+        &self,
+        response: &MessagesResponse,
+        cancel: impl Future<Output = ()>,
+    ) -> Vec<ContentBlock> {
+        let calls: Vec<ToolCall> = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse {
+                    id, name, input, ..
+                } => Some(ToolCall {
+                    id: id.clone(),
+                    name: name.clone(),
+                    input: input.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let mut outputs: Vec<Option<ToolOutput>> = vec![None; calls.len()];
+        tokio::pin!(cancel);
+
+        'batches: for batch in self.batches(&calls) {
+            let semaphore = Arc::new(Semaphore::new(self.max_concurrency));
+            let mut tasks = JoinSet::new();
+
+            for index in batch {
+                let call = &calls[index];
+                let registry = self.registry.clone();
+                let semaphore = semaphore.clone();
+                let timeout = self.timeout_for(&call.name);
+                let (id, name, input) = (call.id.clone(), call.name.clone(), call.input.clone());
+
+                tasks.spawn(async move {
+                    // Waiting for an approver counts neither toward the
+                    // timeout nor against the concurrency limit
+                    let input = match registry.authorize(&id, &name, input).await {
+                        Ok(input) => input,
+                        Err(output) => return (index, output),
+                    };
+                    let permit = semaphore.acquire_owned().await;
+                    let call = registry.call_authorized(&id, &name, input);
+                    let output = match timeout {
+                        Some(limit) => match tokio::time::timeout(limit, call).await {
+                            Ok(output) => output,
+                            Err(_) => {
+                                warn!("Tool {} ({}) timed out after {:?}", name, id, limit);
+                                let output = ToolOutput::error(format!(
+                                    "Tool '{}' timed out after {:?}",
+                                    name, limit
+                                ));
+                                return (index, output);
+                            }
+                        },
+                        None => call.await,
+                    };
+                    drop(permit);
+                    // Summarizing or uploading a long output is neither
+                    // timed nor counted against the concurrency limit
+                    (index, registry.finish_output(&id, &name, output).await)
+                });
+            }
+
+            loop {
+                tokio::select! {
+                    joined = tasks.join_next() => match joined {
+                        Some(Ok((index, output))) => outputs[index] = Some(output),
+                        // Tool and approver panics are caught by the registry,
+                        // so this is an abort
+                        Some(Err(_)) => {}
+                        None => break,
+                    },
+                    _ = &mut cancel => {
+                        debug!("Tool execution cancelled");
+                        tasks.abort_all();
+                        break 'batches;
+                    }
+                }
+            }
+        }
+
+        calls
+            .into_iter()
+            .zip(outputs)
+            .map(|(call, output)| {
+                output
+                    .unwrap_or_else(|| {
+                        ToolOutput::error(format!("Tool '{}' was cancelled", call.name))
+                    })
+                    .into_block(call.id)
+            })
+            .collect()
+    }
+
+    /// Split calls into batches that may run concurrently
+    ///
+    /// Consecutive non-serial calls share a batch; each serial call gets
+    /// its own. Batches run one after another.
+    fn batches(&self, calls: &[ToolCall]) -> Vec<Vec<usize>> {
+        let mut batches = Vec::new();
+        let mut current = Vec::new();
+        for (index, call) in calls.iter().enumerate() {
+            if self.serial_tools.contains(&call.name) {
+                if !current.is_empty() {
+                    batches.push(std::mem::take(&mut current));
+                }
+                batches.push(vec![index]);
+            } else {
+                current.push(index);
+            }
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+        batches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{async_trait, Tool, ToolContext};
+    use crate::types::ToolResultContent;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    /// Sleeps for `input.ms` and tracks how many calls overlap
+    #[derive(Default)]
+    struct Sleep {
+        name: &'static str,
+        active: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl Sleep {
+        fn named(name: &'static str, active: &Arc<AtomicUsize>, peak: &Arc<AtomicUsize>) -> Self {
+            Self {
+                name,
+                active: active.clone(),
+                peak: peak.clone(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Tool for Sleep {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "Sleep for a while"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object", "properties": {"ms": {"type": "integer"}}})
+        }
+
+        async fn call(&self, input: Value, ctx: &ToolContext) -> ToolOutput {
+            let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            let _guard = Active(self.active.clone());
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            let ms = input["ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            ToolOutput::text(format!("{} overlapped {}", ctx.tool_use_id, now))
+        }
+    }
+
+    /// Decrements the active count even when the call is dropped mid-flight
+    struct Active(Arc<AtomicUsize>);
+
+    impl Drop for Active {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    fn response_with(calls: &[(&str, &str, u64)]) -> MessagesResponse {
+        let content: Vec<Value> = calls
+            .iter()
+            .map(|(id, name, ms)| {
+                json!({"type": "tool_use", "id": id, "name": name, "input": {"ms": ms}})
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": content,
+            "model": "claude-sonnet-4-5-20250929",
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 1, "output_tokens": 1}
+        }))
+        .unwrap()
+    }
+
+    fn result(block: &ContentBlock) -> (&str, &str, bool) {
+        match block {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content: Some(ToolResultContent::Text(text)),
+                is_error,
+            } => (tool_use_id, text, is_error.unwrap_or(false)),
+            other => panic!("Expected text ToolResult, got {:?}", other),
+        }
+    }
+
+    fn counters() -> (Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        (Arc::default(), Arc::default())
+    }
+
+    #[tokio::test]
+    async fn test_runs_in_parallel_and_keeps_order() {
+        let (active, peak) = counters();
+        let executor =
+            ToolExecutor::new(ToolRegistry::new().with_tool(Sleep::named("sleep", &active, &peak)));
+        // Later calls finish first
+        let response = response_with(&[
+            ("t1", "sleep", 120),
+            ("t2", "sleep", 80),
+            ("t3", "sleep", 40),
+            ("t4", "sleep", 10),
+        ]);
+
+        let start = Instant::now();
+        let results = executor.execute(&response).await;
+
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+        let ids: Vec<_> = results.iter().map(|r| result(r).0).collect();
+        assert_eq!(ids, vec!["t1", "t2", "t3", "t4"]);
+    }
+
+    #[tokio::test]
+    async fn test_respects_concurrency_limit() {
+        let (active, peak) = counters();
+        let executor =
+            ToolExecutor::new(ToolRegistry::new().with_tool(Sleep::named("sleep", &active, &peak)))
+                .with_max_concurrency(2);
+        let response = response_with(&[
+            ("t1", "sleep", 20),
+            ("t2", "sleep", 20),
+            ("t3", "sleep", 20),
+            ("t4", "sleep", 20),
+            ("t5", "sleep", 20),
+        ]);
+
+        let results = executor.execute(&response).await;
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_becomes_error_result() {
+        let (active, peak) = counters();
+        let executor = ToolExecutor::new(
+            ToolRegistry::new()
+                .with_tool(Sleep::named("slow", &active, &peak))
+                .with_tool(Sleep::named("fast", &active, &peak)),
+        )
+        .with_timeout(Duration::from_secs(5))
+        .with_tool_timeout("slow", Duration::from_millis(20));
+        let response = response_with(&[("t1", "slow", 5_000), ("t2", "fast", 1)]);
+
+        let start = Instant::now();
+        let results = executor.execute(&response).await;
+
+        assert!(start.elapsed() < Duration::from_secs(2));
+        let (id, text, is_error) = result(&results[0]);
+        assert_eq!(id, "t1");
+        assert!(is_error);
+        assert!(text.contains("timed out"));
+        assert!(!result(&results[1]).2);
+        // The timed-out call was dropped, not left running
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_approval_time_is_not_timed() {
+        use crate::permissions::{Approval, PermissionPolicy, PermissionRule};
+
+        let (active, peak) = counters();
+        let policy = PermissionPolicy::new()
+            .with_rule(PermissionRule::ask("sleep"))
+            .with_approver(|_| async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Approval::Approve
+            });
+        let executor = ToolExecutor::new(
+            ToolRegistry::new()
+                .with_tool(Sleep::named("sleep", &active, &peak))
+                .with_permissions(policy),
+        )
+        .with_timeout(Duration::from_millis(50));
+        let response = response_with(&[("t1", "sleep", 1), ("t2", "sleep", 1)]);
+
+        let results = executor.execute(&response).await;
+
+        for block in &results {
+            let (_, text, is_error) = result(block);
+            assert!(!is_error, "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_approver_is_reported() {
+        use crate::permissions::{PermissionPolicy, PermissionRule};
+
+        let (active, peak) = counters();
+        let policy = PermissionPolicy::new()
+            .with_rule(PermissionRule::ask("sleep"))
+            .with_approver(|_| async { panic!("approver failed") });
+        let executor = ToolExecutor::new(
+            ToolRegistry::new()
+                .with_tool(Sleep::named("sleep", &active, &peak))
+                .with_permissions(policy),
+        );
+        let response = response_with(&[("t1", "sleep", 1)]);
+
+        let results = executor.execute(&response).await;
+
+        let (_, text, is_error) = result(&results[0]);
+        assert!(is_error);
+        assert_eq!(text, "Permission check for tool 'sleep' panicked");
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_output_policy_is_not_timed() {
+        use crate::output_policy::OutputPolicy;
+        use crate::testing::{MockResponse, MockServer};
+
+        let server = MockServer::start().await;
+        server.enqueue_message(MockResponse::text("slept").with_delay(Duration::from_millis(150)));
+        let (active, peak) = counters();
+        let executor = ToolExecutor::new(
+            ToolRegistry::new()
+                .with_tool(Sleep::named("sleep", &active, &peak))
+                .with_output_policy(OutputPolicy::summarize(
+                    5,
+                    server.client(),
+                    "claude-haiku-4-5",
+                )),
+        )
+        .with_timeout(Duration::from_millis(50));
+        let response = response_with(&[("t1", "sleep", 1)]);
+
+        let results = executor.execute(&response).await;
+
+        let (_, text, is_error) = result(&results[0]);
+        assert!(!is_error, "{}", text);
+        assert!(text.contains("summarized]"), "{}", text);
+        assert_eq!(server.message_requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_serial_tools_run_alone() {
+        let (active, peak) = counters();
+        let executor = ToolExecutor::new(
+            ToolRegistry::new()
+                .with_tool(Sleep::named("read", &active, &peak))
+                .with_tool(Sleep::named("write", &active, &peak)),
+        )
+        .with_serial_tool("write");
+        let response = response_with(&[
+            ("t1", "read", 30),
+            ("t2", "read", 30),
+            ("t3", "write", 10),
+            ("t4", "read", 30),
+        ]);
+
+        let results = executor.execute(&response).await;
+
+        assert_eq!(result(&results[0]).1, "t1 overlapped 1");
+        assert_eq!(result(&results[2]).1, "t3 overlapped 1");
+        assert_eq!(result(&results[3]).1, "t4 overlapped 1");
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_answers_every_call() {
+        let (active, peak) = counters();
+        let executor = ToolExecutor::new(
+            ToolRegistry::new()
+                .with_tool(Sleep::named("sleep", &active, &peak))
+                .with_tool(Sleep::named("write", &active, &peak)),
+        )
+        .with_serial_tool("write");
+        let response = response_with(&[
+            ("t1", "sleep", 1),
+            ("t2", "sleep", 5_000),
+            ("t3", "write", 1),
+        ]);
+
+        let results = executor
+            .execute_until(&response, tokio::time::sleep(Duration::from_millis(50)))
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert!(!result(&results[0]).2);
+        for block in &results[1..] {
+            let (_, text, is_error) = result(block);
+            assert!(is_error);
+            assert!(text.contains("cancelled"));
+        }
+    }
+}
@@ -11,8 +11,8 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tracing::debug;
 
-/// Files API endpoint
-const FILES_API_URL: &str = "https://api.anthropic.com/v1/files";
+/// Files API endpoint path
+const FILES_API_PATH: &str = "/v1/files";
 
 /// Beta header for Files API
 const FILES_BETA_HEADER: &str = "files-api-2025-04-14";
@@ -62,6 +62,7 @@ pub struct FilesClient {
     http: Client,
     api_key: String,
     api_version: String,
+    base_url: String,
 }
 
 impl FilesClient {
@@ -71,9 +72,21 @@ impl FilesClient {
             http: Client::new(),
             api_key: api_key.into(),
             api_version: "2023-06-01".to_string(),
+            base_url: crate::client::DEFAULT_BASE_URL.to_string(),
         }
     }
 
+    /// Override the API base URL (default: `https://api.anthropic.com`)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Endpoint URL for `suffix` (empty for the collection itself)
+    fn url(&self, suffix: &str) -> String {
+        format!("{}{}{}", self.base_url, FILES_API_PATH, suffix)
+    }
+
     /// Upload a file
     ///
     /// # Arguments
@@ -103,14 +116,39 @@ impl FilesClient {
             .and_then(|n| n.to_str())
             .ok_or_else(|| Error::InvalidRequest("Invalid filename".into()))?;
 
+        self.upload_bytes(filename, file_bytes).await
+    }
+
+    /// Upload in-memory content as a file named `filename`
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use claude_sdk::files::FilesClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = FilesClient::new("your-api-key");
+    /// let file = client.upload_bytes("report.csv", b"region,total\n".to_vec()).await?;
+    /// println!("File ID: {}", file.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_bytes(
+        &self,
+        filename: impl Into<String>,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<FileMetadata> {
+        let filename = filename.into();
+        let bytes = bytes.into();
+        debug!("Uploading {} bytes as {}", bytes.len(), filename);
+
         let form = reqwest::multipart::Form::new().part(
             "file",
-            reqwest::multipart::Part::bytes(file_bytes).file_name(filename.to_string()),
+            reqwest::multipart::Part::bytes(bytes).file_name(filename),
         );
 
         let response = self
             .http
-            .post(FILES_API_URL)
+            .post(self.url(""))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", &self.api_version)
             .header("anthropic-beta", FILES_BETA_HEADER)
@@ -138,7 +176,7 @@ impl FilesClient {
 
         let response = self
             .http
-            .get(FILES_API_URL)
+            .get(self.url(""))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", &self.api_version)
             .header("anthropic-beta", FILES_BETA_HEADER)
@@ -168,7 +206,7 @@ impl FilesClient {
     pub async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata> {
         debug!("Getting metadata for file: {}", file_id);
 
-        let url = format!("{}/{}", FILES_API_URL, file_id);
+        let url = self.url(&format!("/{}", file_id));
 
         let response = self
             .http
@@ -197,7 +235,7 @@ impl FilesClient {
     pub async fn delete(&self, file_id: &str) -> Result<()> {
         debug!("Deleting file: {}", file_id);
 
-        let url = format!("{}/{}", FILES_API_URL, file_id);
+        let url = self.url(&format!("/{}", file_id));
 
         let response = self
             .http
@@ -228,7 +266,7 @@ impl FilesClient {
     pub async fn download(&self, file_id: &str) -> Result<Vec<u8>> {
         debug!("Downloading file: {}", file_id);
 
-        let url = format!("{}/{}/content", FILES_API_URL, file_id);
+        let url = self.url(&format!("/{}/content", file_id));
 
         let response = self
             .http
@@ -262,6 +300,10 @@ mod tests {
     fn test_files_client_creation() {
         let client = FilesClient::new("test-key");
         assert_eq!(client.api_key, "test-key");
+        assert_eq!(client.url(""), "https://api.anthropic.com/v1/files");
+
+        let client = client.with_base_url("http://127.0.0.1:8080/");
+        assert_eq!(client.url("/abc"), "http://127.0.0.1:8080/v1/files/abc");
     }
 
     // Integration tests require API key
@@ -62,6 +62,13 @@
 //! | `anthropic` | Yes | Enable Anthropic API support |
 //! | `bedrock` | No | Enable AWS Bedrock support |
 //! | `repl` | No | Include interactive REPL binary |
+//! | `relay` | No | Relay Claude streams to browsers as SSE (axum) |
+//! | `testing` | No | Mock Claude server for offline tests (axum) |
+//! | `macros` | No | `#[tool]` attribute for defining tools from functions |
+//! | `schemars` | No | Derive tool and output schemas from Rust types |
+//! | `validation` | No | Local JSON Schema validation of tool inputs and structured outputs |
+//! | `local-tools` | No | Local executors for Anthropic-defined client tools (`bash`, text editor) |
+//! | `mcp` | No | Use tools from Model Context Protocol servers and serve tools to MCP hosts |
 //! | `full` | No | Enable all features |
 //!
 //! To enable AWS Bedrock support:
@@ -169,18 +176,34 @@
 //!
 //! ## Modules
 //!
+//! - [`agent`] - Agent loop that runs multi-turn tool use to completion
 //! - [`client`] - API client for Anthropic and AWS Bedrock
 //! - [`types`] - Request/response types and content blocks
 //! - [`streaming`] - SSE streaming types and event parsing
 //! - [`conversation`] - Multi-turn conversation builder
 //! - [`batch`] - Batch processing API for bulk operations
+//! - [`cassette`] - Record and replay API traffic for offline tests
+//! - [`executor`] - Parallel tool execution with limits and timeouts
 //! - [`files`] - Files API for document uploads
+//! - [`memory`] - Pluggable storage for the memory tool
 //! - [`models`] - Model constants and metadata
+//! - [`output_policy`] - Truncate, summarize or upload oversized tool results
+//! - [`partial`] - Incremental parsing of streamed JSON
+//! - [`permissions`] - Permission policies and approval for tool calls
+//! - [`programmatic`] - Dispatch for tools called from code execution
 //! - [`tokens`] - Token counting utilities
+//! - [`tool_search`] - Client-side BM25/regex search over large tool catalogs
 //! - [`retry`] - Retry logic with exponential backoff
 //! - [`error`] - Error types and result aliases
 //! - [`prompts`] - Pre-built system prompts
 //! - [`structured`] - Structured output helpers
+//! - [`tools`] - Executable tools and automatic tool_use dispatch
+//! - `relay` - SSE relay for forwarding streams to browsers (requires `relay` feature)
+//! - `testing` - Scripted mock Claude server for offline tests (requires `testing` feature)
+//! - `validation` - Local JSON Schema validation (requires `validation` feature)
+//! - `bash` - Local executor for the `bash` tool (requires `local-tools` feature)
+//! - `text_editor` - Local executor for the text editor tool (requires `local-tools` feature)
+//! - `mcp` - Model Context Protocol client and server (requires `mcp` feature)
 //!
 //! ## Model Selection
 //!
@@ -225,30 +248,63 @@
 //! # }
 //! ```
 
+// Lets `#[tool]`-generated `::claude_sdk` paths resolve inside this crate
+extern crate self as claude_sdk;
+
+pub mod agent;
+#[cfg(feature = "local-tools")]
+pub mod bash;
 pub mod batch;
+pub mod cassette;
 pub mod client;
 pub mod conversation;
+mod editing;
 pub mod error;
+pub mod executor;
 pub mod files;
+#[cfg(feature = "mcp")]
+pub mod mcp;
+pub mod memory;
 pub mod models;
+pub mod output_policy;
+pub mod partial;
+pub mod permissions;
+pub mod programmatic;
 pub mod prompts;
+#[cfg(feature = "relay")]
+pub mod relay;
 pub mod retry;
 pub mod server_tools;
 pub mod streaming;
 pub mod structured;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "local-tools")]
+pub mod text_editor;
 pub mod tokens;
+pub mod tool_search;
+pub mod tools;
 pub mod types;
+#[cfg(feature = "validation")]
+pub mod validation;
 
 // Re-export main types for convenience
+#[cfg(feature = "macros")]
+pub use claude_sdk_macros::tool;
 pub use client::ClaudeClient;
 pub use conversation::ConversationBuilder;
 pub use error::{Error, Result};
+pub use executor::ToolExecutor;
 pub use models::{BedrockRegion, Model};
+#[cfg(feature = "schemars")]
+pub use schemars;
 pub use streaming::{ContentDelta, MessageDelta, StreamEvent};
+pub use tools::{ToolContext, ToolOutput, ToolRegistry};
 #[allow(deprecated)]
 pub use types::{
-    CacheTtl, Container, ContentBlock, CustomTool, EffortLevel, Message, MessagesRequest,
-    MessagesResponse, Metadata, OutputConfig, OutputFormat, OutputTokensDetails, RateLimitInfo,
-    RefusalCategory, Role, ServerToolUsage, ServiceTier, StopDetails, StopReason, ThinkingConfig,
-    ThinkingDisplay, TokenCount, Tool, ToolChoice, ToolDefinition, ToolResultContent, Usage,
+    CacheTtl, Container, ContentBlock, CustomTool, EffortLevel, McpServerDefinition, Message,
+    MessagesRequest, MessagesResponse, Metadata, OutputConfig, OutputFormat, OutputTokensDetails,
+    RateLimitInfo, RefusalCategory, Role, ServerToolUsage, ServiceTier, StopDetails, StopReason,
+    ThinkingConfig, ThinkingDisplay, TokenCount, Tool, ToolCaller, ToolChoice, ToolDefinition,
+    ToolResultContent, Usage,
 };
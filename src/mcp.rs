@@ -0,0 +1,1477 @@
+//! Model Context Protocol (MCP) client and server
+//!
+//! [`McpClient`] connects to MCP servers and exposes their tools as
+//! [`Tool`]s, so they can be registered in a [`ToolRegistry`] next to local
+//! tools:
+//!
+//! 1. [`McpClient::spawn`] starts a server over stdio (or
+//!    [`McpClient::http`] connects over streamable HTTP) and performs the
+//!    `initialize` handshake
+//! 2. [`McpClient::tools`] pages through `tools/list` and wraps each tool
+//!    as an [`McpTool`], named `{namespace}__{tool}` so tools from several
+//!    servers cannot collide
+//! 3. Calling an [`McpTool`] sends `tools/call` and converts the text and
+//!    image content of the result into a [`ToolOutput`]
+//!
+//! The namespace defaults to the name the server reports in its handshake.
+//! It is cut to [`MAX_NAMESPACE_LEN`] characters so the tool name still fits
+//! in the 64 characters Claude allows.
+//!
+//! In the other direction, [`McpServer`] publishes SDK tools to MCP hosts
+//! over stdio.
+//!
+//! Requires the `mcp` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::mcp::McpClient;
+//! use claude_sdk::ToolRegistry;
+//! use tokio::process::Command;
+//!
+//! # async fn example() -> claude_sdk::Result<()> {
+//! let mut command = Command::new("npx");
+//! command.args(["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]);
+//!
+//! let client = McpClient::spawn(command).await?.with_namespace("fs");
+//! let mut registry = ToolRegistry::new();
+//! client.register_tools(&mut registry).await?;
+//!
+//! // registry.definitions() now includes `fs__read_file`, `fs__list_directory`, ...
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, Result};
+use crate::tools::{async_trait, Tool, ToolContext, ToolOutput, ToolRegistry};
+use crate::types::{ContentBlock, CustomTool, ImageSource, ToolDefinition, ToolResultContent};
+use eventsource_stream::Eventsource;
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// MCP protocol revision this client speaks
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Default time to wait for a response
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Separator between the namespace and the MCP tool name
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+/// Longest namespace kept; leaves 38 of the 64 characters for the tool name
+pub const MAX_NAMESPACE_LEN: usize = 24;
+
+/// Name and version a server reports during the handshake
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Implementation {
+    /// Server or client name
+    pub name: String,
+
+    /// Server or client version
+    #[serde(default)]
+    pub version: String,
+}
+
+/// A tool as listed by `tools/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    /// Tool name on the server
+    pub name: String,
+
+    /// Human-readable title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// What the tool does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// JSON Schema for the arguments
+    #[serde(default = "empty_object_schema")]
+    pub input_schema: Value,
+
+    /// Behavioural hints such as `readOnlyHint`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+}
+
+fn empty_object_schema() -> Value {
+    json!({"type": "object"})
+}
+
+/// One content item of a `tools/call` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpContent {
+    /// Plain text
+    Text {
+        /// The text
+        text: String,
+    },
+    /// Base64-encoded image
+    Image {
+        /// Base64 data
+        data: String,
+        /// MIME type such as `image/png`
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// Base64-encoded audio
+    Audio {
+        /// Base64 data
+        data: String,
+        /// MIME type such as `audio/wav`
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// Link to a resource the server can provide
+    ResourceLink {
+        /// Resource URI
+        uri: String,
+        /// Resource name
+        #[serde(default)]
+        name: String,
+    },
+    /// Embedded resource contents (`uri` plus `text` or `blob`)
+    Resource {
+        /// The resource object
+        resource: Value,
+    },
+    /// A content type this client does not know
+    #[serde(other)]
+    Unsupported,
+}
+
+impl McpContent {
+    /// Plain text content
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    /// Convert into a content block Claude accepts in a `tool_result`
+    ///
+    /// Images become image blocks; audio and resources are described in
+    /// text. Returns `None` for unsupported content.
+    pub fn into_block(self) -> Option<ContentBlock> {
+        let text = match self {
+            Self::Text { text } => text,
+            Self::Image { data, mime_type } => {
+                return Some(ContentBlock::Image {
+                    source: ImageSource::Base64 {
+                        media_type: mime_type,
+                        data,
+                    },
+                    cache_control: None,
+                })
+            }
+            Self::Audio { mime_type, .. } => format!("[{} audio omitted]", mime_type),
+            Self::ResourceLink { uri, name } if name.is_empty() => format!("Resource: {}", uri),
+            Self::ResourceLink { uri, name } => format!("Resource: {} ({})", name, uri),
+            Self::Resource { resource } => match resource["text"].as_str() {
+                Some(text) => text.to_string(),
+                None => format!(
+                    "[binary resource {} omitted]",
+                    resource["uri"].as_str().unwrap_or("(unknown)")
+                ),
+            },
+            Self::Unsupported => return None,
+        };
+        Some(ContentBlock::Text {
+            text,
+            cache_control: None,
+            citations: None,
+        })
+    }
+}
+
+/// Result of `tools/call`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolResult {
+    /// Content items
+    #[serde(default)]
+    pub content: Vec<McpContent>,
+
+    /// Machine-readable result matching the tool's output schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+
+    /// Whether the tool reported a failure
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
+}
+
+impl McpToolResult {
+    /// Convert into a tool output
+    ///
+    /// Text-only results become a single text result; results with images
+    /// keep their blocks. A result with no content falls back to
+    /// `structuredContent` serialized as JSON.
+    pub fn into_output(self) -> ToolOutput {
+        let blocks: Vec<ContentBlock> = self
+            .content
+            .into_iter()
+            .filter_map(McpContent::into_block)
+            .collect();
+        let has_images = blocks
+            .iter()
+            .any(|block| matches!(block, ContentBlock::Image { .. }));
+
+        let mut output = if has_images {
+            ToolOutput::blocks(blocks)
+        } else {
+            let texts: Vec<String> = blocks
+                .into_iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text, .. } => Some(text),
+                    _ => None,
+                })
+                .collect();
+            match (texts.is_empty(), self.structured_content) {
+                (true, Some(structured)) => ToolOutput::json(&structured),
+                _ => ToolOutput::text(texts.join("\n")),
+            }
+        };
+        output.is_error = self.is_error;
+        output
+    }
+}
+
+/// Client for one MCP server
+///
+/// Cloning is cheap; clones share the connection, which closes (and a
+/// spawned server is killed) when the last clone is dropped.
+#[derive(Clone)]
+pub struct McpClient {
+    inner: Arc<Inner>,
+    namespace: String,
+    timeout: Duration,
+}
+
+struct Inner {
+    transport: Transport,
+    next_id: AtomicU64,
+    server: Implementation,
+    instructions: Option<String>,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("server", &self.inner.server)
+            .field("namespace", &self.namespace)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl McpClient {
+    /// Spawn a server and talk to it over its stdin and stdout
+    ///
+    /// The server's stderr is inherited unless `command` configures it. A
+    /// command that fails to start is reported as [`Error::Mcp`] without a
+    /// code, like other transport failures.
+    pub async fn spawn(mut command: Command) -> Result<Self> {
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command
+            .spawn()
+            .map_err(|e| transport_error(format!("Failed to start MCP server: {}", e)))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Self::handshake(Transport::Stream(StreamTransport::new(
+            stdout,
+            stdin,
+            Some(child),
+        )))
+        .await
+    }
+
+    /// Talk to a server over an existing pair of streams
+    ///
+    /// Messages are newline-delimited JSON, as on the stdio transport.
+    pub async fn connect<R, W>(reader: R, writer: W) -> Result<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self::handshake(Transport::Stream(StreamTransport::new(
+            reader, writer, None,
+        )))
+        .await
+    }
+
+    /// Connect to a server over streamable HTTP
+    pub async fn http(url: impl Into<String>) -> Result<Self> {
+        Self::http_with_headers(url, HeaderMap::new()).await
+    }
+
+    /// Connect over streamable HTTP, sending `headers` (such as
+    /// `Authorization`) with every request
+    pub async fn http_with_headers(url: impl Into<String>, headers: HeaderMap) -> Result<Self> {
+        Self::handshake(Transport::Http(HttpTransport {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            headers,
+            session: Mutex::new(None),
+        }))
+        .await
+    }
+
+    async fn handshake(transport: Transport) -> Result<Self> {
+        let mut inner = Inner {
+            transport,
+            next_id: AtomicU64::new(1),
+            server: Implementation::default(),
+            instructions: None,
+        };
+        let params = json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "claude-sdk", "version": env!("CARGO_PKG_VERSION")},
+        });
+        let result = inner.request("initialize", params, DEFAULT_TIMEOUT).await?;
+        inner.server = serde_json::from_value(result["serverInfo"].clone()).unwrap_or_default();
+        inner.instructions = result["instructions"].as_str().map(str::to_string);
+        debug!(
+            "MCP server {} {} speaks {}",
+            inner.server.name, inner.server.version, result["protocolVersion"]
+        );
+        inner
+            .notify("notifications/initialized", DEFAULT_TIMEOUT)
+            .await?;
+
+        let namespace = sanitize_namespace(&inner.server.name);
+        Ok(Self {
+            inner: Arc::new(inner),
+            namespace,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Prefix tool names with `namespace`; an empty namespace disables prefixing
+    ///
+    /// Longer namespaces are cut to [`MAX_NAMESPACE_LEN`] characters.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = sanitize_namespace(&namespace.into());
+        self
+    }
+
+    /// Set how long to wait for each response (default 60 seconds)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Server name and version from the handshake
+    pub fn server_info(&self) -> &Implementation {
+        &self.inner.server
+    }
+
+    /// Usage instructions the server sent during the handshake
+    pub fn instructions(&self) -> Option<&str> {
+        self.inner.instructions.as_deref()
+    }
+
+    /// Prefix applied to tool names
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Send a raw JSON-RPC request and return its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.inner.request(method, params, self.timeout).await
+    }
+
+    /// List every tool the server offers, following pagination
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let mut result = self.request("tools/list", params).await?;
+            let page: Vec<McpToolInfo> = serde_json::from_value(result["tools"].take())?;
+            tools.extend(page);
+            match result["nextCursor"].as_str() {
+                Some(next) if !next.is_empty() => cursor = Some(next.to_string()),
+                _ => return Ok(tools),
+            }
+        }
+    }
+
+    /// Call a tool by its name on the server
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<McpToolResult> {
+        let result = self
+            .request("tools/call", json!({"name": name, "arguments": arguments}))
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// The server's tools, wrapped for use in a [`ToolRegistry`]
+    pub async fn tools(&self) -> Result<Vec<McpTool>> {
+        Ok(self
+            .list_tools()
+            .await?
+            .into_iter()
+            .map(|info| McpTool {
+                name: self.qualified_name(&info.name),
+                description: info
+                    .description
+                    .clone()
+                    .or_else(|| info.title.clone())
+                    .unwrap_or_default(),
+                client: self.clone(),
+                info,
+            })
+            .collect())
+    }
+
+    /// The server's tools as definitions for a request
+    pub async fn custom_tools(&self) -> Result<Vec<CustomTool>> {
+        Ok(self.tools().await?.iter().map(Tool::definition).collect())
+    }
+
+    /// Add the server's tools to `registry`, returning how many were added
+    pub async fn register_tools(&self, registry: &mut ToolRegistry) -> Result<usize> {
+        let tools = self.tools().await?;
+        let count = tools.len();
+        for tool in tools {
+            registry.register(Arc::new(tool));
+        }
+        Ok(count)
+    }
+
+    /// The name Claude sees for the server tool `name`
+    pub fn qualified_name(&self, name: &str) -> String {
+        let name = sanitize_name(name);
+        if self.namespace.is_empty() {
+            return name;
+        }
+        let mut qualified = format!("{}{}{}", self.namespace, NAMESPACE_SEPARATOR, name);
+        qualified.truncate(64);
+        qualified
+    }
+}
+
+/// An MCP server tool usable as a local [`Tool`]
+#[derive(Debug, Clone)]
+pub struct McpTool {
+    client: McpClient,
+    info: McpToolInfo,
+    name: String,
+    description: String,
+}
+
+impl McpTool {
+    /// The tool as listed by the server
+    pub fn info(&self) -> &McpToolInfo {
+        &self.info
+    }
+
+    /// The client that runs this tool
+    pub fn client(&self) -> &McpClient {
+        &self.client
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn input_schema(&self) -> Value {
+        self.info.input_schema.clone()
+    }
+
+    async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+        match self.client.call_tool(&self.info.name, input).await {
+            Ok(result) => result.into_output(),
+            Err(e) => ToolOutput::error(e.to_string()),
+        }
+    }
+}
+
+/// Replace characters Claude does not accept in tool names
+fn sanitize_namespace(namespace: &str) -> String {
+    let mut sanitized = sanitize_name(namespace);
+    sanitized.truncate(MAX_NAMESPACE_LEN);
+    sanitized
+}
+
+fn sanitize_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    sanitized.truncate(64);
+    sanitized
+}
+
+impl Inner {
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        debug!("MCP request {} ({})", method, id);
+
+        let response = match &self.transport {
+            Transport::Stream(stream) => stream.request(id, &message, timeout).await?,
+            Transport::Http(http) => http
+                .send(&message, Some(id), timeout)
+                .await?
+                .ok_or_else(|| transport_error(format!("No response to {}", method)))?,
+        };
+
+        if let Some(error) = response.get("error") {
+            return Err(Error::Mcp {
+                code: error["code"].as_i64(),
+                message: error["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            });
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn notify(&self, method: &str, timeout: Duration) -> Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": method});
+        match &self.transport {
+            Transport::Stream(stream) => stream.write(&message).await,
+            Transport::Http(http) => http.send(&message, None, timeout).await.map(|_| ()),
+        }
+    }
+}
+
+fn transport_error(message: impl Into<String>) -> Error {
+    Error::Mcp {
+        code: None,
+        message: message.into(),
+    }
+}
+
+enum Transport {
+    Stream(StreamTransport),
+    Http(HttpTransport),
+}
+
+type SharedWriter = Arc<tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>>;
+
+#[derive(Default)]
+struct Pending {
+    waiters: HashMap<u64, oneshot::Sender<Value>>,
+    closed: bool,
+}
+
+/// Newline-delimited JSON-RPC over a byte stream
+struct StreamTransport {
+    writer: SharedWriter,
+    pending: Arc<Mutex<Pending>>,
+    reader: JoinHandle<()>,
+    _child: Option<Child>,
+}
+
+impl StreamTransport {
+    fn new<R, W>(reader: R, writer: W, child: Option<Child>) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let writer: SharedWriter = Arc::new(tokio::sync::Mutex::new(Box::new(writer)));
+        let pending = Arc::new(Mutex::new(Pending::default()));
+        let reader = tokio::spawn(read_loop(reader, writer.clone(), pending.clone()));
+        Self {
+            writer,
+            pending,
+            reader,
+            _child: child,
+        }
+    }
+
+    async fn write(&self, message: &Value) -> Result<()> {
+        write_line(&self.writer, message).await
+    }
+
+    async fn request(&self, id: u64, message: &Value, timeout: Duration) -> Result<Value> {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().expect("MCP pending lock poisoned");
+            if pending.closed {
+                return Err(transport_error("MCP server closed the connection"));
+            }
+            pending.waiters.insert(id, tx);
+        }
+        self.write(message).await?;
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(transport_error("MCP server closed the connection")),
+            Err(_) => {
+                self.pending
+                    .lock()
+                    .expect("MCP pending lock poisoned")
+                    .waiters
+                    .remove(&id);
+                Err(transport_error(format!(
+                    "MCP request timed out after {:?}",
+                    timeout
+                )))
+            }
+        }
+    }
+}
+
+impl Drop for StreamTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+async fn write_line(writer: &SharedWriter, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    let mut writer = writer.lock().await;
+    writer
+        .write_all(&line)
+        .await
+        .map_err(|e| transport_error(format!("Failed to write to MCP server: {}", e)))?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| transport_error(format!("Failed to write to MCP server: {}", e)))
+}
+
+/// Route responses to waiting requests and answer server pings
+async fn read_loop<R>(reader: R, writer: SharedWriter, pending: Arc<Mutex<Pending>>)
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Ignoring malformed MCP message: {}", e);
+                continue;
+            }
+        };
+
+        match (message.get("id"), message.get("method")) {
+            (Some(id), None) => {
+                let waiter = id.as_u64().and_then(|id| {
+                    pending
+                        .lock()
+                        .expect("MCP pending lock poisoned")
+                        .waiters
+                        .remove(&id)
+                });
+                if let Some(waiter) = waiter {
+                    let _ = waiter.send(message);
+                }
+            }
+            (Some(id), Some(method)) => {
+                let response = if method == "ping" {
+                    json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                } else {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32601, "message": format!("Method not found: {}", method)},
+                    })
+                };
+                if write_line(&writer, &response).await.is_err() {
+                    break;
+                }
+            }
+            (None, method) => debug!("MCP notification {:?}", method),
+        }
+    }
+
+    let mut pending = pending.lock().expect("MCP pending lock poisoned");
+    pending.closed = true;
+    pending.waiters.clear();
+}
+
+/// JSON-RPC over streamable HTTP
+struct HttpTransport {
+    http: reqwest::Client,
+    url: String,
+    headers: HeaderMap,
+    session: Mutex<Option<HeaderValue>>,
+}
+
+impl HttpTransport {
+    /// POST a message; returns the response matching `id`, if one is expected
+    async fn send(
+        &self,
+        message: &Value,
+        id: Option<u64>,
+        timeout: Duration,
+    ) -> Result<Option<Value>> {
+        let exchange = async {
+            let mut request = self
+                .http
+                .post(&self.url)
+                .headers(self.headers.clone())
+                .header(ACCEPT, "application/json, text/event-stream")
+                .header("MCP-Protocol-Version", PROTOCOL_VERSION)
+                .json(message);
+            let session = self
+                .session
+                .lock()
+                .expect("MCP session lock poisoned")
+                .clone();
+            if let Some(session) = session {
+                request = request.header("Mcp-Session-Id", session);
+            }
+
+            let response = request.send().await?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(transport_error(format!("HTTP {}: {}", status, body)));
+            }
+            if let Some(session) = response.headers().get("Mcp-Session-Id") {
+                *self.session.lock().expect("MCP session lock poisoned") = Some(session.clone());
+            }
+            let Some(id) = id else {
+                return Ok(None);
+            };
+
+            let is_stream = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("text/event-stream"));
+            if !is_stream {
+                return Ok(Some(response.json::<Value>().await?));
+            }
+
+            let mut events = response.bytes_stream().eventsource();
+            while let Some(event) = events.next().await {
+                let event = event.map_err(|e| Error::StreamParse(e.to_string()))?;
+                let Ok(message) = serde_json::from_str::<Value>(&event.data) else {
+                    continue;
+                };
+                if message["id"].as_u64() == Some(id) && message.get("method").is_none() {
+                    return Ok(Some(message));
+                }
+            }
+            Err(transport_error("MCP event stream ended without a response"))
+        };
+
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| transport_error(format!("MCP request timed out after {:?}", timeout)))?
+    }
+}
+
+/// Serves [`Tool`]s to other MCP hosts
+///
+/// Answers `initialize`, `ping`, `tools/list` and `tools/call`. Calls go
+/// through a [`ToolRegistry`], so permission policies and input validation
+/// configured on it apply. A failed call is reported as a tool error
+/// (`isError: true`); an unknown tool is a JSON-RPC error.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use claude_sdk::mcp::McpServer;
+/// use claude_sdk::{CustomTool, ToolOutput};
+/// use serde_json::json;
+///
+/// # async fn example() -> claude_sdk::Result<()> {
+/// let shout = CustomTool::new(
+///     "shout",
+///     "Upper-case some text",
+///     json!({"type": "object", "properties": {"text": {"type": "string"}}}),
+/// );
+///
+/// McpServer::new("shouter", "1.0.0")
+///     .with_handler(shout, |input| async move {
+///         ToolOutput::text(input["text"].as_str().unwrap_or_default().to_uppercase())
+///     })
+///     .serve_stdio()
+///     .await
+/// # }
+/// ```
+pub struct McpServer {
+    info: Implementation,
+    instructions: Option<String>,
+    registry: ToolRegistry,
+}
+
+type Handler = dyn Fn(Value) -> BoxFuture<'static, ToolOutput> + Send + Sync;
+
+/// A tool made from a definition and a closure
+struct HandlerTool {
+    definition: CustomTool,
+    handler: Box<Handler>,
+}
+
+#[async_trait]
+impl Tool for HandlerTool {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn description(&self) -> &str {
+        &self.definition.description
+    }
+
+    fn input_schema(&self) -> Value {
+        self.definition.input_schema.clone()
+    }
+
+    fn definition(&self) -> CustomTool {
+        self.definition.clone()
+    }
+
+    async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+        (self.handler)(input).await
+    }
+}
+
+impl std::fmt::Debug for McpServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpServer")
+            .field("info", &self.info)
+            .field("tools", &self.registry.names())
+            .finish_non_exhaustive()
+    }
+}
+
+impl McpServer {
+    /// Create a server that reports `name` and `version` to hosts
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            info: Implementation {
+                name: name.into(),
+                version: version.into(),
+            },
+            instructions: None,
+            registry: ToolRegistry::new(),
+        }
+    }
+
+    /// Usage instructions sent to hosts during the handshake
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Publish a tool
+    pub fn with_tool(mut self, tool: impl Tool + 'static) -> Self {
+        self.registry.register(Arc::new(tool));
+        self
+    }
+
+    /// Publish `definition`, answering calls with `handler`
+    pub fn with_handler<F, Fut>(self, definition: CustomTool, handler: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolOutput> + Send + 'static,
+    {
+        self.with_tool(HandlerTool {
+            definition,
+            handler: Box::new(move |input| handler(input).boxed()),
+        })
+    }
+
+    /// Publish every tool in `registry`, replacing tools added so far
+    ///
+    /// Anthropic-defined tools such as `bash` have no input schema to
+    /// publish and are left out of `tools/list`.
+    pub fn with_registry(mut self, registry: ToolRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Serve on this process's stdin and stdout until stdin closes
+    ///
+    /// Stdout carries protocol messages, so logs must go to stderr.
+    pub async fn serve_stdio(self) -> Result<()> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Serve newline-delimited JSON-RPC until `reader` closes
+    ///
+    /// Requests are handled concurrently; in-flight calls finish before
+    /// this returns.
+    pub async fn serve<R, W>(self, reader: R, writer: W) -> Result<()>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let server = Arc::new(self);
+        let writer: SharedWriter = Arc::new(tokio::sync::Mutex::new(Box::new(writer)));
+        let mut lines = BufReader::new(reader).lines();
+        let mut in_flight = tokio::task::JoinSet::new();
+
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| transport_error(format!("Failed to read from MCP host: {}", e)))?
+        {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let server = server.clone();
+            let writer = writer.clone();
+            in_flight.spawn(async move {
+                let response = match serde_json::from_str::<Value>(&line) {
+                    Ok(message) => server.handle(message).await,
+                    Err(e) => Some(error_response(
+                        Value::Null,
+                        -32700,
+                        format!("Parse error: {}", e),
+                    )),
+                };
+                match response {
+                    Some(response) => write_line(&writer, &response).await,
+                    None => Ok(()),
+                }
+            });
+            // Reap finished calls so the set does not grow without bound
+            while let Some(done) = in_flight.try_join_next() {
+                flatten_join(done)?;
+            }
+        }
+
+        while let Some(done) = in_flight.join_next().await {
+            flatten_join(done)?;
+        }
+        Ok(())
+    }
+
+    /// Handle one JSON-RPC message, returning the response to send, if any
+    pub async fn handle(&self, message: Value) -> Option<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let Some(id) = message.get("id").cloned() else {
+            debug!("MCP notification {}", method);
+            return None;
+        };
+        let params = &message["params"];
+
+        let result = match method {
+            "initialize" => {
+                let mut result = json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {"tools": {"listChanged": false}},
+                    "serverInfo": self.info,
+                });
+                if let Some(instructions) = &self.instructions {
+                    result["instructions"] = json!(instructions);
+                }
+                result
+            }
+            "ping" => json!({}),
+            "tools/list" => json!({"tools": self.list_tools()}),
+            "tools/call" => {
+                let Some(name) = params["name"].as_str() else {
+                    return Some(error_response(id, -32602, "Missing tool name".into()));
+                };
+                if self.registry.get(name).is_none() {
+                    return Some(error_response(
+                        id,
+                        -32602,
+                        format!("Unknown tool: {}", name),
+                    ));
+                }
+                let arguments = match &params["arguments"] {
+                    Value::Null => json!({}),
+                    arguments => arguments.clone(),
+                };
+                let output = self
+                    .registry
+                    .call(&format!("mcp-{}", id), name, arguments)
+                    .await;
+                serde_json::to_value(McpToolResult::from(output)).unwrap_or_default()
+            }
+            _ => {
+                return Some(error_response(
+                    id,
+                    -32601,
+                    format!("Method not found: {}", method),
+                ))
+            }
+        };
+        Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    fn list_tools(&self) -> Vec<McpToolInfo> {
+        self.registry
+            .definitions()
+            .into_iter()
+            .filter_map(|definition| match definition {
+                ToolDefinition::Custom(tool) => Some(McpToolInfo {
+                    name: tool.name,
+                    title: None,
+                    description: Some(tool.description),
+                    input_schema: tool.input_schema,
+                    annotations: None,
+                }),
+                ToolDefinition::Server(_) => None,
+            })
+            .collect()
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn flatten_join(done: std::result::Result<Result<()>, tokio::task::JoinError>) -> Result<()> {
+    done.map_err(|e| transport_error(format!("MCP request handler failed: {}", e)))?
+}
+
+impl From<ToolOutput> for McpToolResult {
+    fn from(output: ToolOutput) -> Self {
+        let content = match output.content {
+            ToolResultContent::Text(text) => vec![McpContent::Text { text }],
+            ToolResultContent::Blocks(blocks) => blocks
+                .into_iter()
+                .map(|block| match block {
+                    ContentBlock::Text { text, .. } => McpContent::Text { text },
+                    ContentBlock::Image {
+                        source: ImageSource::Base64 { media_type, data },
+                        ..
+                    } => McpContent::Image {
+                        data,
+                        mime_type: media_type,
+                    },
+                    other => McpContent::Text {
+                        text: serde_json::to_string(&other).unwrap_or_default(),
+                    },
+                })
+                .collect(),
+        };
+        Self {
+            content,
+            structured_content: None,
+            is_error: output.is_error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolResultContent;
+
+    /// Answer MCP requests the way a small server would
+    fn fixture_response(request: &Value) -> Option<Value> {
+        let id = request.get("id")?.clone();
+        let result = match request["method"].as_str()? {
+            "initialize" => json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "fixture server", "version": "1.0"},
+                "instructions": "Use echo to repeat things",
+            }),
+            "tools/list" if request["params"]["cursor"].is_null() => json!({
+                "tools": [{
+                    "name": "echo",
+                    "description": "Repeat the input",
+                    "inputSchema": {"type": "object", "properties": {"text": {"type": "string"}}},
+                }],
+                "nextCursor": "page2",
+            }),
+            "tools/list" => json!({"tools": [{"name": "snapshot", "title": "Take a picture"}]}),
+            "tools/call" => match request["params"]["name"].as_str()? {
+                "echo" => {
+                    json!({"content": [{"type": "text", "text": request["params"]["arguments"]["text"]}]})
+                }
+                "snapshot" => json!({"content": [
+                    {"type": "text", "text": "Here it is"},
+                    {"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"},
+                ]}),
+                "fail" => json!({"content": [{"type": "text", "text": "boom"}], "isError": true}),
+                other => {
+                    return Some(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32602, "message": format!("Unknown tool: {}", other)},
+                    }))
+                }
+            },
+            _ => return None,
+        };
+        Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    async fn fixture_client() -> McpClient {
+        let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+        let (server_read, mut server_write) = tokio::io::split(server_end);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(server_read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                if let Some(response) = fixture_response(&request) {
+                    let mut out = response.to_string();
+                    out.push('\n');
+                    server_write.write_all(out.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        let (reader, writer) = tokio::io::split(client_end);
+        McpClient::connect(reader, writer).await.unwrap()
+    }
+
+    fn text(output: &ToolOutput) -> &str {
+        match &output.content {
+            ToolResultContent::Text(text) => text,
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_namespaced_tools() {
+        let client = fixture_client().await;
+        assert_eq!(client.server_info().name, "fixture server");
+        assert_eq!(client.instructions(), Some("Use echo to repeat things"));
+        assert_eq!(client.namespace(), "fixture_server");
+
+        let tools = client.custom_tools().await.unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["fixture_server__echo", "fixture_server__snapshot"]);
+        assert_eq!(tools[0].description, "Repeat the input");
+        assert_eq!(tools[1].description, "Take a picture");
+        assert_eq!(tools[1].input_schema, json!({"type": "object"}));
+
+        let client = client.with_namespace("");
+        assert_eq!(client.qualified_name("echo"), "echo");
+
+        let client = client.with_namespace("n".repeat(64));
+        assert_eq!(client.namespace().len(), MAX_NAMESPACE_LEN);
+        assert!(client.qualified_name("echo").ends_with("__echo"));
+        assert_ne!(
+            client.qualified_name("read_file"),
+            client.qualified_name("write_file")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spawn_failure_is_a_transport_error() {
+        let command = Command::new("/nonexistent/claude-sdk-mcp-server");
+        let err = McpClient::spawn(command).await.unwrap_err();
+        assert!(
+            matches!(&err, Error::Mcp { code: None, message } if message.starts_with("Failed to start")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_routes_calls_to_server() {
+        let client = fixture_client().await.with_namespace("fx");
+        let mut registry = ToolRegistry::new();
+        assert_eq!(client.register_tools(&mut registry).await.unwrap(), 2);
+
+        let output = registry
+            .call("toolu_1", "fx__echo", json!({"text": "hello"}))
+            .await;
+        assert!(!output.is_error);
+        assert_eq!(text(&output), "hello");
+
+        let output = registry.call("toolu_2", "fx__snapshot", json!({})).await;
+        match output.content {
+            ToolResultContent::Blocks(blocks) => {
+                assert!(
+                    matches!(&blocks[0], ContentBlock::Text { text, .. } if text == "Here it is")
+                );
+                assert!(matches!(
+                    &blocks[1],
+                    ContentBlock::Image { source: ImageSource::Base64 { media_type, .. }, .. }
+                        if media_type == "image/png"
+                ));
+            }
+            other => panic!("Expected blocks, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_and_protocol_errors() {
+        let client = fixture_client().await;
+        let result = client.call_tool("fail", json!({})).await.unwrap();
+        let output = result.into_output();
+        assert!(output.is_error);
+        assert_eq!(text(&output), "boom");
+
+        match client.call_tool("missing", json!({})).await {
+            Err(Error::Mcp { code, message }) => {
+                assert_eq!(code, Some(-32602));
+                assert_eq!(message, "Unknown tool: missing");
+            }
+            other => panic!("Expected MCP error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_result_conversion() {
+        let result: McpToolResult = serde_json::from_value(json!({
+            "content": [
+                {"type": "text", "text": "a"},
+                {"type": "resource", "resource": {"uri": "file:///x", "text": "b"}},
+                {"type": "resource_link", "uri": "file:///y", "name": "y"},
+                {"type": "something_new"},
+            ]
+        }))
+        .unwrap();
+        assert_eq!(result.content[3], McpContent::Unsupported);
+        assert_eq!(text(&result.into_output()), "a\nb\nResource: y (file:///y)");
+
+        let structured: McpToolResult =
+            serde_json::from_value(json!({"content": [], "structuredContent": {"n": 1}})).unwrap();
+        assert_eq!(text(&structured.into_output()), r#"{"n":1}"#);
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(sanitize_name("my server.v2"), "my_server_v2");
+        assert_eq!(sanitize_name(&"x".repeat(80)).len(), 64);
+    }
+
+    fn shout_server() -> McpServer {
+        let shout = CustomTool::new(
+            "shout",
+            "Upper-case some text",
+            json!({"type": "object", "properties": {"text": {"type": "string"}}}),
+        );
+        McpServer::new("shouter", "2.0")
+            .with_instructions("Shout things")
+            .with_handler(shout, |input| async move {
+                match input["text"].as_str() {
+                    Some(text) => ToolOutput::text(text.to_uppercase()),
+                    None => ToolOutput::error("Missing text"),
+                }
+            })
+    }
+
+    #[tokio::test]
+    async fn test_server_round_trip_with_client() {
+        let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_end);
+        let serving = tokio::spawn(shout_server().serve(server_read, server_write));
+
+        let (reader, writer) = tokio::io::split(client_end);
+        let client = McpClient::connect(reader, writer).await.unwrap();
+        assert_eq!(client.server_info().name, "shouter");
+        assert_eq!(client.instructions(), Some("Shout things"));
+
+        let tools = client.list_tools().await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(
+            tools[0].description.as_deref(),
+            Some("Upper-case some text")
+        );
+
+        let result = client
+            .call_tool("shout", json!({"text": "hi"}))
+            .await
+            .unwrap();
+        assert_eq!(result.content, vec![McpContent::text("HI")]);
+        assert!(!result.is_error);
+
+        let result = client.call_tool("shout", json!({})).await.unwrap();
+        assert!(result.is_error);
+        assert_eq!(result.content, vec![McpContent::text("Missing text")]);
+
+        drop(client);
+        serving.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_server_protocol_errors() {
+        let server = shout_server();
+        let response = server
+            .handle(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "nope"}}))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], -32602);
+        assert_eq!(response["error"]["message"], "Unknown tool: nope");
+
+        let response = server
+            .handle(json!({"jsonrpc": "2.0", "id": "a", "method": "resources/list"}))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["error"]["code"], -32601);
+
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle(notification).await.is_none());
+        let ping = json!({"jsonrpc": "2.0", "id": 2, "method": "ping"});
+        assert_eq!(server.handle(ping).await.unwrap()["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_server_respects_registry_permissions() {
+        use crate::permissions::{PermissionPolicy, PermissionRule};
+
+        let registry = ToolRegistry::new()
+            .with_tool(McpServerTestTool)
+            .with_permissions(PermissionPolicy::new().with_rule(PermissionRule::deny("danger")));
+        let server = McpServer::new("guarded", "1.0").with_registry(registry);
+        let response = server
+            .handle(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "danger"}}))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    struct McpServerTestTool;
+
+    #[async_trait]
+    impl Tool for McpServerTestTool {
+        fn name(&self) -> &str {
+            "danger"
+        }
+
+        fn description(&self) -> &str {
+            "Does something risky"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn call(&self, _input: Value, _ctx: &ToolContext) -> ToolOutput {
+            ToolOutput::text("done")
+        }
+    }
+
+    #[test]
+    fn test_tool_output_to_mcp_result() {
+        let output = ToolOutput::blocks(vec![
+            ContentBlock::Text {
+                text: "caption".into(),
+                cache_control: None,
+                citations: None,
+            },
+            ContentBlock::Image {
+                source: ImageSource::Base64 {
+                    media_type: "image/png".into(),
+                    data: "AAAA".into(),
+                },
+                cache_control: None,
+            },
+        ]);
+        let result = McpToolResult::from(output);
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({"content": [
+                {"type": "text", "text": "caption"},
+                {"type": "image", "data": "AAAA", "mimeType": "image/png"},
+            ]})
+        );
+    }
+
+    /// A stdio server written in shell; ids are read from the sorted JSON keys
+    #[cfg(unix)]
+    const STDIO_FIXTURE: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/^{"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"shell","version":"0.1"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"greet","description":"Say hello","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"hello from shell"}]}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_server() {
+        let mut command = Command::new("sh");
+        command.args(["-c", STDIO_FIXTURE]);
+        let client = McpClient::spawn(command).await.unwrap();
+        assert_eq!(client.server_info().version, "0.1");
+
+        let tools = client.tools().await.unwrap();
+        assert_eq!(tools[0].name(), "shell__greet");
+        let output = tools[0]
+            .call(json!({}), &ToolContext::new("toolu_1", "shell__greet"))
+            .await;
+        assert_eq!(text(&output), "hello from shell");
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_streamable_http() {
+        use axum::http::HeaderMap as AxumHeaders;
+        use axum::response::{IntoResponse, Response};
+        use axum::{routing::post, Json, Router};
+
+        async fn handle(headers: AxumHeaders, Json(request): Json<Value>) -> Response {
+            let Some(response) = fixture_response(&request) else {
+                return axum::http::StatusCode::ACCEPTED.into_response();
+            };
+            if request["method"] == "initialize" {
+                return ([("Mcp-Session-Id", "session-1")], Json(response)).into_response();
+            }
+            assert_eq!(headers["mcp-session-id"], "session-1");
+            // Later responses arrive as an event stream after a notification
+            let body = format!(
+                "event: message\ndata: {}\n\nevent: message\ndata: {}\n\n",
+                json!({"jsonrpc": "2.0", "method": "notifications/progress"}),
+                response
+            );
+            (
+                [(axum::http::header::CONTENT_TYPE, "text/event-stream")],
+                body,
+            )
+                .into_response()
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, Router::new().route("/mcp", post(handle))).await;
+        });
+
+        let client = McpClient::http(format!("http://{}/mcp", addr))
+            .await
+            .unwrap();
+        let result = client
+            .call_tool("echo", json!({"text": "over http"}))
+            .await
+            .unwrap();
+        assert_eq!(result.content, vec![McpContent::text("over http")]);
+    }
+}
@@ -0,0 +1,781 @@
+//! Client-side handling of the memory tool
+//!
+//! [`MemoryTool`] lets Claude keep notes in a `/memories` directory that
+//! outlives a single conversation. Its calls come back as `tool_use` blocks
+//! that the client must run. [`MemoryExecutor`] is a [`Tool`] that runs them
+//! (`view`, `create`, `str_replace`, `insert`, `delete`, `rename`) and
+//! formats the results the way Claude expects.
+//!
+//! Storage is pluggable through [`MemoryStore`]:
+//!
+//! - [`FileMemoryStore`] keeps memories as files under a directory, so they
+//!   persist across sessions
+//! - [`InMemoryStore`] keeps them in a map, for tests and short-lived agents
+//!
+//! Paths are checked before they reach a store: they must lie under
+//! `/memories` and may not contain `..`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::memory::MemoryExecutor;
+//! use claude_sdk::ToolRegistry;
+//!
+//! let registry = ToolRegistry::new().with_tool(MemoryExecutor::filesystem("./agent-memory"));
+//! ```
+
+use crate::editing::{insert_after, number_lines, replace_once, snippet, view_range, ReplaceError};
+use crate::server_tools::MemoryTool;
+use crate::tools::{async_trait, Tool, ToolContext, ToolOutput};
+use crate::types::ToolDefinition;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Directory Claude sees as the memory root
+pub const MEMORY_ROOT: &str = "/memories";
+
+/// A file or directory in a [`MemoryStore`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEntry {
+    /// Path relative to `/memories`
+    pub path: String,
+
+    /// Size in bytes
+    pub size: u64,
+
+    /// Whether the entry is a directory
+    pub is_dir: bool,
+}
+
+/// Storage backend for [`MemoryExecutor`]
+///
+/// Paths are relative to `/memories`, use `/` separators and never contain
+/// `.` or `..` components. The empty path is the memory root, which always
+/// exists as a directory.
+#[async_trait]
+pub trait MemoryStore: Send + Sync {
+    /// Entries directly inside `dir`, or `None` if it is not a directory
+    async fn list(&self, dir: &str) -> io::Result<Option<Vec<MemoryEntry>>>;
+
+    /// Contents of the file at `path`, or `None` if there is no such file
+    async fn read(&self, path: &str) -> io::Result<Option<String>>;
+
+    /// Create or overwrite the file at `path`, creating parent directories
+    async fn write(&self, path: &str, content: &str) -> io::Result<()>;
+
+    /// Delete a file or directory tree; returns whether anything was removed
+    async fn delete(&self, path: &str) -> io::Result<bool>;
+
+    /// Move a file or directory; returns whether `from` existed
+    async fn rename(&self, from: &str, to: &str) -> io::Result<bool>;
+}
+
+/// Stores memories as files under a directory
+#[derive(Debug, Clone)]
+pub struct FileMemoryStore {
+    root: PathBuf,
+}
+
+impl FileMemoryStore {
+    /// Store memories under `root`, which is created on first write
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The directory backing `/memories`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Map a memory path to a file, refusing symlinks that leave the root
+    fn resolve(&self, path: &str) -> io::Result<PathBuf> {
+        let full = self.root.join(path);
+        let Ok(root) = self.root.canonicalize() else {
+            return Ok(full);
+        };
+        let mut existing = full.as_path();
+        while !existing.exists() {
+            match existing.parent() {
+                Some(parent) => existing = parent,
+                None => break,
+            }
+        }
+        match existing.canonicalize() {
+            Ok(resolved) if !resolved.starts_with(&root) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "path leaves the memory directory",
+            )),
+            _ => Ok(full),
+        }
+    }
+}
+
+#[async_trait]
+impl MemoryStore for FileMemoryStore {
+    async fn list(&self, dir: &str) -> io::Result<Option<Vec<MemoryEntry>>> {
+        let full = self.resolve(dir)?;
+        if !full.is_dir() {
+            return Ok(dir.is_empty().then(Vec::new));
+        }
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(&full).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            entries.push(MemoryEntry {
+                path: join(dir, &name),
+                size: metadata.len(),
+                is_dir: metadata.is_dir(),
+            });
+        }
+        Ok(Some(entries))
+    }
+
+    async fn read(&self, path: &str) -> io::Result<Option<String>> {
+        let full = self.resolve(path)?;
+        if !full.is_file() {
+            return Ok(None);
+        }
+        tokio::fs::read_to_string(full).await.map(Some)
+    }
+
+    async fn write(&self, path: &str, content: &str) -> io::Result<()> {
+        let full = self.resolve(path)?;
+        if let Some(parent) = full.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(full, content).await
+    }
+
+    async fn delete(&self, path: &str) -> io::Result<bool> {
+        let full = self.resolve(path)?;
+        if full.is_dir() {
+            tokio::fs::remove_dir_all(full).await?;
+        } else if full.exists() {
+            tokio::fs::remove_file(full).await?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> io::Result<bool> {
+        let source = self.resolve(from)?;
+        let target = self.resolve(to)?;
+        if !source.exists() {
+            return Ok(false);
+        }
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(source, target).await?;
+        Ok(true)
+    }
+}
+
+/// Keeps memories in memory; directories exist implicitly while they hold files
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    files: Mutex<BTreeMap<String, String>>,
+}
+
+impl InMemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn files(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.files.lock().expect("memory store lock poisoned")
+    }
+}
+
+#[async_trait]
+impl MemoryStore for InMemoryStore {
+    async fn list(&self, dir: &str) -> io::Result<Option<Vec<MemoryEntry>>> {
+        let files = self.files();
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir)
+        };
+        let mut children: BTreeMap<String, MemoryEntry> = BTreeMap::new();
+        for (path, content) in files.range(prefix.clone()..) {
+            let Some(rest) = path.strip_prefix(&prefix) else {
+                break;
+            };
+            let (name, is_dir) = match rest.split_once('/') {
+                Some((name, _)) => (name, true),
+                None => (rest, false),
+            };
+            let entry = children
+                .entry(name.to_string())
+                .or_insert_with(|| MemoryEntry {
+                    path: join(dir, name),
+                    size: 0,
+                    is_dir,
+                });
+            entry.size += content.len() as u64;
+        }
+        if children.is_empty() && !dir.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(children.into_values().collect()))
+    }
+
+    async fn read(&self, path: &str) -> io::Result<Option<String>> {
+        Ok(self.files().get(path).cloned())
+    }
+
+    async fn write(&self, path: &str, content: &str) -> io::Result<()> {
+        self.files().insert(path.to_string(), content.to_string());
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> io::Result<bool> {
+        let mut files = self.files();
+        let prefix = format!("{}/", path);
+        let before = files.len();
+        files.retain(|p, _| p != path && !p.starts_with(&prefix));
+        Ok(files.len() != before)
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> io::Result<bool> {
+        let mut files = self.files();
+        let prefix = format!("{}/", from);
+        let moved: Vec<String> = files
+            .keys()
+            .filter(|p| *p == from || p.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for path in &moved {
+            let content = files.remove(path).expect("listed key exists");
+            files.insert(format!("{}{}", to, &path[from.len()..]), content);
+        }
+        Ok(!moved.is_empty())
+    }
+}
+
+/// Runs memory tool calls against a [`MemoryStore`]
+#[derive(Clone)]
+pub struct MemoryExecutor {
+    store: Arc<dyn MemoryStore>,
+    definition: MemoryTool,
+}
+
+impl std::fmt::Debug for MemoryExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryExecutor")
+            .field("definition", &self.definition)
+            .finish_non_exhaustive()
+    }
+}
+
+type MemoryResult = std::result::Result<String, String>;
+
+impl MemoryExecutor {
+    /// Run memory calls against `store`
+    pub fn new(store: impl MemoryStore + 'static) -> Self {
+        Self::with_store(Arc::new(store))
+    }
+
+    /// Run memory calls against a shared store
+    pub fn with_store(store: Arc<dyn MemoryStore>) -> Self {
+        Self {
+            store,
+            definition: MemoryTool::new(),
+        }
+    }
+
+    /// Keep memories as files under `root`
+    pub fn filesystem(root: impl Into<PathBuf>) -> Self {
+        Self::new(FileMemoryStore::new(root))
+    }
+
+    /// Keep memories in memory only
+    pub fn in_memory() -> Self {
+        Self::new(InMemoryStore::new())
+    }
+
+    /// Advertise a different tool version or name
+    pub fn with_definition(mut self, definition: MemoryTool) -> Self {
+        self.definition = definition;
+        self
+    }
+
+    /// The backing store
+    pub fn store(&self) -> &Arc<dyn MemoryStore> {
+        &self.store
+    }
+
+    /// Run one memory command and format its result for Claude
+    pub async fn handle(&self, input: &Value) -> ToolOutput {
+        let result = match input["command"].as_str().unwrap_or_default() {
+            "view" => self.view(input).await,
+            "create" => self.create(input).await,
+            "str_replace" => self.str_replace(input).await,
+            "insert" => self.insert(input).await,
+            "delete" => self.delete(input).await,
+            "rename" => self.rename(input).await,
+            other => Err(format!("Error: Unknown command: {}", other)),
+        };
+        match result {
+            Ok(text) => ToolOutput::text(text),
+            Err(e) => ToolOutput::error(e),
+        }
+    }
+
+    async fn view(&self, input: &Value) -> MemoryResult {
+        let (display, path) = path_arg(input, "path")?;
+        if let Some(entries) = self.store.list(&path).await.map_err(io_error)? {
+            let mut lines = vec![format!("{}\t{}", human_size(dir_size(&entries)), display)];
+            for entry in sorted(entries) {
+                lines.push(listing_line(&entry));
+                if entry.is_dir {
+                    let children = self.store.list(&entry.path).await.map_err(io_error)?;
+                    for child in sorted(children.unwrap_or_default()) {
+                        lines.push(listing_line(&child));
+                    }
+                }
+            }
+            return Ok(format!(
+                "Here're the files and directories up to 2 levels deep in {}, excluding hidden items:\n{}",
+                display,
+                lines.join("\n")
+            ));
+        }
+
+        let content = self.read(&display, &path).await?;
+        let lines: Vec<&str> = content.lines().collect();
+        let (start, end) = match input.get("view_range").and_then(Value::as_array) {
+            Some(range) => view_range(range, lines.len()).map_err(error)?,
+            None => (1, lines.len()),
+        };
+        Ok(format!(
+            "Here's the content of {} with line numbers:\n{}",
+            display,
+            number_lines(&lines[start.saturating_sub(1)..end], start)
+        ))
+    }
+
+    async fn create(&self, input: &Value) -> MemoryResult {
+        let (display, path) = path_arg(input, "path")?;
+        let text = string_arg(input, "file_text")?;
+        if path.is_empty() || self.store.list(&path).await.map_err(io_error)?.is_some() {
+            return Err(format!("Error: {} is a directory", display));
+        }
+        if self.store.read(&path).await.map_err(io_error)?.is_some() {
+            return Err(format!("Error: File {} already exists", display));
+        }
+        self.store.write(&path, text).await.map_err(io_error)?;
+        Ok(format!("File created successfully at: {}", display))
+    }
+
+    async fn str_replace(&self, input: &Value) -> MemoryResult {
+        let (display, path) = path_arg(input, "path")?;
+        let old = string_arg(input, "old_str")?;
+        let new = input.get("new_str").and_then(Value::as_str).unwrap_or("");
+        let content = self.read(&display, &path).await?;
+
+        let edit = replace_once(&content, old, new).map_err(|e| match e {
+            ReplaceError::NotFound => format!(
+                "No replacement was performed, old_str `{}` did not appear verbatim in {}.",
+                old, display
+            ),
+            ReplaceError::Ambiguous(lines) => format!(
+                "No replacement was performed. Multiple occurrences of old_str `{}` in lines: {}. Please ensure it is unique",
+                old,
+                lines.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")
+            ),
+        })?;
+
+        self.store
+            .write(&path, &edit.updated)
+            .await
+            .map_err(io_error)?;
+        Ok(format!(
+            "The memory file has been edited.\n{}",
+            snippet(&edit.updated, edit.first, edit.last)
+        ))
+    }
+
+    async fn insert(&self, input: &Value) -> MemoryResult {
+        let (display, path) = path_arg(input, "path")?;
+        let text = string_arg(input, "insert_text")?;
+        let content = self.read(&display, &path).await?;
+        let edit = input
+            .get("insert_line")
+            .and_then(Value::as_u64)
+            .and_then(|after| insert_after(&content, after as usize, text))
+            .ok_or_else(|| {
+                format!(
+                    "Error: Invalid `insert_line` parameter: {}. It should be within the range of lines of the file: [0, {}]",
+                    input.get("insert_line").unwrap_or(&Value::Null),
+                    content.lines().count()
+                )
+            })?;
+
+        self.store
+            .write(&path, &edit.updated)
+            .await
+            .map_err(io_error)?;
+        Ok(format!("The file {} has been edited.", display))
+    }
+
+    async fn delete(&self, input: &Value) -> MemoryResult {
+        let (display, path) = path_arg(input, "path")?;
+        if path.is_empty() {
+            return Err(format!("Error: Cannot delete {}", MEMORY_ROOT));
+        }
+        if !self.store.delete(&path).await.map_err(io_error)? {
+            return Err(missing(&display));
+        }
+        Ok(format!("Successfully deleted {}", display))
+    }
+
+    async fn rename(&self, input: &Value) -> MemoryResult {
+        let (from_display, from) = path_arg(input, "old_path")?;
+        let (to_display, to) = path_arg(input, "new_path")?;
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("Error: Cannot rename {}", MEMORY_ROOT));
+        }
+        if to.starts_with(&format!("{}/", from)) {
+            return Err(format!("Error: Cannot move {} into itself", from_display));
+        }
+        let target_exists = self.store.read(&to).await.map_err(io_error)?.is_some()
+            || self.store.list(&to).await.map_err(io_error)?.is_some();
+        if target_exists {
+            return Err(format!(
+                "Error: The destination {} already exists",
+                to_display
+            ));
+        }
+        if !self.store.rename(&from, &to).await.map_err(io_error)? {
+            return Err(missing(&from_display));
+        }
+        Ok(format!(
+            "Successfully renamed {} to {}",
+            from_display, to_display
+        ))
+    }
+
+    async fn read(&self, display: &str, path: &str) -> MemoryResult {
+        self.store
+            .read(path)
+            .await
+            .map_err(io_error)?
+            .ok_or_else(|| missing(display))
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryExecutor {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn description(&self) -> &str {
+        "Store and retrieve information across conversations"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "enum": ["view", "create", "str_replace", "insert", "delete", "rename"]
+                },
+                "path": {"type": "string"},
+                "view_range": {"type": "array", "items": {"type": "integer"}},
+                "file_text": {"type": "string"},
+                "old_str": {"type": "string"},
+                "new_str": {"type": "string"},
+                "insert_line": {"type": "integer"},
+                "insert_text": {"type": "string"},
+                "old_path": {"type": "string"},
+                "new_path": {"type": "string"}
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn tool_definition(&self) -> ToolDefinition {
+        self.definition.clone().into()
+    }
+
+    async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+        self.handle(&input).await
+    }
+}
+
+/// Validate a path argument, returning it as given and relative to `/memories`
+fn path_arg(input: &Value, name: &str) -> std::result::Result<(String, String), String> {
+    let display = string_arg(input, name)?.trim_end_matches('/').to_string();
+    let relative = match display.strip_prefix(MEMORY_ROOT) {
+        Some("") => "",
+        Some(rest) if rest.starts_with('/') => &rest[1..],
+        _ => {
+            return Err(format!(
+                "Error: The path {} is invalid; memory paths must start with {}",
+                display, MEMORY_ROOT
+            ))
+        }
+    };
+    if relative
+        .split('/')
+        .any(|part| part == ".." || part == "." || (part.is_empty() && !relative.is_empty()))
+        || relative.contains('\\')
+    {
+        return Err(format!(
+            "Error: The path {} is invalid; it may not contain `.`, `..` or empty segments",
+            display
+        ));
+    }
+    let relative = relative.to_string();
+    Ok((display, relative))
+}
+
+fn string_arg<'a>(input: &'a Value, name: &str) -> std::result::Result<&'a str, String> {
+    crate::editing::string_arg(input, name).map_err(error)
+}
+
+fn error(message: String) -> String {
+    format!("Error: {}", message)
+}
+
+fn missing(display: &str) -> String {
+    format!(
+        "The path {} does not exist. Please provide a valid path.",
+        display
+    )
+}
+
+fn io_error(e: io::Error) -> String {
+    error(e.to_string())
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn sorted(entries: Vec<MemoryEntry>) -> Vec<MemoryEntry> {
+    let mut entries: Vec<MemoryEntry> = entries
+        .into_iter()
+        .filter(|e| {
+            !e.path
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .starts_with('.')
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+fn dir_size(entries: &[MemoryEntry]) -> u64 {
+    entries.iter().map(|e| e.size).sum()
+}
+
+fn listing_line(entry: &MemoryEntry) -> String {
+    format!("{}\t{}/{}", human_size(entry.size), MEMORY_ROOT, entry.path)
+}
+
+/// Format a byte count like `du -h`
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolResultContent;
+
+    async fn run(memory: &MemoryExecutor, input: Value) -> (String, bool) {
+        let output = memory.handle(&input).await;
+        match output.content {
+            ToolResultContent::Text(text) => (text, output.is_error),
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    async fn exercise(memory: MemoryExecutor) {
+        let (text, is_error) = run(
+            &memory,
+            json!({"command": "create", "path": "/memories/notes/todo.md", "file_text": "- a\n- b\n"}),
+        )
+        .await;
+        assert!(!is_error, "{}", text);
+        assert_eq!(
+            text,
+            "File created successfully at: /memories/notes/todo.md"
+        );
+
+        let (text, _) = run(
+            &memory,
+            json!({"command": "view", "path": "/memories/notes/todo.md"}),
+        )
+        .await;
+        assert_eq!(
+            text,
+            "Here's the content of /memories/notes/todo.md with line numbers:\n     1\t- a\n     2\t- b"
+        );
+
+        let (text, is_error) = run(
+            &memory,
+            json!({"command": "str_replace", "path": "/memories/notes/todo.md", "old_str": "- b", "new_str": "- c"}),
+        )
+        .await;
+        assert!(!is_error, "{}", text);
+        run(
+            &memory,
+            json!({"command": "insert", "path": "/memories/notes/todo.md", "insert_line": 0, "insert_text": "# Todo"}),
+        )
+        .await;
+        let (text, _) = run(
+            &memory,
+            json!({"command": "view", "path": "/memories/notes/todo.md", "view_range": [2, -1]}),
+        )
+        .await;
+        assert!(text.ends_with("     2\t- a\n     3\t- c"), "{}", text);
+
+        let (text, _) = run(&memory, json!({"command": "view", "path": "/memories"})).await;
+        assert!(
+            text.ends_with("/memories/notes\n15\t/memories/notes/todo.md"),
+            "{}",
+            text
+        );
+
+        let (text, is_error) = run(
+            &memory,
+            json!({"command": "rename", "old_path": "/memories/notes", "new_path": "/memories/archive"}),
+        )
+        .await;
+        assert!(!is_error, "{}", text);
+        let (_, is_error) = run(
+            &memory,
+            json!({"command": "view", "path": "/memories/archive/todo.md"}),
+        )
+        .await;
+        assert!(!is_error);
+
+        let (text, _) = run(
+            &memory,
+            json!({"command": "delete", "path": "/memories/archive"}),
+        )
+        .await;
+        assert_eq!(text, "Successfully deleted /memories/archive");
+        let (text, is_error) = run(
+            &memory,
+            json!({"command": "view", "path": "/memories/archive/todo.md"}),
+        )
+        .await;
+        assert!(is_error);
+        assert_eq!(
+            text,
+            "The path /memories/archive/todo.md does not exist. Please provide a valid path."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store() {
+        exercise(MemoryExecutor::in_memory()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_store() {
+        let dir = std::env::temp_dir().join(format!("claude_sdk_memory_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        exercise(MemoryExecutor::filesystem(&dir)).await;
+        assert!(dir.is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_str_replace_errors() {
+        let memory = MemoryExecutor::in_memory();
+        run(
+            &memory,
+            json!({"command": "create", "path": "/memories/a.txt", "file_text": "x\nx\n"}),
+        )
+        .await;
+
+        let (text, is_error) = run(
+            &memory,
+            json!({"command": "str_replace", "path": "/memories/a.txt", "old_str": "x", "new_str": "y"}),
+        )
+        .await;
+        assert!(is_error);
+        assert_eq!(
+            text,
+            "No replacement was performed. Multiple occurrences of old_str `x` in lines: 1, 2. Please ensure it is unique"
+        );
+
+        let (_, is_error) = run(
+            &memory,
+            json!({"command": "create", "path": "/memories/a.txt", "file_text": "again"}),
+        )
+        .await;
+        assert!(is_error);
+
+        let (text, is_error) = run(
+            &memory,
+            json!({"command": "insert", "path": "/memories/a.txt", "insert_line": 5, "insert_text": "z"}),
+        )
+        .await;
+        assert!(is_error);
+        assert!(text.contains("[0, 2]"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_paths_outside_memories() {
+        let memory = MemoryExecutor::in_memory();
+        for path in [
+            "/etc/passwd",
+            "/memories/../secret",
+            "/memoriesx/a",
+            "notes.md",
+        ] {
+            let (text, is_error) = run(
+                &memory,
+                json!({"command": "create", "path": path, "file_text": "x"}),
+            )
+            .await;
+            assert!(is_error, "{} was allowed", path);
+            assert!(text.contains("is invalid"), "{}", text);
+        }
+        let (_, is_error) = run(&memory, json!({"command": "delete", "path": "/memories"})).await;
+        assert!(is_error);
+    }
+
+    #[test]
+    fn test_advertises_memory_tool() {
+        let memory = MemoryExecutor::in_memory();
+        assert_eq!(memory.name(), "memory");
+        let definition = serde_json::to_value(memory.tool_definition()).unwrap();
+        assert_eq!(definition["type"], "memory_20250818");
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512");
+        assert_eq!(human_size(4096), "4.0K");
+        assert_eq!(human_size(3 * 1024 * 1024 / 2), "1.5M");
+    }
+}
@@ -0,0 +1,598 @@
+//! Size limits for tool output
+//!
+//! A tool that reads logs or greps a large tree can return megabytes, and a
+//! single oversized `tool_result` can push a conversation past the context
+//! window. An [`OutputPolicy`] attached with
+//! [`ToolRegistry::with_output_policy`](crate::ToolRegistry::with_output_policy)
+//! measures every result with [`TokenCounter`] and, when one is over the
+//! limit, shrinks it in one of three ways:
+//!
+//! - [`OutputPolicy::truncate`] keeps the head and tail with a marker
+//!   noting how much was cut
+//! - [`OutputPolicy::summarize`] asks a cheap model to condense it
+//! - [`OutputPolicy::upload`] stores the full output with the Files API and
+//!   returns the file id plus an excerpt
+//!
+//! Summaries and uploads fall back to truncation if the request fails.
+//! Images and documents in a result are kept; only its text is measured
+//! and shrunk.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::models::CLAUDE_HAIKU_4_5;
+//! use claude_sdk::output_policy::OutputPolicy;
+//! use claude_sdk::{ClaudeClient, ToolRegistry};
+//!
+//! # fn example(client: ClaudeClient, registry: ToolRegistry) {
+//! let registry = registry.with_output_policy(
+//!     OutputPolicy::summarize(8_000, client, CLAUDE_HAIKU_4_5.anthropic_id)
+//!         .with_tool_limit("read_log", 20_000),
+//! );
+//! # }
+//! ```
+
+use crate::client::ClaudeClient;
+use crate::files::FilesClient;
+use crate::tokens::TokenCounter;
+use crate::tools::{ToolContext, ToolOutput};
+use crate::types::{ContentBlock, Message, MessagesRequest, ToolResultContent};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Default size of the excerpt returned with an uploaded output
+pub const DEFAULT_EXCERPT_TOKENS: usize = 2_000;
+
+/// Most tokens of an oversized output sent to the summarizing model
+pub const DEFAULT_SUMMARY_INPUT_TOKENS: usize = 100_000;
+
+/// Longest summary requested, whatever the limit
+const MAX_SUMMARY_TOKENS: usize = 4_096;
+
+/// Size of the pieces large outputs are counted in
+const COUNT_CHUNK_BYTES: usize = 4_096;
+
+/// Tokens set aside for the truncation marker or a summary/upload header
+const MARKER_TOKENS: usize = 24;
+
+const SUMMARY_PROMPT: &str = "You condense tool output for another AI assistant. \
+     Keep everything needed to act on it: errors, file paths, line numbers, \
+     identifiers and counts. Reply with the condensed output only.";
+
+/// What happens to output over the limit
+#[derive(Clone)]
+enum Overflow {
+    Truncate,
+    Summarize { client: ClaudeClient, model: String },
+    Upload { files: Arc<FilesClient> },
+}
+
+/// Limits the size of tool results
+#[derive(Clone)]
+pub struct OutputPolicy {
+    max_tokens: usize,
+    tool_limits: HashMap<String, usize>,
+    overflow: Overflow,
+    head_ratio: f64,
+    excerpt_tokens: usize,
+    summary_input_tokens: usize,
+    counter: Arc<TokenCounter>,
+}
+
+impl OutputPolicy {
+    fn with_overflow(max_tokens: usize, overflow: Overflow) -> Self {
+        Self {
+            max_tokens,
+            tool_limits: HashMap::new(),
+            overflow,
+            head_ratio: 0.5,
+            excerpt_tokens: DEFAULT_EXCERPT_TOKENS,
+            summary_input_tokens: DEFAULT_SUMMARY_INPUT_TOKENS,
+            counter: Arc::new(TokenCounter::new()),
+        }
+    }
+
+    /// Cut output over `max_tokens` down to its head and tail
+    pub fn truncate(max_tokens: usize) -> Self {
+        Self::with_overflow(max_tokens, Overflow::Truncate)
+    }
+
+    /// Replace output over `max_tokens` with a summary written by `model`
+    ///
+    /// Use a small, fast model; the summary is asked to fit the limit.
+    pub fn summarize(max_tokens: usize, client: ClaudeClient, model: impl Into<String>) -> Self {
+        Self::with_overflow(
+            max_tokens,
+            Overflow::Summarize {
+                client,
+                model: model.into(),
+            },
+        )
+    }
+
+    /// Upload output over `max_tokens` and return its file id with an excerpt
+    ///
+    /// The file is named `<tool>-<tool_use_id>.txt`, so code execution or a
+    /// later request can load the full output.
+    pub fn upload(max_tokens: usize, files: FilesClient) -> Self {
+        Self::with_overflow(
+            max_tokens,
+            Overflow::Upload {
+                files: Arc::new(files),
+            },
+        )
+    }
+
+    /// Use a different limit for one tool
+    pub fn with_tool_limit(mut self, name: impl Into<String>, max_tokens: usize) -> Self {
+        self.tool_limits.insert(name.into(), max_tokens);
+        self
+    }
+
+    /// Share of a truncated output kept from the start (default 0.5)
+    ///
+    /// The rest comes from the end; use a low ratio for logs, where the
+    /// latest lines matter most.
+    pub fn with_head_ratio(mut self, ratio: f64) -> Self {
+        self.head_ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Size of the excerpt returned with an uploaded output
+    pub fn with_excerpt_tokens(mut self, tokens: usize) -> Self {
+        self.excerpt_tokens = tokens;
+        self
+    }
+
+    /// Most tokens of an oversized output sent to the summarizing model
+    pub fn with_summary_input_tokens(mut self, tokens: usize) -> Self {
+        self.summary_input_tokens = tokens;
+        self
+    }
+
+    /// Limit for the tool `name`
+    pub fn limit_for(&self, name: &str) -> usize {
+        self.tool_limits
+            .get(name)
+            .copied()
+            .unwrap_or(self.max_tokens)
+    }
+
+    /// Apply the policy to one tool's output
+    pub async fn apply(&self, ctx: &ToolContext, mut output: ToolOutput) -> ToolOutput {
+        let limit = self.limit_for(&ctx.tool_name);
+        let tokens = self.count(&text_of(&output.content));
+        if tokens <= limit {
+            return output;
+        }
+        let (text, others) = split_text(std::mem::replace(
+            &mut output.content,
+            ToolResultContent::Blocks(Vec::new()),
+        ));
+
+        debug!(
+            "Output of {} ({}) is {} tokens, over the {} token limit",
+            ctx.tool_name, ctx.tool_use_id, tokens, limit
+        );
+        let text = match &self.overflow {
+            Overflow::Truncate => self.truncate_text(&text, limit),
+            Overflow::Summarize { client, model } => {
+                match self.summary(client, model, ctx, &text, tokens, limit).await {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        warn!("Summarizing output of {} failed: {}", ctx.tool_name, e);
+                        self.truncate_text(&text, limit)
+                    }
+                }
+            }
+            Overflow::Upload { files } => {
+                let filename = format!("{}-{}.txt", ctx.tool_name, ctx.tool_use_id);
+                match files.upload_bytes(filename.clone(), text.as_bytes()).await {
+                    Ok(file) => format!(
+                        "[Output of {} tokens was too large; the full output is in file {} ({})]\n\n{}",
+                        tokens,
+                        file.id,
+                        filename,
+                        self.truncate_text(
+                            &text,
+                            self.excerpt_tokens.min(limit.saturating_sub(MARKER_TOKENS))
+                        )
+                    ),
+                    Err(e) => {
+                        warn!("Uploading output of {} failed: {}", ctx.tool_name, e);
+                        self.truncate_text(&text, limit)
+                    }
+                }
+            }
+        };
+        output.content = join_text(text, others);
+        output
+    }
+
+    async fn summary(
+        &self,
+        client: &ClaudeClient,
+        model: &str,
+        ctx: &ToolContext,
+        text: &str,
+        tokens: usize,
+        limit: usize,
+    ) -> crate::Result<String> {
+        let input = self.truncate_text(text, self.summary_input_tokens);
+        let max_tokens = limit.clamp(1, MAX_SUMMARY_TOKENS) as u32;
+        let request = MessagesRequest::new(
+            model,
+            max_tokens,
+            vec![Message::user(format!(
+                "Output of the `{}` tool ({} tokens):\n\n{}",
+                ctx.tool_name, tokens, input
+            ))],
+        )
+        .with_system(SUMMARY_PROMPT);
+
+        let response = client.send_message(request).await?;
+        let summary: String = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        Ok(format!(
+            "[Output of {} tokens summarized]\n{}",
+            tokens,
+            self.truncate_text(&summary, limit.saturating_sub(MARKER_TOKENS))
+        ))
+    }
+
+    /// Token count of `text`, counted in chunks when it is large
+    ///
+    /// BPE is quadratic on long runs without whitespace (minified JSON,
+    /// base64), so large texts are split at whitespace first.
+    fn count(&self, text: &str) -> usize {
+        let mut total = 0;
+        let mut rest = text;
+        while rest.len() > COUNT_CHUNK_BYTES {
+            let mut end = floor_boundary(rest, COUNT_CHUNK_BYTES);
+            if let Some(space) = rest[..end].rfind(char::is_whitespace) {
+                if space > 0 {
+                    end = space;
+                }
+            }
+            total += self.counter.count_text(&rest[..end]);
+            rest = &rest[end..];
+        }
+        total + self.counter.count_text(rest)
+    }
+
+    /// Keep the head and tail of `text` within `max_tokens`
+    ///
+    /// Cuts fall on line breaks where possible; a marker between head and
+    /// tail records how many tokens and lines were removed.
+    ///
+    /// ```rust
+    /// use claude_sdk::output_policy::OutputPolicy;
+    ///
+    /// let log: String = (0..1000).map(|i| format!("line {}\n", i)).collect();
+    /// let short = OutputPolicy::truncate(100).truncate_text(&log, 100);
+    ///
+    /// assert!(short.starts_with("line 0\n"));
+    /// assert!(short.ends_with("line 999\n"));
+    /// assert!(short.contains("lines) truncated ...]"));
+    /// ```
+    pub fn truncate_text(&self, text: &str, max_tokens: usize) -> String {
+        let total = self.count(text);
+        if total <= max_tokens {
+            return text.to_string();
+        }
+
+        let budget = max_tokens.saturating_sub(MARKER_TOKENS);
+        let head_budget = (budget as f64 * self.head_ratio) as usize;
+        let head = self.head_within(text, head_budget, total);
+        let rest = &text[head.len()..];
+        let tail = self.tail_within(rest, budget - head_budget, total);
+        let omitted = &rest[..rest.len() - tail.len()];
+
+        let separator = if head.is_empty() || head.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        format!(
+            "{}{}[... {} tokens ({} lines) truncated ...]\n{}",
+            head,
+            separator,
+            self.count(omitted),
+            omitted.lines().count(),
+            tail
+        )
+    }
+
+    /// Longest prefix within `budget` tokens, preferring to end after a newline
+    fn head_within<'a>(&self, text: &'a str, budget: usize, total: usize) -> &'a str {
+        let mut end = estimate_bytes(text, budget, total);
+        loop {
+            end = floor_boundary(text, end);
+            if let Some(newline) = text[..end].rfind('\n').filter(|&i| i >= end / 2) {
+                end = newline + 1;
+            }
+            if end == 0 || self.count(&text[..end]) <= budget {
+                return &text[..end];
+            }
+            end = end * 9 / 10;
+        }
+    }
+
+    /// Longest suffix within `budget` tokens, preferring to start after a newline
+    fn tail_within<'a>(&self, text: &'a str, budget: usize, total: usize) -> &'a str {
+        let mut len = estimate_bytes(text, budget, total);
+        loop {
+            let mut start = ceil_boundary(text, text.len() - len.min(text.len()));
+            if let Some(newline) = text[start..]
+                .find('\n')
+                .filter(|&i| i < (text.len() - start) / 2)
+            {
+                start += newline + 1;
+            }
+            if start == text.len() || self.count(&text[start..]) <= budget {
+                return &text[start..];
+            }
+            len = (text.len() - start) * 9 / 10;
+        }
+    }
+}
+
+impl std::fmt::Debug for OutputPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let overflow = match &self.overflow {
+            Overflow::Truncate => "truncate",
+            Overflow::Summarize { .. } => "summarize",
+            Overflow::Upload { .. } => "upload",
+        };
+        f.debug_struct("OutputPolicy")
+            .field("max_tokens", &self.max_tokens)
+            .field("tool_limits", &self.tool_limits)
+            .field("overflow", &overflow)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Bytes of `text` expected to hold `budget` of its `total` tokens
+fn estimate_bytes(text: &str, budget: usize, total: usize) -> usize {
+    (text.len() as u128 * budget as u128 / total.max(1) as u128) as usize
+}
+
+fn floor_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// All text in a result, text blocks joined by newlines
+fn text_of(content: &ToolResultContent) -> Cow<'_, str> {
+    match content {
+        ToolResultContent::Text(text) => Cow::Borrowed(text),
+        ToolResultContent::Blocks(blocks) => Cow::Owned(
+            blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text, .. } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    }
+}
+
+/// The text of a result, and its non-text blocks
+fn split_text(content: ToolResultContent) -> (String, Vec<ContentBlock>) {
+    match content {
+        ToolResultContent::Text(text) => (text, Vec::new()),
+        ToolResultContent::Blocks(blocks) => {
+            let mut texts = Vec::new();
+            let mut others = Vec::new();
+            for block in blocks {
+                match block {
+                    ContentBlock::Text { text, .. } => texts.push(text),
+                    other => others.push(other),
+                }
+            }
+            (texts.join("\n"), others)
+        }
+    }
+}
+
+/// Inverse of [`split_text`]; the text comes first
+fn join_text(text: String, others: Vec<ContentBlock>) -> ToolResultContent {
+    if others.is_empty() {
+        return ToolResultContent::Text(text);
+    }
+    let mut blocks = Vec::with_capacity(others.len() + 1);
+    if !text.is_empty() {
+        blocks.push(ContentBlock::Text {
+            text,
+            cache_control: None,
+            citations: None,
+        });
+    }
+    blocks.extend(others);
+    ToolResultContent::Blocks(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{async_trait, Tool, ToolRegistry};
+    use serde_json::{json, Value};
+
+    fn log(lines: usize) -> String {
+        (0..lines).map(|i| format!("line {}\n", i)).collect()
+    }
+
+    fn ctx() -> ToolContext {
+        ToolContext::new("toolu_1", "read_log")
+    }
+
+    fn text(output: &ToolOutput) -> &str {
+        match &output.content {
+            ToolResultContent::Text(text) => text,
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncate_text() {
+        let policy = OutputPolicy::truncate(200);
+        let counter = TokenCounter::new();
+        let long = log(5_000);
+
+        let short = policy.truncate_text(&long, 200);
+        assert!(counter.count_text(&short) <= 200);
+        assert!(short.starts_with("line 0\n"));
+        assert!(short.ends_with("line 4999\n"));
+        assert!(short.contains("lines) truncated ...]"));
+
+        // Head ratio moves the budget toward the end
+        let tail = policy
+            .clone()
+            .with_head_ratio(0.0)
+            .truncate_text(&long, 200);
+        assert!(tail.starts_with("[... "));
+        assert!(counter.count_text(&tail) <= 200);
+
+        // Text without newlines or with multi-byte characters still fits
+        let wide = "é".repeat(50_000);
+        assert!(counter.count_text(&policy.truncate_text(&wide, 200)) <= 200);
+        assert_eq!(policy.truncate_text("short", 200), "short");
+    }
+
+    #[tokio::test]
+    async fn test_apply_keeps_small_outputs_and_other_blocks() {
+        let policy = OutputPolicy::truncate(100).with_tool_limit("big", 10_000);
+
+        let small = ToolOutput::blocks(vec![ContentBlock::Text {
+            text: "ok".into(),
+            cache_control: None,
+            citations: None,
+        }]);
+        let kept = policy.apply(&ctx(), small).await;
+        assert!(matches!(kept.content, ToolResultContent::Blocks(_)));
+
+        let output = ToolOutput::text(log(1_000))
+            .with(ToolOutput::image("image/png", [1, 2, 3]))
+            .into_error();
+        let shrunk = policy.apply(&ctx(), output).await;
+        assert!(shrunk.is_error);
+        match &shrunk.content {
+            ToolResultContent::Blocks(blocks) => {
+                assert_eq!(blocks.len(), 2);
+                assert!(matches!(blocks[1], ContentBlock::Image { .. }));
+            }
+            other => panic!("Expected blocks, got {:?}", other),
+        }
+
+        let big = ToolContext::new("toolu_2", "big");
+        let output = policy.apply(&big, ToolOutput::text(log(1_000))).await;
+        assert_eq!(text(&output), log(1_000));
+    }
+
+    struct ReadLog;
+
+    #[async_trait]
+    impl Tool for ReadLog {
+        fn name(&self) -> &str {
+            "read_log"
+        }
+
+        fn description(&self) -> &str {
+            "Read the whole log"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn call(&self, _input: Value, _ctx: &ToolContext) -> ToolOutput {
+            ToolOutput::text(log(10_000))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_applies_policy() {
+        let registry = ToolRegistry::new()
+            .with_tool(ReadLog)
+            .with_output_policy(OutputPolicy::truncate(500));
+
+        let output = registry.call("toolu_1", "read_log", json!({})).await;
+        assert!(TokenCounter::new().count_text(text(&output)) <= 500);
+    }
+
+    #[cfg(feature = "testing")]
+    mod remote {
+        use super::*;
+        use crate::testing::{MockResponse, MockRoute, MockServer};
+
+        #[tokio::test]
+        async fn test_summarize() {
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::text("3 errors, all in db.rs"));
+            let policy = OutputPolicy::summarize(100, server.client(), "claude-haiku-4-5")
+                .with_summary_input_tokens(300);
+
+            let output = policy.apply(&ctx(), ToolOutput::text(log(1_000))).await;
+            assert!(text(&output).starts_with("[Output of "));
+            assert!(text(&output).ends_with("summarized]\n3 errors, all in db.rs"));
+
+            let request = &server.message_requests()[0];
+            assert_eq!(request.model, "claude-haiku-4-5");
+            assert_eq!(request.max_tokens, 100);
+            let prompt = TokenCounter::new().count_message(&request.messages[0]);
+            assert!(prompt < 400);
+
+            // Failed summaries fall back to truncation
+            let output = policy.apply(&ctx(), ToolOutput::text(log(1_000))).await;
+            assert!(text(&output).contains("lines) truncated ...]"));
+        }
+
+        #[tokio::test]
+        async fn test_upload() {
+            let server = MockServer::start().await;
+            server.enqueue(
+                MockRoute::Files,
+                MockResponse::json(json!({
+                    "id": "file_1",
+                    "type": "file",
+                    "filename": "read_log-toolu_1.txt",
+                    "mime_type": "text/plain",
+                    "size_bytes": 1,
+                    "created_at": "2026-01-01T00:00:00Z",
+                    "downloadable": true
+                })),
+            );
+            let policy = OutputPolicy::upload(300, server.files_client()).with_excerpt_tokens(100);
+
+            let output = policy.apply(&ctx(), ToolOutput::text(log(1_000))).await;
+            let text = text(&output);
+            assert!(text.contains("the full output is in file file_1 (read_log-toolu_1.txt)"));
+            assert!(text.contains("line 999"));
+            assert!(TokenCounter::new().count_text(text) <= 300);
+
+            let upload = server.requests().pop().unwrap();
+            assert_eq!(upload.path, "/v1/files");
+            assert!(String::from_utf8_lossy(&upload.body).contains("line 500\n"));
+        }
+    }
+}
@@ -0,0 +1,601 @@
+//! Incremental parsing of streamed JSON
+//!
+//! Structured outputs arrive as JSON fragments: text deltas when
+//! `output_config.format` is set, `input_json_delta` events for tool input.
+//! [`PartialJson`] accumulates the fragments and parses the incomplete
+//! document into a [`PartialValue`] snapshot after each one, so a UI can
+//! render fields as they fill in.
+//!
+//! Incomplete input is closed off as follows:
+//!
+//! - unterminated strings keep the characters received so far
+//! - numbers keep their longest valid prefix
+//! - partial `true`/`false`/`null` literals, dangling keys, and keys without
+//!   a value yet are left out
+//! - open objects and arrays are closed
+//!
+//! # Example
+//!
+//! ```rust
+//! use claude_sdk::partial::PartialJson;
+//! use serde_json::json;
+//!
+//! let mut partial = PartialJson::new();
+//! partial.push(r#"{"name": "Ali"#);
+//! assert_eq!(partial.snapshot().unwrap().value, json!({"name": "Ali"}));
+//!
+//! partial.push(r#"ce", "tags": ["a", "b"#);
+//! let snapshot = partial.snapshot().unwrap();
+//! assert_eq!(snapshot.value, json!({"name": "Alice", "tags": ["a", "b"]}));
+//! assert!(snapshot.is_complete("/name"));
+//! assert!(snapshot.is_complete("/tags/0"));
+//! assert!(!snapshot.is_complete("/tags"));
+//! ```
+
+use crate::error::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+
+/// A snapshot of partially received JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialValue {
+    /// The document parsed so far, with open containers closed
+    pub value: Value,
+    /// JSON pointers of values that have been received in full, in
+    /// document order; `""` is the whole document
+    pub completed: Vec<String>,
+}
+
+impl PartialValue {
+    /// Whether the value at `pointer` has been received in full
+    pub fn is_complete(&self, pointer: &str) -> bool {
+        self.completed.iter().any(|p| p == pointer)
+    }
+
+    /// Deserialize the snapshot, e.g. into a type with `Option` fields
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(T::deserialize(&self.value)?)
+    }
+}
+
+/// Accumulates JSON fragments and parses the partial document
+///
+/// Parsing is incremental: each fragment is parsed once, when it is
+/// pushed, and [`snapshot`](Self::snapshot) only assembles the values
+/// parsed so far.
+#[derive(Debug, Clone, Default)]
+pub struct PartialJson {
+    buffer: String,
+    parser: Parser,
+}
+
+impl PartialJson {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a fragment
+    pub fn push(&mut self, fragment: &str) {
+        self.buffer.push_str(fragment);
+        self.parser.feed(fragment);
+    }
+
+    /// Everything received so far
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// The document parsed so far
+    ///
+    /// Returns `None` until a value has started, e.g. while only whitespace
+    /// or a partial literal has arrived.
+    pub fn snapshot(&self) -> Option<PartialValue> {
+        self.parser.snapshot()
+    }
+
+    /// Deserialize the complete document
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Json`] if the input is incomplete or does not match `T`.
+    pub fn finish<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(self.buffer.trim()).map_err(Error::from)
+    }
+}
+
+/// Parse possibly truncated JSON into a snapshot
+pub fn parse_partial(input: &str) -> Option<PartialValue> {
+    let mut parser = Parser::default();
+    parser.feed(input);
+    parser.snapshot()
+}
+
+/// Push parser state carried between fragments
+#[derive(Debug, Clone, Default)]
+struct Parser {
+    /// Open objects and arrays, outermost first
+    stack: Vec<Frame>,
+    /// The string, number or literal being read
+    token: Option<Token>,
+    /// The whole document, once complete
+    root: Option<Value>,
+    completed: Vec<String>,
+    /// Set on input that is not JSON; everything after it is ignored
+    stopped: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    pointer: String,
+    container: Container,
+    expect: Expect,
+}
+
+#[derive(Debug, Clone)]
+enum Container {
+    Object {
+        map: Map<String, Value>,
+        /// Key whose value is being read
+        key: Option<String>,
+    },
+    Array(Vec<Value>),
+}
+
+/// What may come next inside a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Key,
+    Colon,
+    Value,
+    Separator,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    String {
+        text: String,
+        key: bool,
+        /// Characters after a backslash, while an escape is incomplete
+        escape: Option<String>,
+    },
+    Number(String),
+    Literal {
+        word: &'static str,
+        matched: usize,
+    },
+}
+
+/// Result of decoding the characters after a backslash
+enum Escape {
+    Pending,
+    Char(char),
+    /// Not a valid escape: U+FFFD, then the characters from this index
+    /// are read again as string content
+    Invalid(usize),
+}
+
+impl Parser {
+    fn feed(&mut self, input: &str) {
+        for c in input.chars() {
+            if self.stopped {
+                return;
+            }
+            self.feed_char(c);
+        }
+    }
+
+    fn feed_char(&mut self, c: char) {
+        match self.token.take() {
+            Some(Token::String { text, key, escape }) => self.string_char(text, key, escape, c),
+            Some(Token::Number(mut text)) => {
+                if is_number_char(c) {
+                    text.push(c);
+                    self.token = Some(Token::Number(text));
+                    return;
+                }
+                // A number is only known to be complete once a delimiter follows it
+                match serde_json::from_str::<Number>(&text) {
+                    Ok(number) => self.complete(Value::Number(number)),
+                    Err(_) => {
+                        self.stopped = true;
+                        return;
+                    }
+                }
+                self.structural(c);
+            }
+            Some(Token::Literal { word, matched }) => {
+                if word[matched..].starts_with(c) {
+                    if matched + 1 == word.len() {
+                        self.complete(literal_value(word));
+                    } else {
+                        self.token = Some(Token::Literal {
+                            word,
+                            matched: matched + 1,
+                        });
+                    }
+                } else {
+                    self.stopped = true;
+                }
+            }
+            None => self.structural(c),
+        }
+    }
+
+    fn string_char(&mut self, mut text: String, key: bool, escape: Option<String>, c: char) {
+        let Some(mut sequence) = escape else {
+            match c {
+                '"' => self.finish_string(text, key),
+                '\\' => {
+                    self.token = Some(Token::String {
+                        text,
+                        key,
+                        escape: Some(String::new()),
+                    })
+                }
+                c => {
+                    text.push(c);
+                    self.token = Some(Token::String {
+                        text,
+                        key,
+                        escape: None,
+                    });
+                }
+            }
+            return;
+        };
+
+        sequence.push(c);
+        match decode_escape(&sequence) {
+            Escape::Pending => {
+                self.token = Some(Token::String {
+                    text,
+                    key,
+                    escape: Some(sequence),
+                });
+            }
+            Escape::Char(decoded) => {
+                text.push(decoded);
+                self.token = Some(Token::String {
+                    text,
+                    key,
+                    escape: None,
+                });
+            }
+            Escape::Invalid(rest) => {
+                text.push('\u{FFFD}');
+                self.token = Some(Token::String {
+                    text,
+                    key,
+                    escape: None,
+                });
+                for c in sequence.chars().skip(rest) {
+                    self.feed_char(c);
+                }
+            }
+        }
+    }
+
+    /// Handle a character outside any string, number or literal
+    fn structural(&mut self, c: char) {
+        if c.is_whitespace() {
+            return;
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            if self.root.is_none() {
+                self.start_value(c);
+            }
+            return;
+        };
+        let is_object = matches!(frame.container, Container::Object { .. });
+        match (frame.expect, c) {
+            (Expect::Key, '"') => {
+                self.token = Some(Token::String {
+                    text: String::new(),
+                    key: true,
+                    escape: None,
+                })
+            }
+            (Expect::Key, '}') | (Expect::Separator, '}') if is_object => self.close(),
+            (Expect::Value, ']') | (Expect::Separator, ']') if !is_object => self.close(),
+            (Expect::Key, ',') => {}
+            (Expect::Colon, ':') => frame.expect = Expect::Value,
+            (Expect::Separator, ',') => {
+                frame.expect = if is_object {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                }
+            }
+            (Expect::Value, _) => self.start_value(c),
+            _ => self.stopped = true,
+        }
+    }
+
+    fn start_value(&mut self, c: char) {
+        match c {
+            '{' | '[' => {
+                let container = if c == '{' {
+                    Container::Object {
+                        map: Map::new(),
+                        key: None,
+                    }
+                } else {
+                    Container::Array(Vec::new())
+                };
+                self.stack.push(Frame {
+                    pointer: self.child_pointer(),
+                    container,
+                    expect: if c == '{' { Expect::Key } else { Expect::Value },
+                });
+            }
+            '"' => {
+                self.token = Some(Token::String {
+                    text: String::new(),
+                    key: false,
+                    escape: None,
+                })
+            }
+            't' | 'f' | 'n' => {
+                let word = match c {
+                    't' => "true",
+                    'f' => "false",
+                    _ => "null",
+                };
+                self.token = Some(Token::Literal { word, matched: 1 });
+            }
+            '-' | '0'..='9' => self.token = Some(Token::Number(c.to_string())),
+            _ => self.stopped = true,
+        }
+    }
+
+    fn finish_string(&mut self, text: String, key: bool) {
+        if !key {
+            self.complete(Value::String(text));
+            return;
+        }
+        if let Some(Frame {
+            container: Container::Object { key, .. },
+            expect,
+            ..
+        }) = self.stack.last_mut()
+        {
+            *key = Some(text);
+            *expect = Expect::Colon;
+        }
+    }
+
+    /// Close the innermost container
+    fn close(&mut self) {
+        if let Some(frame) = self.stack.pop() {
+            let value = match frame.container {
+                Container::Object { map, .. } => Value::Object(map),
+                Container::Array(items) => Value::Array(items),
+            };
+            self.complete(value);
+        }
+    }
+
+    /// Store a value received in full at the current position
+    fn complete(&mut self, value: Value) {
+        self.completed.push(self.child_pointer());
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+            Some(frame) => {
+                match &mut frame.container {
+                    Container::Object { map, key } => {
+                        if let Some(key) = key.take() {
+                            map.insert(key, value);
+                        }
+                    }
+                    Container::Array(items) => items.push(value),
+                }
+                frame.expect = Expect::Separator;
+            }
+        }
+    }
+
+    /// Pointer of the value starting at the current position
+    fn child_pointer(&self) -> String {
+        match self.stack.last() {
+            None => String::new(),
+            Some(frame) => match &frame.container {
+                Container::Object { key, .. } => format!(
+                    "{}/{}",
+                    frame.pointer,
+                    escape_pointer(key.as_deref().unwrap_or_default())
+                ),
+                Container::Array(items) => format!("{}/{}", frame.pointer, items.len()),
+            },
+        }
+    }
+
+    fn snapshot(&self) -> Option<PartialValue> {
+        // The value being read, closed off as far as it has arrived
+        let mut child = match &self.token {
+            Some(Token::String {
+                text, key: false, ..
+            }) => Some(Value::String(text.clone())),
+            Some(Token::Number(text)) => {
+                serde_json::from_str(text.trim_end_matches(['-', '+', '.', 'e', 'E']))
+                    .ok()
+                    .map(Value::Number)
+            }
+            _ => None,
+        };
+        for frame in self.stack.iter().rev() {
+            child = Some(match &frame.container {
+                Container::Object { map, key } => {
+                    let mut map = map.clone();
+                    if let (Some(key), Some(value)) = (key, child) {
+                        map.insert(key.clone(), value);
+                    }
+                    Value::Object(map)
+                }
+                Container::Array(items) => {
+                    let mut items = items.clone();
+                    items.extend(child);
+                    Value::Array(items)
+                }
+            });
+        }
+        let value = self.root.clone().or(child)?;
+        Some(PartialValue {
+            value,
+            completed: self.completed.clone(),
+        })
+    }
+}
+
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')
+}
+
+fn literal_value(word: &str) -> Value {
+    match word {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Decode the characters after a backslash
+fn decode_escape(sequence: &str) -> Escape {
+    let chars: Vec<char> = sequence.chars().collect();
+    let simple = match chars[0] {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'u' => {
+            if chars.len() < 5 {
+                return Escape::Pending;
+            }
+            let Some(high) = hex4(&chars[1..5]) else {
+                return Escape::Invalid(5);
+            };
+            if !(0xD800..0xDC00).contains(&high) {
+                return Escape::Char(char::from_u32(high).unwrap_or('\u{FFFD}'));
+            }
+            // A high surrogate needs a `\uXXXX` low surrogate after it
+            match chars.get(5..7) {
+                None if chars.get(5).map_or(true, |&c| c == '\\') => return Escape::Pending,
+                Some(['\\', 'u']) => {}
+                _ => return Escape::Invalid(5),
+            }
+            if chars.len() < 11 {
+                return Escape::Pending;
+            }
+            let Some(low) = hex4(&chars[7..11]) else {
+                return Escape::Invalid(11);
+            };
+            let combined = 0x10000 + ((high - 0xD800) << 10) + low.wrapping_sub(0xDC00);
+            return Escape::Char(char::from_u32(combined).unwrap_or('\u{FFFD}'));
+        }
+        other => other,
+    };
+    Escape::Char(simple)
+}
+
+fn hex4(digits: &[char]) -> Option<u32> {
+    let digits: String = digits.iter().collect();
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+/// Escape a key for use in a JSON pointer (RFC 6901)
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn value(input: &str) -> Option<Value> {
+        parse_partial(input).map(|p| p.value)
+    }
+
+    #[test]
+    fn test_closes_open_containers() {
+        assert_eq!(value(""), None);
+        assert_eq!(value("  {"), Some(json!({})));
+        assert_eq!(value(r#"{"a": [1, 2"#), Some(json!({"a": [1, 2]})));
+        assert_eq!(value(r#"{"a": {"b": "te"#), Some(json!({"a": {"b": "te"}})));
+        assert_eq!(value(r#"[{"x": 1}, {"#), Some(json!([{"x": 1}, {}])));
+    }
+
+    #[test]
+    fn test_drops_incomplete_tokens() {
+        assert_eq!(value(r#"{"a": 1, "b"#), Some(json!({"a": 1})));
+        assert_eq!(value(r#"{"a": 1, "b":"#), Some(json!({"a": 1})));
+        assert_eq!(value(r#"{"a": tr"#), Some(json!({})));
+        assert_eq!(value(r#"{"a": nul"#), Some(json!({})));
+        assert_eq!(value(r#"[1.5e"#), Some(json!([1.5])));
+        assert_eq!(value(r#"[-"#), Some(json!([])));
+        assert_eq!(value(r#"["a\"#), Some(json!(["a"])));
+        assert_eq!(value(r#"["\u00e"#), Some(json!([""])));
+        assert_eq!(value(r#"["café 😀"]"#), Some(json!(["café 😀"])));
+    }
+
+    #[test]
+    fn test_tracks_completed_paths() {
+        let snapshot = parse_partial(r#"{"a/b": [1, {"c": true}], "d": 12"#).unwrap();
+        assert_eq!(
+            snapshot.completed,
+            vec!["/a~1b/0", "/a~1b/1/c", "/a~1b/1", "/a~1b"]
+        );
+        // The trailing number may still grow
+        assert!(!snapshot.is_complete("/d"));
+
+        let snapshot = parse_partial(r#"{"d": 12}"#).unwrap();
+        assert!(snapshot.is_complete("/d"));
+        assert!(snapshot.is_complete(""));
+    }
+
+    #[test]
+    fn test_fragments_split_anywhere() {
+        let document =
+            r#"{"a\"b": [1, -2.5e3, true, null], "c": {"d": "\u00e9\ud83d\ude00\n"}, "e": []}"#;
+        let expected: Value = serde_json::from_str(document).unwrap();
+
+        let mut partial = PartialJson::new();
+        for c in document.chars() {
+            partial.push(&c.to_string());
+            assert!(partial.snapshot().is_some());
+        }
+        let snapshot = partial.snapshot().unwrap();
+        assert_eq!(snapshot.value, expected);
+        assert!(snapshot.is_complete(""));
+        assert_eq!(partial.finish::<Value>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_stops_at_invalid_input() {
+        assert_eq!(value(r#"{"a": 1 "b": 2}"#), Some(json!({"a": 1})));
+        assert_eq!(value(r#"[1, tx, 2]"#), Some(json!([1])));
+        assert_eq!(value(r#"{"a": 1} trailing"#), Some(json!({"a": 1})));
+    }
+
+    #[test]
+    fn test_accumulator_finish() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Point {
+            x: i32,
+            y: Option<i32>,
+        }
+
+        let mut partial = PartialJson::new();
+        partial.push(r#"{"x": 3, "#);
+        let point: Point = partial.snapshot().unwrap().parse().unwrap();
+        assert_eq!(point, Point { x: 3, y: None });
+        assert!(partial.finish::<Point>().is_err());
+
+        partial.push(r#""y": 4}"#);
+        assert_eq!(
+            partial.finish::<Point>().unwrap(),
+            Point { x: 3, y: Some(4) }
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Permission policies and human-in-the-loop approval for tool calls
+//!
+//! A [`PermissionPolicy`] sits in front of tool dispatch. Each call is
+//! matched against allow, deny and ask rules by tool name and, optionally,
+//! by a regex over the tool input. Calls that need a decision are passed to
+//! an async approver, which can approve, deny with a reason, or edit the
+//! input before the tool runs.
+//!
+//! Denied calls are answered with an `is_error` tool result carrying the
+//! reason, so Claude sees why and can adjust.
+//!
+//! Attach a policy with [`ToolRegistry::with_permissions`]; it then applies
+//! to [`ToolRegistry::execute`], [`ToolExecutor`](crate::ToolExecutor) and
+//! the [`Agent`](crate::agent::Agent) loop alike.
+//!
+//! # Rule Precedence
+//!
+//! Deny rules win over ask rules, which win over allow rules, regardless of
+//! the order they were added in. Calls that match no rule get the policy's
+//! default permission ([`Permission::Allow`] unless changed).
+//!
+//! # Example
+//!
+//! ```rust
+//! use claude_sdk::permissions::{Approval, InputPattern, PermissionPolicy, PermissionRule};
+//!
+//! # fn example() -> claude_sdk::Result<()> {
+//! let policy = PermissionPolicy::new()
+//!     .with_rule(PermissionRule::allow("read_file"))
+//!     .with_rule(
+//!         PermissionRule::deny("bash")
+//!             .when(InputPattern::field("/command", r"rm\s+-rf")?)
+//!             .with_reason("Recursive deletes are not allowed"),
+//!     )
+//!     .with_rule(PermissionRule::ask("bash"))
+//!     .with_rule(PermissionRule::ask("write_*"))
+//!     .with_approver(|request| async move {
+//!         println!("Allow {} with {}?", request.tool_name, request.input);
+//!         Approval::Approve
+//!     });
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Permissions are resolved before [`ToolExecutor`](crate::ToolExecutor)
+//! timeouts start, so a slow human approver does not time the call out.
+//!
+//! [`ToolRegistry::with_permissions`]: crate::ToolRegistry::with_permissions
+//! [`ToolRegistry::execute`]: crate::ToolRegistry::execute
+
+use crate::error::{Error, Result};
+use futures::future::BoxFuture;
+use regex::Regex;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::debug;
+
+/// What a rule does with a matching call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Run the tool
+    Allow,
+    /// Refuse the call
+    Deny,
+    /// Ask the approver first
+    Ask,
+}
+
+/// A regex matched against a tool's input
+#[derive(Debug, Clone)]
+pub struct InputPattern {
+    pointer: Option<String>,
+    regex: Regex,
+}
+
+impl InputPattern {
+    /// Match anywhere in the JSON-serialized input
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRequest`] if the regex is invalid.
+    pub fn new(regex: &str) -> Result<Self> {
+        Ok(Self {
+            pointer: None,
+            regex: compile(regex)?,
+        })
+    }
+
+    /// Match the input field at a JSON pointer, e.g. `/command`
+    ///
+    /// Strings are matched as is; other values as JSON. Inputs without the
+    /// field do not match.
+    pub fn field(pointer: impl Into<String>, regex: &str) -> Result<Self> {
+        Ok(Self {
+            pointer: Some(pointer.into()),
+            regex: compile(regex)?,
+        })
+    }
+
+    fn matches(&self, input: &Value) -> bool {
+        let value = match &self.pointer {
+            Some(pointer) => match input.pointer(pointer) {
+                Some(value) => value,
+                None => return false,
+            },
+            None => input,
+        };
+        match value {
+            Value::String(s) => self.regex.is_match(s),
+            other => self.regex.is_match(&other.to_string()),
+        }
+    }
+}
+
+fn compile(regex: &str) -> Result<Regex> {
+    Regex::new(regex).map_err(|e| Error::InvalidRequest(format!("Invalid input pattern: {}", e)))
+}
+
+/// Allow, deny or ask for calls to matching tools
+#[derive(Debug, Clone)]
+pub struct PermissionRule {
+    tool: String,
+    permission: Permission,
+    pattern: Option<InputPattern>,
+    reason: Option<String>,
+}
+
+impl PermissionRule {
+    /// Create a rule
+    ///
+    /// `tool` is a tool name, `*` for every tool, or a prefix ending in `*`
+    /// such as `mcp__github__*`.
+    pub fn new(tool: impl Into<String>, permission: Permission) -> Self {
+        Self {
+            tool: tool.into(),
+            permission,
+            pattern: None,
+            reason: None,
+        }
+    }
+
+    /// Allow calls to `tool`
+    pub fn allow(tool: impl Into<String>) -> Self {
+        Self::new(tool, Permission::Allow)
+    }
+
+    /// Deny calls to `tool`
+    pub fn deny(tool: impl Into<String>) -> Self {
+        Self::new(tool, Permission::Deny)
+    }
+
+    /// Ask the approver before calling `tool`
+    pub fn ask(tool: impl Into<String>) -> Self {
+        Self::new(tool, Permission::Ask)
+    }
+
+    /// Only apply to calls whose input matches `pattern`
+    pub fn when(mut self, pattern: InputPattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Reason sent to Claude when this rule denies a call
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    fn matches(&self, name: &str, input: &Value) -> bool {
+        let name_matches = match self.tool.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => self.tool == name,
+        };
+        name_matches && self.pattern.as_ref().map_or(true, |p| p.matches(input))
+    }
+}
+
+/// A call waiting for approval
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ApprovalRequest {
+    /// ID of the `tool_use` block
+    pub tool_use_id: String,
+    /// Name of the tool
+    pub tool_name: String,
+    /// Input Claude sent
+    pub input: Value,
+}
+
+/// The approver's answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Approval {
+    /// Run the tool with the original input
+    Approve,
+    /// Refuse the call, telling Claude why
+    Deny(String),
+    /// Run the tool with a replacement input
+    Edit(Value),
+}
+
+/// Outcome of [`PermissionPolicy::check`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// Run the tool with this input (possibly edited by the approver)
+    Allowed(Value),
+    /// Refuse the call with this reason
+    Denied(String),
+}
+
+type Approver = Arc<dyn Fn(ApprovalRequest) -> BoxFuture<'static, Approval> + Send + Sync>;
+
+/// Rules and approver applied before each tool call
+#[derive(Clone)]
+pub struct PermissionPolicy {
+    rules: Vec<PermissionRule>,
+    default: Permission,
+    approver: Option<Approver>,
+}
+
+impl Default for PermissionPolicy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: Permission::Allow,
+            approver: None,
+        }
+    }
+}
+
+impl PermissionPolicy {
+    /// Create a policy that allows everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule
+    pub fn with_rule(mut self, rule: PermissionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Permission for calls that match no rule (default: allow)
+    pub fn with_default(mut self, permission: Permission) -> Self {
+        self.default = permission;
+        self
+    }
+
+    /// Set the callback that decides [`Permission::Ask`] calls
+    ///
+    /// Without an approver, calls that need approval are denied.
+    pub fn with_approver<F, Fut>(mut self, approver: F) -> Self
+    where
+        F: Fn(ApprovalRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Approval> + Send + 'static,
+    {
+        self.approver = Some(Arc::new(move |request| Box::pin(approver(request))));
+        self
+    }
+
+    /// The permission that applies to a call, and the matching rule
+    fn permission_for(&self, name: &str, input: &Value) -> (Permission, Option<&PermissionRule>) {
+        for permission in [Permission::Deny, Permission::Ask, Permission::Allow] {
+            if let Some(rule) = self
+                .rules
+                .iter()
+                .find(|rule| rule.permission == permission && rule.matches(name, input))
+            {
+                return (permission, Some(rule));
+            }
+        }
+        (self.default, None)
+    }
+
+    /// Decide whether a call may run, asking the approver if needed
+    pub async fn check(&self, tool_use_id: &str, name: &str, input: Value) -> Verdict {
+        match self.permission_for(name, &input) {
+            (Permission::Allow, _) => Verdict::Allowed(input),
+            (Permission::Deny, rule) => {
+                debug!("Denied tool {} ({}) by policy", name, tool_use_id);
+                Verdict::Denied(
+                    rule.and_then(|r| r.reason.clone())
+                        .unwrap_or_else(|| "Denied by policy".into()),
+                )
+            }
+            (Permission::Ask, _) => {
+                let Some(approver) = &self.approver else {
+                    return Verdict::Denied(
+                        "Requires approval, but no approver is configured".into(),
+                    );
+                };
+                let request = ApprovalRequest {
+                    tool_use_id: tool_use_id.to_string(),
+                    tool_name: name.to_string(),
+                    input: input.clone(),
+                };
+                match approver(request).await {
+                    Approval::Approve => Verdict::Allowed(input),
+                    Approval::Edit(edited) => {
+                        debug!("Approver edited input for tool {} ({})", name, tool_use_id);
+                        Verdict::Allowed(edited)
+                    }
+                    Approval::Deny(reason) => Verdict::Denied(reason),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for PermissionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PermissionPolicy")
+            .field("rules", &self.rules)
+            .field("default", &self.default)
+            .field("approver", &self.approver.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bash(command: &str) -> Value {
+        json!({"command": command})
+    }
+
+    #[tokio::test]
+    async fn test_deny_wins_over_allow() {
+        let policy = PermissionPolicy::new()
+            .with_rule(PermissionRule::allow("bash"))
+            .with_rule(
+                PermissionRule::deny("bash")
+                    .when(InputPattern::field("/command", r"rm\s+-rf").unwrap())
+                    .with_reason("No recursive deletes"),
+            );
+
+        assert_eq!(
+            policy.check("t1", "bash", bash("ls -la")).await,
+            Verdict::Allowed(bash("ls -la"))
+        );
+        assert_eq!(
+            policy.check("t2", "bash", bash("rm  -rf /")).await,
+            Verdict::Denied("No recursive deletes".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_and_wildcards() {
+        let policy = PermissionPolicy::new()
+            .with_default(Permission::Deny)
+            .with_rule(PermissionRule::allow("read_*"))
+            .with_rule(PermissionRule::deny("*").when(InputPattern::new("secret").unwrap()));
+
+        assert!(matches!(
+            policy.check("t1", "read_file", json!({"path": "a"})).await,
+            Verdict::Allowed(_)
+        ));
+        assert_eq!(
+            policy
+                .check("t2", "read_file", json!({"path": "secret.txt"}))
+                .await,
+            Verdict::Denied("Denied by policy".into())
+        );
+        assert!(matches!(
+            policy.check("t3", "write_file", json!({})).await,
+            Verdict::Denied(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_approver_decisions() {
+        let policy = PermissionPolicy::new()
+            .with_rule(PermissionRule::ask("bash"))
+            .with_approver(|request| async move {
+                match request.input["command"].as_str() {
+                    Some("make") => Approval::Approve,
+                    Some("make install") => Approval::Edit(json!({"command": "make"})),
+                    _ => Approval::Deny("Not today".into()),
+                }
+            });
+
+        assert_eq!(
+            policy.check("t1", "bash", bash("make")).await,
+            Verdict::Allowed(bash("make"))
+        );
+        assert_eq!(
+            policy.check("t2", "bash", bash("make install")).await,
+            Verdict::Allowed(bash("make"))
+        );
+        assert_eq!(
+            policy.check("t3", "bash", bash("curl")).await,
+            Verdict::Denied("Not today".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ask_without_approver_denies() {
+        let policy = PermissionPolicy::new().with_rule(PermissionRule::ask("*"));
+        assert!(matches!(
+            policy.check("t1", "anything", json!({})).await,
+            Verdict::Denied(_)
+        ));
+        assert!(InputPattern::new("(").is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Programmatic tool calling
+//!
+//! Tools whose [`allowed_callers`](crate::CustomTool::allowed_callers)
+//! include [`CODE_EXECUTION_CALLER`] can be called from code Claude runs in
+//! the code execution tool. Those calls arrive as ordinary `tool_use` blocks
+//! whose `caller` names the `server_tool_use` block running the code, and the
+//! script stays suspended in its container until the results come back.
+//! [`ToolDispatcher`] answers them while keeping the rules the API requires:
+//!
+//! - every `tool_use` gets exactly one `tool_result`, in `tool_use` order
+//! - a user turn answering code execution calls contains only `tool_result`
+//!   blocks
+//! - the next request reuses the container the code is running in
+//! - a call from a caller the tool does not allow is answered with an
+//!   `is_error` result instead of running the tool
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::programmatic::ToolDispatcher;
+//! use claude_sdk::types::CODE_EXECUTION_CALLER;
+//! use claude_sdk::{ClaudeClient, Message, MessagesRequest, StopReason, ToolRegistry};
+//!
+//! # async fn example(client: ClaudeClient, registry: ToolRegistry) -> claude_sdk::Result<()> {
+//! let dispatcher = ToolDispatcher::new(registry);
+//! let mut request = MessagesRequest::new(
+//!     "claude-sonnet-4-5-20250929",
+//!     4096,
+//!     vec![Message::user("Total last quarter's orders by region")],
+//! )
+//! .with_tools(dispatcher.definitions());
+//!
+//! loop {
+//!     let response = client.send_message(request.clone()).await?;
+//!     if response.stop_reason != Some(StopReason::ToolUse) {
+//!         break;
+//!     }
+//!     request = dispatcher.continue_request(request, &response).await;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`CODE_EXECUTION_CALLER`]: crate::types::CODE_EXECUTION_CALLER
+
+use crate::executor::ToolExecutor;
+use crate::tools::{ToolOutput, ToolRegistry};
+use crate::types::{
+    ContentBlock, Message, MessagesRequest, MessagesResponse, Role, ToolCaller, ToolDefinition,
+};
+use tracing::{debug, warn};
+
+/// Where a `tool_use` block came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallOrigin {
+    /// Claude called the tool itself
+    Direct,
+    /// Code running in code execution called the tool
+    CodeExecution {
+        /// Id of the `server_tool_use` block running the code
+        tool_id: Option<String>,
+    },
+}
+
+impl CallOrigin {
+    /// Origin of a `tool_use` block with the given `caller`
+    pub fn of(caller: Option<&ToolCaller>) -> Self {
+        match caller {
+            Some(caller) if caller.is_code_execution() => CallOrigin::CodeExecution {
+                tool_id: caller.tool_id.clone(),
+            },
+            _ => CallOrigin::Direct,
+        }
+    }
+}
+
+/// One answered `tool_use` block
+#[derive(Debug, Clone)]
+pub struct DispatchedCall {
+    /// The `tool_use` id
+    pub id: String,
+    /// Tool name
+    pub name: String,
+    /// Who made the call
+    pub origin: CallOrigin,
+}
+
+/// Results for one assistant turn
+#[derive(Debug, Clone)]
+pub struct Dispatch {
+    /// The calls answered, in `tool_use` order
+    pub calls: Vec<DispatchedCall>,
+    /// One `tool_result` per call, in the same order
+    pub results: Vec<ContentBlock>,
+    /// Container the code execution calls are waiting in
+    pub container: Option<String>,
+}
+
+impl Dispatch {
+    /// Whether any call came from code execution
+    pub fn has_programmatic_calls(&self) -> bool {
+        self.calls
+            .iter()
+            .any(|call| matches!(call.origin, CallOrigin::CodeExecution { .. }))
+    }
+
+    /// The user turn answering the calls
+    pub fn into_message(self) -> Message {
+        Message {
+            role: Role::User,
+            content: self.results,
+        }
+    }
+}
+
+/// Answers direct and code execution tool calls
+#[derive(Debug, Clone)]
+pub struct ToolDispatcher {
+    executor: ToolExecutor,
+}
+
+impl ToolDispatcher {
+    /// Dispatch to the tools in `registry`
+    pub fn new(registry: ToolRegistry) -> Self {
+        Self::with_executor(ToolExecutor::new(registry))
+    }
+
+    /// Dispatch through an executor, running calls concurrently
+    pub fn with_executor(executor: ToolExecutor) -> Self {
+        Self { executor }
+    }
+
+    /// The registry calls are dispatched to
+    pub fn registry(&self) -> &ToolRegistry {
+        self.executor.registry()
+    }
+
+    /// Tool definitions for [`MessagesRequest::with_tools`]
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.registry().definitions()
+    }
+
+    /// Whether the registered tool `name` accepts calls from `caller`
+    ///
+    /// Tools without a custom definition only accept direct calls.
+    fn allows(&self, name: &str, caller: Option<&ToolCaller>) -> bool {
+        match self.registry().get(name).map(|tool| tool.tool_definition()) {
+            Some(ToolDefinition::Custom(definition)) => definition.allows_caller(caller),
+            // Unknown tools are answered by the registry
+            None => true,
+            Some(ToolDefinition::Server(_)) => CallOrigin::of(caller) == CallOrigin::Direct,
+        }
+    }
+
+    /// Answer every `tool_use` block in a response
+    pub async fn dispatch(&self, response: &MessagesResponse) -> Dispatch {
+        let mut calls = Vec::new();
+        let mut rejected = Vec::new();
+        let mut allowed = response.clone();
+        allowed.content.clear();
+
+        for block in &response.content {
+            let ContentBlock::ToolUse {
+                id, name, caller, ..
+            } = block
+            else {
+                continue;
+            };
+            let origin = CallOrigin::of(caller.as_ref());
+            if self.allows(name, caller.as_ref()) {
+                allowed.content.push(block.clone());
+            } else {
+                warn!(
+                    "Tool {} ({}) does not accept calls from {:?}",
+                    name, id, origin
+                );
+                rejected.push(calls.len());
+            }
+            calls.push(DispatchedCall {
+                id: id.clone(),
+                name: name.clone(),
+                origin,
+            });
+        }
+
+        let mut executed = self.executor.execute(&allowed).await.into_iter();
+        let results = calls
+            .iter()
+            .enumerate()
+            .map(|(index, call)| {
+                if rejected.contains(&index) {
+                    let message = match call.origin {
+                        CallOrigin::Direct => {
+                            format!(
+                                "Tool '{}' can only be called from code execution",
+                                call.name
+                            )
+                        }
+                        CallOrigin::CodeExecution { .. } => {
+                            format!("Tool '{}' cannot be called from code execution", call.name)
+                        }
+                    };
+                    ToolOutput::error(message).into_block(call.id.clone())
+                } else {
+                    executed
+                        .next()
+                        .expect("executor answers every allowed call")
+                }
+            })
+            .collect();
+
+        let dispatch = Dispatch {
+            calls,
+            results,
+            container: response.container.as_ref().map(|c| c.id.clone()),
+        };
+        debug!(
+            "Dispatched {} tool calls (programmatic: {})",
+            dispatch.calls.len(),
+            dispatch.has_programmatic_calls()
+        );
+        dispatch
+    }
+
+    /// Extend `request` with the assistant turn and its tool results
+    ///
+    /// The response content is appended as the assistant turn, the results
+    /// as the next user turn, and the response's container is carried
+    /// forward so suspended code can resume.
+    pub async fn continue_request(
+        &self,
+        mut request: MessagesRequest,
+        response: &MessagesResponse,
+    ) -> MessagesRequest {
+        let dispatch = self.dispatch(response).await;
+        if let Some(container) = &dispatch.container {
+            request = request.with_container(container.clone());
+        }
+        request.messages.push(Message {
+            role: Role::Assistant,
+            content: response.content.clone(),
+        });
+        request.messages.push(dispatch.into_message());
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{async_trait, Tool, ToolContext};
+    use crate::types::{CustomTool, CODE_EXECUTION_CALLER, DIRECT_CALLER};
+    use serde_json::{json, Value};
+
+    struct Query {
+        callers: &'static [&'static str],
+    }
+
+    #[async_trait]
+    impl Tool for Query {
+        fn name(&self) -> &str {
+            "query"
+        }
+
+        fn description(&self) -> &str {
+            "Run a query"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        fn definition(&self) -> CustomTool {
+            CustomTool::new(self.name(), self.description(), self.input_schema())
+                .with_allowed_callers(self.callers.iter().copied())
+        }
+
+        async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+            ToolOutput::json(&input)
+        }
+    }
+
+    fn response() -> MessagesResponse {
+        serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "server_tool_use", "id": "srvtoolu_1", "name": "code_execution",
+                 "input": {"code": "query({'n': 1})"}},
+                {"type": "tool_use", "id": "tu_1", "name": "query", "input": {"n": 1},
+                 "caller": {"type": "code_execution_20250825", "tool_id": "srvtoolu_1"}},
+                {"type": "tool_use", "id": "tu_2", "name": "query", "input": {"n": 2},
+                 "caller": {"type": "direct"}}
+            ],
+            "model": "claude-sonnet-4-5-20250929",
+            "stop_reason": "tool_use",
+            "container": {"id": "ctr_1", "expires_at": "2026-01-01T00:00:00Z"},
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }))
+        .unwrap()
+    }
+
+    fn result_text(block: &ContentBlock) -> (String, bool) {
+        let ContentBlock::ToolResult {
+            content, is_error, ..
+        } = block
+        else {
+            panic!("Expected ToolResult, got {:?}", block);
+        };
+        let text = match content {
+            Some(crate::types::ToolResultContent::Text(text)) => text.clone(),
+            other => format!("{:?}", other),
+        };
+        (text, is_error.unwrap_or(false))
+    }
+
+    #[tokio::test]
+    async fn test_tells_origins_apart() {
+        let registry = ToolRegistry::new().with_tool(Query {
+            callers: &[DIRECT_CALLER, CODE_EXECUTION_CALLER],
+        });
+        let dispatch = ToolDispatcher::new(registry).dispatch(&response()).await;
+
+        assert_eq!(dispatch.calls.len(), 2);
+        assert_eq!(
+            dispatch.calls[0].origin,
+            CallOrigin::CodeExecution {
+                tool_id: Some("srvtoolu_1".into())
+            }
+        );
+        assert_eq!(dispatch.calls[1].origin, CallOrigin::Direct);
+        assert!(dispatch.has_programmatic_calls());
+        assert_eq!(dispatch.container.as_deref(), Some("ctr_1"));
+        assert_eq!(
+            result_text(&dispatch.results[0]),
+            (r#"{"n":1}"#.into(), false)
+        );
+        assert_eq!(
+            result_text(&dispatch.results[1]),
+            (r#"{"n":2}"#.into(), false)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_disallowed_callers() {
+        let registry = ToolRegistry::new().with_tool(Query {
+            callers: &[CODE_EXECUTION_CALLER],
+        });
+        let dispatch = ToolDispatcher::new(registry).dispatch(&response()).await;
+
+        // Results stay paired with their calls
+        assert_eq!(
+            result_text(&dispatch.results[0]),
+            (r#"{"n":1}"#.into(), false)
+        );
+        let (text, is_error) = result_text(&dispatch.results[1]);
+        assert!(is_error);
+        assert_eq!(text, "Tool 'query' can only be called from code execution");
+    }
+
+    #[tokio::test]
+    async fn test_continue_request_carries_container() {
+        let registry = ToolRegistry::new().with_tool(Query {
+            callers: &[DIRECT_CALLER, CODE_EXECUTION_CALLER],
+        });
+        let request = MessagesRequest::new(
+            "claude-sonnet-4-5-20250929",
+            1024,
+            vec![Message::user("Run it")],
+        );
+        let request = ToolDispatcher::new(registry)
+            .continue_request(request, &response())
+            .await;
+
+        assert_eq!(request.container.as_deref(), Some("ctr_1"));
+        assert_eq!(request.messages.len(), 3);
+        assert_eq!(request.messages[1].content.len(), 3);
+        let answer = &request.messages[2];
+        assert_eq!(answer.role, Role::User);
+        assert_eq!(answer.content.len(), 2);
+        assert!(answer
+            .content
+            .iter()
+            .all(|block| matches!(block, ContentBlock::ToolResult { .. })));
+    }
+}
@@ -0,0 +1,534 @@
+//! SSE relay for forwarding Claude streams to browsers (requires `relay` feature).
+//!
+//! [`SseRelay`] turns the stream returned by
+//! [`ClaudeClient::send_streaming`](crate::ClaudeClient::send_streaming) into an
+//! [axum](https://docs.rs/axum) SSE response that keeps the Anthropic event framing:
+//! every event is written with its `event:` name (`message_start`,
+//! `content_block_delta`, ...) and the same JSON payload the API sends. Browser
+//! clients can therefore parse relayed streams exactly like direct API streams.
+//!
+//! # Features
+//!
+//! - **Filtering**: drop thinking blocks with [`SseRelay::without_thinking`] or
+//!   any event with [`SseRelay::with_filter`]
+//! - **Keepalive**: emit `ping` events while the upstream stream is idle
+//! - **Errors**: [`StreamEvent::Error`] events and SDK errors are forwarded as
+//!   `error` events, after which the relayed stream ends
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use axum::{extract::State, response::Response, routing::post, Json, Router};
+//! use claude_sdk::relay::SseRelay;
+//! use claude_sdk::{ClaudeClient, MessagesRequest};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! async fn chat(
+//!     State(client): State<Arc<ClaudeClient>>,
+//!     Json(request): Json<MessagesRequest>,
+//! ) -> Response {
+//!     let relay = SseRelay::new()
+//!         .without_thinking()
+//!         .with_keep_alive(Duration::from_secs(15));
+//!
+//!     match client.send_streaming(request).await {
+//!         Ok(stream) => relay.into_response(stream),
+//!         // Errors before the stream starts are relayed as a single `error` event
+//!         Err(e) => relay.into_response(futures::stream::once(async { Err(e) })),
+//!     }
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(ClaudeClient::anthropic(std::env::var("ANTHROPIC_API_KEY")?));
+//! let app = Router::new().route("/chat", post(chat)).with_state(client);
+//!
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+//! axum::serve(listener, app).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Error;
+use crate::streaming::{StreamError, StreamEvent};
+use crate::types::ContentBlock;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Predicate deciding whether an event is forwarded to the client
+type EventFilter = Arc<dyn Fn(&StreamEvent) -> bool + Send + Sync>;
+
+/// Relays Claude streaming events to HTTP clients as server-sent events
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::relay::SseRelay;
+/// use claude_sdk::StreamEvent;
+/// use std::time::Duration;
+///
+/// let relay = SseRelay::new()
+///     .without_thinking()
+///     .with_filter(|event| !matches!(event, StreamEvent::Ping))
+///     .with_keep_alive(Duration::from_secs(10));
+/// ```
+#[derive(Clone, Default)]
+pub struct SseRelay {
+    drop_thinking: bool,
+    filter: Option<EventFilter>,
+    keep_alive: Option<Duration>,
+}
+
+impl SseRelay {
+    /// Create a relay that forwards every event unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop thinking and redacted thinking blocks from the relayed stream
+    ///
+    /// The remaining content blocks are re-indexed so the client still sees
+    /// contiguous `index` values starting at 0.
+    pub fn without_thinking(mut self) -> Self {
+        self.drop_thinking = true;
+        self
+    }
+
+    /// Only forward events for which `filter` returns `true`
+    ///
+    /// The filter is applied per event, after thinking blocks have been removed.
+    /// `error` events are always forwarded.
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&StreamEvent) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Send a `ping` event whenever the upstream stream is idle for `interval`
+    pub fn with_keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Convert an SDK event stream into a stream of SSE events
+    ///
+    /// The returned stream ends after `message_stop` or after the first error.
+    pub fn into_event_stream<S>(self, stream: S) -> impl Stream<Item = Event> + Send + 'static
+    where
+        S: Stream<Item = crate::error::Result<StreamEvent>> + Send + 'static,
+    {
+        let mut stream = Box::pin(stream);
+        let mut reindexer = Reindexer::new(self.drop_thinking);
+        let filter = self.filter;
+
+        async_stream::stream! {
+            while let Some(item) = stream.next().await {
+                let event = match item {
+                    Ok(event) => event,
+                    Err(e) => {
+                        yield to_sse_event(&StreamEvent::Error { error: stream_error(&e) });
+                        break;
+                    }
+                };
+
+                match event {
+                    StreamEvent::Error { .. } => {
+                        yield to_sse_event(&event);
+                        break;
+                    }
+                    StreamEvent::MessageStop => {
+                        if filter.as_ref().map_or(true, |f| f(&event)) {
+                            yield to_sse_event(&event);
+                        }
+                        break;
+                    }
+                    event => {
+                        let Some(event) = reindexer.apply(event) else {
+                            continue;
+                        };
+                        if filter.as_ref().map_or(true, |f| f(&event)) {
+                            yield to_sse_event(&event);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Build an SSE response from an SDK event stream
+    ///
+    /// Sets `content-type: text/event-stream` and, if configured, keepalive pings.
+    pub fn into_response<S>(self, stream: S) -> Response
+    where
+        S: Stream<Item = crate::error::Result<StreamEvent>> + Send + 'static,
+    {
+        let keep_alive = self.keep_alive;
+        let events = self.into_event_stream(stream).map(Ok::<_, Infallible>);
+        let sse = Sse::new(events);
+
+        match keep_alive {
+            Some(interval) => sse
+                .keep_alive(
+                    KeepAlive::new()
+                        .interval(interval)
+                        .event(to_sse_event(&StreamEvent::Ping)),
+                )
+                .into_response(),
+            None => sse.into_response(),
+        }
+    }
+}
+
+impl std::fmt::Debug for SseRelay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SseRelay")
+            .field("drop_thinking", &self.drop_thinking)
+            .field("filter", &self.filter.is_some())
+            .field("keep_alive", &self.keep_alive)
+            .finish()
+    }
+}
+
+/// Encode a stream event using the Anthropic SSE framing
+///
+/// The `event:` name is the event's `type` and the data is its JSON encoding.
+pub fn to_sse_event(event: &StreamEvent) -> Event {
+    let data = serde_json::to_string(event).expect("Failed to serialize StreamEvent");
+    Event::default().event(event_name(event)).data(data)
+}
+
+/// SSE event name for a stream event
+fn event_name(event: &StreamEvent) -> &'static str {
+    match event {
+        StreamEvent::MessageStart { .. } => "message_start",
+        StreamEvent::ContentBlockStart { .. } => "content_block_start",
+        StreamEvent::ContentBlockDelta { .. } => "content_block_delta",
+        StreamEvent::ContentBlockStop { .. } => "content_block_stop",
+        StreamEvent::MessageDelta { .. } => "message_delta",
+        StreamEvent::MessageStop => "message_stop",
+        StreamEvent::Ping => "ping",
+        StreamEvent::Error { .. } => "error",
+    }
+}
+
+/// Map an SDK error onto the error payload of an Anthropic `error` event
+fn stream_error(error: &Error) -> StreamError {
+    let error_type = match error {
+        Error::RateLimit { .. } => "rate_limit_error".to_string(),
+        Error::Authentication(_) => "authentication_error".to_string(),
+        Error::InvalidRequest(_) => "invalid_request_error".to_string(),
+        Error::Server { status: 529, .. } => "overloaded_error".to_string(),
+        Error::Api {
+            error_type: Some(error_type),
+            ..
+        } => error_type.clone(),
+        _ => "api_error".to_string(),
+    };
+
+    StreamError {
+        error_type,
+        message: error.to_string(),
+    }
+}
+
+/// Drops thinking blocks and keeps the remaining block indices contiguous
+struct Reindexer {
+    drop_thinking: bool,
+    /// Original block index -> relayed index (`None` for dropped blocks)
+    indices: HashMap<usize, Option<usize>>,
+    next_index: usize,
+}
+
+impl Reindexer {
+    fn new(drop_thinking: bool) -> Self {
+        Self {
+            drop_thinking,
+            indices: HashMap::new(),
+            next_index: 0,
+        }
+    }
+
+    fn apply(&mut self, event: StreamEvent) -> Option<StreamEvent> {
+        if !self.drop_thinking {
+            return Some(event);
+        }
+
+        match event {
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                let dropped = matches!(
+                    content_block,
+                    ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. }
+                );
+                if dropped {
+                    self.indices.insert(index, None);
+                    return None;
+                }
+                let new_index = self.next_index;
+                self.next_index += 1;
+                self.indices.insert(index, Some(new_index));
+                Some(StreamEvent::ContentBlockStart {
+                    index: new_index,
+                    content_block,
+                })
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => self
+                .lookup(index)
+                .map(|index| StreamEvent::ContentBlockDelta { index, delta }),
+            StreamEvent::ContentBlockStop { index } => self
+                .lookup(index)
+                .map(|index| StreamEvent::ContentBlockStop { index }),
+            event => Some(event),
+        }
+    }
+
+    fn lookup(&self, index: usize) -> Option<usize> {
+        // Blocks we never saw start are passed through unchanged
+        self.indices.get(&index).copied().unwrap_or(Some(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::{ContentDelta, MessageDelta, MessageMetadata};
+    use crate::types::{Role, StopReason, Usage};
+    use axum::routing::get;
+    use axum::Router;
+    use eventsource_stream::Eventsource;
+
+    fn usage() -> Usage {
+        serde_json::from_value(serde_json::json!({"input_tokens": 10, "output_tokens": 5})).unwrap()
+    }
+
+    fn thinking_conversation() -> Vec<crate::error::Result<StreamEvent>> {
+        vec![
+            Ok(StreamEvent::MessageStart {
+                message: MessageMetadata {
+                    id: "msg_1".into(),
+                    message_type: "message".into(),
+                    role: Role::Assistant,
+                    content: vec![],
+                    model: "claude-sonnet-4-5-20250929".into(),
+                    stop_reason: None,
+                    stop_sequence: None,
+                    usage: usage(),
+                },
+            }),
+            Ok(StreamEvent::ContentBlockStart {
+                index: 0,
+                content_block: ContentBlock::Thinking {
+                    thinking: String::new(),
+                    signature: None,
+                },
+            }),
+            Ok(StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::ThinkingDelta {
+                    thinking: "Let me think".into(),
+                },
+            }),
+            Ok(StreamEvent::ContentBlockStop { index: 0 }),
+            Ok(StreamEvent::ContentBlockStart {
+                index: 1,
+                content_block: ContentBlock::Text {
+                    text: String::new(),
+                    cache_control: None,
+                    citations: None,
+                },
+            }),
+            Ok(StreamEvent::ContentBlockDelta {
+                index: 1,
+                delta: ContentDelta::TextDelta {
+                    text: "Hello".into(),
+                },
+            }),
+            Ok(StreamEvent::ContentBlockStop { index: 1 }),
+            Ok(StreamEvent::MessageDelta {
+                delta: MessageDelta {
+                    stop_reason: Some(StopReason::EndTurn),
+                    stop_sequence: None,
+                },
+                usage: usage(),
+            }),
+            Ok(StreamEvent::MessageStop),
+        ]
+    }
+
+    /// Serve `relay` over a local axum server and collect `(event, data)` pairs
+    async fn relay_over_http<F, S>(make_stream: F, relay: SseRelay) -> Vec<(String, String)>
+    where
+        F: Fn() -> S + Clone + Send + Sync + 'static,
+        S: Stream<Item = crate::error::Result<StreamEvent>> + Send + 'static,
+    {
+        let app = Router::new().route(
+            "/stream",
+            get(move || {
+                let relay = relay.clone();
+                let make_stream = make_stream.clone();
+                async move { relay.into_response(make_stream()) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let response = reqwest::get(format!("http://{}/stream", addr))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["content-type"].to_str().unwrap(),
+            "text/event-stream"
+        );
+
+        response
+            .bytes_stream()
+            .eventsource()
+            .map(|event| {
+                let event = event.unwrap();
+                (event.event, event.data)
+            })
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_relay_preserves_anthropic_framing() {
+        let events = relay_over_http(
+            || futures::stream::iter(thinking_conversation()),
+            SseRelay::new(),
+        )
+        .await;
+
+        let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names[0], "message_start");
+        assert_eq!(names.last(), Some(&"message_stop"));
+        assert_eq!(events.len(), 9);
+
+        // Every data payload round-trips into the same event type
+        for (name, data) in &events {
+            let event: StreamEvent = serde_json::from_str(data).unwrap();
+            assert_eq!(event_name(&event), name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_without_thinking_reindexes_blocks() {
+        let events = relay_over_http(
+            || futures::stream::iter(thinking_conversation()),
+            SseRelay::new().without_thinking(),
+        )
+        .await;
+
+        assert_eq!(events.len(), 6);
+        for (_, data) in &events {
+            match serde_json::from_str::<StreamEvent>(data).unwrap() {
+                StreamEvent::ContentBlockStart {
+                    index,
+                    content_block,
+                } => {
+                    assert_eq!(index, 0);
+                    assert!(matches!(content_block, ContentBlock::Text { .. }));
+                }
+                StreamEvent::ContentBlockDelta { index, delta } => {
+                    assert_eq!(index, 0);
+                    assert_eq!(delta.text(), Some("Hello"));
+                }
+                StreamEvent::ContentBlockStop { index } => assert_eq!(index, 0),
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_filter() {
+        let events = relay_over_http(
+            || futures::stream::iter(thinking_conversation()),
+            SseRelay::new().with_filter(|event| !matches!(event, StreamEvent::MessageDelta { .. })),
+        )
+        .await;
+
+        assert!(events.iter().all(|(name, _)| name != "message_delta"));
+        assert_eq!(events.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_relay_propagates_sdk_error() {
+        let events = relay_over_http(
+            || {
+                futures::stream::iter(vec![
+                    Ok(StreamEvent::Ping),
+                    Err(Error::RateLimit {
+                        retry_after: Some(5),
+                        message: "slow down".into(),
+                    }),
+                    // Never relayed: the stream ends after the first error
+                    Ok(StreamEvent::MessageStop),
+                ])
+            },
+            SseRelay::new(),
+        )
+        .await;
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].0, "error");
+        match serde_json::from_str::<StreamEvent>(&events[1].1).unwrap() {
+            StreamEvent::Error { error } => {
+                assert_eq!(error.error_type, "rate_limit_error");
+                assert!(error.message.contains("Rate limit"));
+            }
+            other => panic!("Expected error event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_forwards_stream_error_event() {
+        let events = relay_over_http(
+            || {
+                futures::stream::iter(vec![
+                    Ok(StreamEvent::Error {
+                        error: StreamError {
+                            error_type: "overloaded_error".into(),
+                            message: "Overloaded".into(),
+                        },
+                    }),
+                    Ok(StreamEvent::MessageStop),
+                ])
+            },
+            SseRelay::new(),
+        )
+        .await;
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "error");
+        assert!(events[0].1.contains("overloaded_error"));
+    }
+
+    #[tokio::test]
+    async fn test_relay_keep_alive_pings() {
+        let events = relay_over_http(
+            || {
+                async_stream::stream! {
+                    tokio::time::sleep(Duration::from_millis(250)).await;
+                    yield Ok(StreamEvent::MessageStop);
+                }
+            },
+            SseRelay::new().with_keep_alive(Duration::from_millis(50)),
+        )
+        .await;
+
+        let pings = events.iter().filter(|(name, _)| name == "ping").count();
+        assert!(pings >= 2, "expected keepalive pings, got {:?}", events);
+        assert_eq!(events.last().unwrap().0, "message_stop");
+        assert_eq!(events.last().unwrap().1, r#"{"type":"message_stop"}"#);
+    }
+}
@@ -1,9 +1,22 @@
-//! Server-side tool definitions for Claude API built-in tools.
+//! Tool definitions for Claude API built-in tools.
 //!
-//! These tools are executed by Claude's infrastructure, not by your code.
-//! Add them to `MessagesRequest.tools` to enable server-side capabilities.
+//! Add them to `MessagesRequest.tools` to enable built-in capabilities.
+//!
+//! Most of these tools (web search, web fetch, code execution, tool search)
+//! are executed by Claude's infrastructure. The [`BashTool`],
+//! [`TextEditorTool`] and [`MemoryTool`] definitions describe client tools:
+//! their calls come back as `tool_use` blocks that your code executes and
+//! answers with `tool_result` blocks. With the `local-tools` feature,
+//! `bash::BashExecutor` runs `bash` calls locally and
+//! `text_editor::TextEditorExecutor` runs text editor calls;
+//! [`crate::memory::MemoryExecutor`] handles memory calls in any build.
+//!
+//! Results of server-executed tools come back as content blocks whose
+//! content is typed here, e.g. [`WebSearchToolResultContent`] and
+//! [`CodeExecutionToolResultContent`]; failures carry a
+//! [`ServerToolErrorCode`].
 
-use crate::types::CacheControl;
+use crate::types::{CacheControl, CitationConfig};
 use serde::{Deserialize, Serialize};
 
 /// Web search tool — lets Claude search the internet during a response.
@@ -241,7 +254,10 @@ impl Default for CodeExecutionTool {
     }
 }
 
-/// Bash tool — lets Claude execute bash commands in a sandboxed environment.
+/// Bash tool — lets Claude execute bash commands.
+///
+/// A client tool: each call arrives as a `tool_use` block with a `command`
+/// (or `restart: true`) input that your code runs.
 ///
 /// # Example
 ///
@@ -509,6 +525,354 @@ impl From<ToolSearchRegex> for crate::types::ToolDefinition {
     }
 }
 
+// --- Result content ---
+
+/// Error code reported by a server tool
+///
+/// Codes this SDK does not know are kept in [`Other`](Self::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ServerToolErrorCode {
+    /// The tool input was malformed
+    InvalidToolInput,
+    /// The search input was malformed
+    InvalidInput,
+    /// The tool's `max_uses` limit was reached
+    MaxUsesExceeded,
+    /// The tool is rate limited
+    TooManyRequests,
+    /// The search query is too long
+    QueryTooLong,
+    /// The tool is temporarily unavailable
+    Unavailable,
+    /// The URL is too long to fetch
+    UrlTooLong,
+    /// The URL is blocked by the domain settings
+    UrlNotAllowed,
+    /// The URL could not be fetched
+    UrlNotAccessible,
+    /// The fetched content type is not supported
+    UnsupportedContentType,
+    /// Code ran longer than allowed
+    CodeExecutionExceeded,
+    /// The container is no longer available
+    ContainerExpired,
+    /// A command ran longer than allowed
+    ExecutionTimeExceeded,
+    /// An output file is too large to keep
+    OutputFileTooLarge,
+    /// The file does not exist in the container
+    FileNotFound,
+    /// A code this SDK does not know yet
+    Other(String),
+}
+
+impl ServerToolErrorCode {
+    /// The code as sent by the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::InvalidToolInput => "invalid_tool_input",
+            Self::InvalidInput => "invalid_input",
+            Self::MaxUsesExceeded => "max_uses_exceeded",
+            Self::TooManyRequests => "too_many_requests",
+            Self::QueryTooLong => "query_too_long",
+            Self::Unavailable => "unavailable",
+            Self::UrlTooLong => "url_too_long",
+            Self::UrlNotAllowed => "url_not_allowed",
+            Self::UrlNotAccessible => "url_not_accessible",
+            Self::UnsupportedContentType => "unsupported_content_type",
+            Self::CodeExecutionExceeded => "code_execution_exceeded",
+            Self::ContainerExpired => "container_expired",
+            Self::ExecutionTimeExceeded => "execution_time_exceeded",
+            Self::OutputFileTooLarge => "output_file_too_large",
+            Self::FileNotFound => "file_not_found",
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl From<String> for ServerToolErrorCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "invalid_tool_input" => Self::InvalidToolInput,
+            "invalid_input" => Self::InvalidInput,
+            "max_uses_exceeded" => Self::MaxUsesExceeded,
+            "too_many_requests" => Self::TooManyRequests,
+            "query_too_long" => Self::QueryTooLong,
+            "unavailable" => Self::Unavailable,
+            "url_too_long" => Self::UrlTooLong,
+            "url_not_allowed" => Self::UrlNotAllowed,
+            "url_not_accessible" => Self::UrlNotAccessible,
+            "unsupported_content_type" => Self::UnsupportedContentType,
+            "code_execution_exceeded" => Self::CodeExecutionExceeded,
+            "container_expired" => Self::ContainerExpired,
+            "execution_time_exceeded" => Self::ExecutionTimeExceeded,
+            "output_file_too_large" => Self::OutputFileTooLarge,
+            "file_not_found" => Self::FileNotFound,
+            _ => Self::Other(code),
+        }
+    }
+}
+
+impl From<ServerToolErrorCode> for String {
+    fn from(code: ServerToolErrorCode) -> String {
+        match code {
+            ServerToolErrorCode::Other(code) => code,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ServerToolErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error result from a server tool, such as `web_search_tool_result_error`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerToolError {
+    /// Error type, e.g. `"web_search_tool_result_error"`
+    #[serde(rename = "type")]
+    pub error_type: String,
+
+    /// What went wrong
+    pub error_code: ServerToolErrorCode,
+
+    /// Details, when the tool provides them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// Content of a `web_search_tool_result` block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WebSearchToolResultContent {
+    /// Pages found by the search
+    Results(Vec<WebSearchResult>),
+    /// The search failed
+    Error(ServerToolError),
+}
+
+/// One page found by web search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSearchResult {
+    /// Always `"web_search_result"`
+    #[serde(rename = "type")]
+    pub result_type: String,
+
+    /// Page URL
+    pub url: String,
+
+    /// Page title
+    pub title: String,
+
+    /// Opaque page content; pass it back unchanged in later turns so
+    /// Claude can cite it
+    pub encrypted_content: String,
+
+    /// When the page was last updated, e.g. `"April 30, 2025"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_age: Option<String>,
+}
+
+/// Content of a `web_fetch_tool_result` block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WebFetchToolResultContent {
+    /// The fetched page
+    Result(WebFetchResult),
+    /// The fetch failed
+    Error(ServerToolError),
+}
+
+/// A page retrieved by web fetch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebFetchResult {
+    /// Always `"web_fetch_result"`
+    #[serde(rename = "type")]
+    pub result_type: String,
+
+    /// URL that was fetched
+    pub url: String,
+
+    /// The page as a document
+    pub content: WebFetchDocument,
+
+    /// When the page was retrieved (ISO 8601)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retrieved_at: Option<String>,
+}
+
+/// Document produced by web fetch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebFetchDocument {
+    /// Always `"document"`
+    #[serde(rename = "type")]
+    pub document_type: String,
+
+    /// Text of the page, or base64 data for PDFs
+    pub source: FetchedDocumentSource,
+
+    /// Page title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Whether citations are enabled for the document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citations: Option<CitationConfig>,
+}
+
+/// Source of a fetched document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FetchedDocumentSource {
+    /// Plain text, e.g. `text/plain`
+    Text {
+        /// MIME type
+        media_type: String,
+        /// The text
+        data: String,
+    },
+    /// Base64-encoded binary content, e.g. `application/pdf`
+    Base64 {
+        /// MIME type
+        media_type: String,
+        /// Base64 data
+        data: String,
+    },
+}
+
+/// Content of a `code_execution_tool_result` or
+/// `bash_code_execution_tool_result` block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CodeExecutionToolResultContent {
+    /// The code ran
+    Result(CodeExecutionResult),
+    /// The code could not run
+    Error(ServerToolError),
+}
+
+/// Output of code or a bash command run in the code execution container
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeExecutionResult {
+    /// `"code_execution_result"` or `"bash_code_execution_result"`
+    #[serde(rename = "type")]
+    pub result_type: String,
+
+    /// Standard output
+    #[serde(default)]
+    pub stdout: String,
+
+    /// Standard error
+    #[serde(default)]
+    pub stderr: String,
+
+    /// Exit status
+    pub return_code: i32,
+
+    /// Files the code created; download them with the Files API
+    #[serde(default)]
+    pub content: Vec<CodeExecutionOutput>,
+}
+
+impl CodeExecutionResult {
+    /// IDs of the files the code created
+    pub fn file_ids(&self) -> Vec<&str> {
+        self.content.iter().map(|o| o.file_id.as_str()).collect()
+    }
+}
+
+/// A file produced by code execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeExecutionOutput {
+    /// `"code_execution_output"` or `"bash_code_execution_output"`
+    #[serde(rename = "type")]
+    pub output_type: String,
+
+    /// Files API ID of the file
+    pub file_id: String,
+}
+
+/// Content of a `text_editor_code_execution_tool_result` block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TextEditorCodeExecutionToolResultContent {
+    /// The command failed
+    Error(ServerToolError),
+    /// Result of `view`
+    View(TextEditorViewResult),
+    /// Result of `str_replace`
+    StrReplace(TextEditorStrReplaceResult),
+    /// Result of `create`
+    Create(TextEditorCreateResult),
+}
+
+/// File contents returned by the container text editor's `view` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEditorViewResult {
+    /// Always `"text_editor_code_execution_view_result"`
+    #[serde(rename = "type")]
+    pub result_type: String,
+
+    /// `"text"`, `"image"` or `"pdf"`
+    pub file_type: String,
+
+    /// File contents
+    pub content: String,
+
+    /// Lines shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_lines: Option<u32>,
+
+    /// First line shown (1-indexed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+
+    /// Lines in the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<u32>,
+}
+
+/// Diff returned by the container text editor's `str_replace` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEditorStrReplaceResult {
+    /// Always `"text_editor_code_execution_str_replace_result"`
+    #[serde(rename = "type")]
+    pub result_type: String,
+
+    /// Diff lines
+    pub lines: Vec<String>,
+
+    /// First replaced line in the old file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_start: Option<u32>,
+
+    /// Number of replaced lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_lines: Option<u32>,
+
+    /// First inserted line in the new file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_start: Option<u32>,
+
+    /// Number of inserted lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_lines: Option<u32>,
+}
+
+/// Result of the container text editor's `create` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEditorCreateResult {
+    /// Always `"text_editor_code_execution_create_result"`
+    #[serde(rename = "type")]
+    pub result_type: String,
+
+    /// Whether an existing file was overwritten
+    pub is_file_update: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
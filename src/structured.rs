@@ -2,9 +2,1026 @@
 //!
 //! This module provides helpers for getting structured JSON outputs from Claude
 //! by using tool_choice to force a specific tool that returns the desired schema.
+//!
+//! # Deriving Schemas
+//!
+//! With the `schemars` feature, schemas can be derived from Rust types
+//! instead of written by hand. [`schema_for`], [`CustomTool::from_type`] and
+//! [`MessagesRequest::with_output_type`](crate::MessagesRequest::with_output_type)
+//! all pass the derived schema through [`strict_schema`], so it is accepted
+//! by strict tools and `output_config.format`:
+//!
+//! ```rust,ignore
+//! use claude_sdk::{CustomTool, Message, MessagesRequest};
+//! use schemars::JsonSchema;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, JsonSchema)]
+//! struct Person {
+//!     /// Full name
+//!     name: String,
+//!     age: u32,
+//!     email: Option<String>,
+//! }
+//!
+//! let tool = CustomTool::from_type::<Person>("extract_person", "Extract a person").with_strict();
+//!
+//! let request = MessagesRequest::new(
+//!     "claude-sonnet-4-5-20250929",
+//!     1024,
+//!     vec![Message::user("Alice, 30, alice@example.com")],
+//! )
+//! .with_output_type::<Person>();
+//! ```
+
+use crate::client::ClaudeClient;
+use crate::error::{Error, Result};
+use crate::partial::{PartialJson, PartialValue};
+use crate::streaming::StreamEvent;
+use crate::types::{
+    ContentBlock, CustomTool, Message, MessagesRequest, MessagesResponse, Role, ToolChoice,
+    ToolDefinition, ToolResultContent,
+};
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::pin::Pin;
+
+/// Repair attempts used by [`ClaudeClient::send_structured`]
+pub const DEFAULT_REPAIR_ATTEMPTS: u32 = 2;
+
+/// A streamed snapshot is taken once the output has grown by this fraction
+/// (1/n) since the last one, so long outputs are not copied on every delta
+const SNAPSHOT_GROWTH_DIVISOR: usize = 64;
+
+/// `format` values accepted in strict schemas
+const STRICT_FORMATS: &[&str] = &[
+    "date-time",
+    "time",
+    "date",
+    "duration",
+    "email",
+    "hostname",
+    "uri",
+    "ipv4",
+    "ipv6",
+    "uuid",
+];
+
+/// Keywords rejected in strict schemas
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "$schema",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+];
+
+/// Keywords whose value is a single subschema
+const SUBSCHEMA_KEYWORDS: &[&str] = &["items", "additionalProperties", "not", "contains"];
+
+/// Keywords whose value is a list of subschemas
+const SUBSCHEMA_LIST_KEYWORDS: &[&str] = &["anyOf", "allOf", "prefixItems"];
+
+/// Keywords whose value maps names to subschemas
+const SUBSCHEMA_MAP_KEYWORDS: &[&str] = &["properties", "$defs", "definitions"];
+
+/// Rewrite a JSON schema into the subset strict mode accepts
+///
+/// Applied recursively to every subschema:
+///
+/// - objects get `additionalProperties: false` (unless it is already a
+///   schema) and list every property in `required`; properties that were
+///   optional are made nullable first, so Claude can still leave them out by
+///   sending `null`
+/// - `oneOf` becomes `anyOf`
+/// - numeric and length constraints, `$schema`, and unsupported `format`
+///   values are removed
+/// - `minItems` above 1 is removed
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::structured::strict_schema;
+/// use serde_json::json;
+///
+/// let schema = strict_schema(json!({
+///     "type": "object",
+///     "properties": {
+///         "name": {"type": "string", "maxLength": 100},
+///         "age": {"type": "integer", "format": "uint32", "minimum": 0}
+///     },
+///     "required": ["name"]
+/// }));
+///
+/// assert_eq!(schema, json!({
+///     "type": "object",
+///     "properties": {
+///         "name": {"type": "string"},
+///         "age": {"type": ["integer", "null"]}
+///     },
+///     "required": ["age", "name"],
+///     "additionalProperties": false
+/// }));
+/// ```
+pub fn strict_schema(mut schema: Value) -> Value {
+    make_strict(&mut schema);
+    schema
+}
+
+fn make_strict(schema: &mut Value) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+
+    for keyword in UNSUPPORTED_KEYWORDS {
+        object.remove(*keyword);
+    }
+    if object
+        .get("format")
+        .and_then(Value::as_str)
+        .is_some_and(|format| !STRICT_FORMATS.contains(&format))
+    {
+        object.remove("format");
+    }
+    if object
+        .get("minItems")
+        .and_then(Value::as_u64)
+        .is_some_and(|min| min > 1)
+    {
+        object.remove("minItems");
+    }
+    if let Some(one_of) = object.remove("oneOf") {
+        object.insert("anyOf".into(), one_of);
+    }
+
+    let mut optional = Vec::new();
+    if is_object_schema(object) {
+        let required: HashSet<String> = object
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| {
+                r.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let properties: Vec<String> = object
+            .get("properties")
+            .and_then(Value::as_object)
+            .map(|properties| properties.keys().cloned().collect())
+            .unwrap_or_default();
+        optional = properties
+            .iter()
+            .filter(|name| !required.contains(*name))
+            .cloned()
+            .collect();
+        if !properties.is_empty() {
+            let properties = properties.into_iter().map(Value::from).collect();
+            object.insert("required".into(), Value::Array(properties));
+        }
+        if !object
+            .get("additionalProperties")
+            .is_some_and(Value::is_object)
+        {
+            object.insert("additionalProperties".into(), Value::Bool(false));
+        }
+    }
+
+    for keyword in SUBSCHEMA_KEYWORDS {
+        if let Some(subschema) = object.get_mut(*keyword) {
+            make_strict(subschema);
+        }
+    }
+    for keyword in SUBSCHEMA_LIST_KEYWORDS {
+        if let Some(Value::Array(subschemas)) = object.get_mut(*keyword) {
+            subschemas.iter_mut().for_each(make_strict);
+        }
+    }
+    for keyword in SUBSCHEMA_MAP_KEYWORDS {
+        if let Some(Value::Object(subschemas)) = object.get_mut(*keyword) {
+            subschemas.values_mut().for_each(make_strict);
+        }
+    }
+
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        for name in optional {
+            if let Some(property) = properties.get_mut(&name) {
+                make_nullable(property);
+            }
+        }
+    }
+}
+
+/// Let a subschema also accept `null`
+///
+/// A plain `type` gains `"null"`, an `anyOf` gains a null branch, and
+/// anything else is wrapped in `anyOf: [schema, {"type": "null"}]`.
+fn make_nullable(schema: &mut Value) {
+    if accepts_null(schema) {
+        return;
+    }
+    let null_branch = || serde_json::json!({"type": "null"});
+    if let Value::Object(object) = schema {
+        let constrained = object.contains_key("enum") || object.contains_key("const");
+        match object.get_mut("type") {
+            Some(Value::String(t)) if !constrained => {
+                let t = Value::String(std::mem::take(t));
+                object.insert("type".into(), Value::Array(vec![t, "null".into()]));
+                return;
+            }
+            Some(Value::Array(types)) if !constrained => {
+                types.push("null".into());
+                return;
+            }
+            None => {
+                if let Some(Value::Array(branches)) = object.get_mut("anyOf") {
+                    branches.push(null_branch());
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+    *schema = serde_json::json!({"anyOf": [std::mem::take(schema), null_branch()]});
+}
+
+/// Whether a subschema accepts `null` as it is
+fn accepts_null(schema: &Value) -> bool {
+    let Value::Object(object) = schema else {
+        return schema == &Value::Bool(true);
+    };
+    if object.is_empty() {
+        return true;
+    }
+    let allows_type = match object.get("type") {
+        Some(Value::String(t)) => t == "null",
+        Some(Value::Array(types)) => types.iter().any(|t| t == "null"),
+        _ => true,
+    };
+    let allows_enum = match (object.get("enum"), object.get("const")) {
+        (Some(Value::Array(values)), _) => values.contains(&Value::Null),
+        (_, Some(value)) => value.is_null(),
+        _ => true,
+    };
+    let branches = object
+        .get("anyOf")
+        .and_then(Value::as_array)
+        .map(|branches| branches.iter().any(accepts_null));
+    let typed =
+        object.contains_key("type") || object.contains_key("enum") || object.contains_key("const");
+    match branches {
+        Some(nullable_branch) => nullable_branch && allows_type && allows_enum,
+        None => typed && allows_type && allows_enum,
+    }
+}
+
+fn is_object_schema(schema: &Map<String, Value>) -> bool {
+    match schema.get("type") {
+        Some(Value::String(t)) => t == "object",
+        Some(Value::Array(types)) => types.iter().any(|t| t == "object"),
+        _ => schema.contains_key("properties"),
+    }
+}
+
+/// A construct in a schema that strict mode or `output_config.format` rejects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+    /// JSON pointer of the offending subschema; empty for the root
+    pub path: String,
+    /// What is unsupported
+    pub message: String,
+    /// Whether [`strict_schema`] rewrites it away
+    pub fixable: bool,
+}
+
+impl std::fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root): {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Report everything in a schema that strict mode would reject
+///
+/// Reports what [`strict_schema`] rewrites (marked `fixable`) as well as
+/// what it cannot fix: map-like objects (`additionalProperties` with a
+/// schema), which it keeps because rewriting them would change their
+/// meaning, and recursive and external `$ref`s. An empty result means the
+/// schema can be used as is.
+///
+/// [`CustomTool::with_strict`] and [`MessagesRequest::with_json_schema`]
+/// run this check and log each issue as a warning.
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::structured::{lint_schema, strict_schema};
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {"age": {"type": "integer", "minimum": 0}},
+///     "required": ["age"]
+/// });
+///
+/// let issues = lint_schema(&schema);
+/// assert_eq!(issues.len(), 2);
+/// assert_eq!(issues[0].to_string(), "(root): object schemas must set `additionalProperties: false`");
+/// assert_eq!(issues[1].to_string(), "/properties/age: `minimum` is not supported");
+///
+/// assert!(issues.iter().all(|issue| issue.fixable));
+/// assert!(lint_schema(&strict_schema(schema)).is_empty());
+/// ```
+pub fn lint_schema(schema: &Value) -> Vec<SchemaIssue> {
+    let mut issues = Vec::new();
+    lint_subschema(schema, "", &mut issues);
+    lint_refs(schema, &mut issues);
+    issues
+}
+
+fn lint_subschema(schema: &Value, path: &str, issues: &mut Vec<SchemaIssue>) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+    if is_object_schema(object)
+        && object
+            .get("additionalProperties")
+            .is_some_and(Value::is_object)
+    {
+        issues.push(SchemaIssue {
+            path: path.to_string(),
+            message: "map-like objects (`additionalProperties` with a schema) are not supported"
+                .into(),
+            fixable: false,
+        });
+    }
+    let mut issue = |message: String| {
+        issues.push(SchemaIssue {
+            path: path.to_string(),
+            message,
+            fixable: true,
+        })
+    };
+
+    if is_object_schema(object) {
+        match object.get("additionalProperties") {
+            Some(Value::Bool(false)) | Some(Value::Object(_)) => {}
+            _ => issue("object schemas must set `additionalProperties: false`".into()),
+        }
+        if let Some(properties) = object.get("properties").and_then(Value::as_object) {
+            let required: Vec<&str> = object
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|r| r.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            for name in properties.keys() {
+                if !required.contains(&name.as_str()) {
+                    issue(format!("property `{}` must be listed in `required`", name));
+                }
+            }
+        }
+    }
+    for keyword in UNSUPPORTED_KEYWORDS {
+        if object.contains_key(*keyword) {
+            issue(format!("`{}` is not supported", keyword));
+        }
+    }
+    if let Some(format) = object.get("format").and_then(Value::as_str) {
+        if !STRICT_FORMATS.contains(&format) {
+            issue(format!("format `{}` is not supported", format));
+        }
+    }
+    if let Some(min) = object.get("minItems").and_then(Value::as_u64) {
+        if min > 1 {
+            issue(format!(
+                "`minItems` of {} is not supported (only 0 or 1)",
+                min
+            ));
+        }
+    }
+    if object.contains_key("oneOf") {
+        issue("`oneOf` is not supported; use `anyOf`".into());
+    }
+
+    for keyword in SUBSCHEMA_KEYWORDS {
+        if let Some(subschema) = object.get(*keyword) {
+            lint_subschema(subschema, &format!("{}/{}", path, keyword), issues);
+        }
+    }
+    for keyword in SUBSCHEMA_LIST_KEYWORDS.iter().chain(&["oneOf"]) {
+        if let Some(Value::Array(subschemas)) = object.get(*keyword) {
+            for (i, subschema) in subschemas.iter().enumerate() {
+                lint_subschema(subschema, &format!("{}/{}/{}", path, keyword, i), issues);
+            }
+        }
+    }
+    for keyword in SUBSCHEMA_MAP_KEYWORDS {
+        if let Some(Value::Object(subschemas)) = object.get(*keyword) {
+            for (name, subschema) in subschemas {
+                let name = name.replace('~', "~0").replace('/', "~1");
+                lint_subschema(subschema, &format!("{}/{}/{}", path, keyword, name), issues);
+            }
+        }
+    }
+}
+
+/// Report external `$ref`s and definitions that refer back to themselves
+fn lint_refs(schema: &Value, issues: &mut Vec<SchemaIssue>) {
+    let mut refs = Vec::new();
+    collect_refs(schema, "", &mut refs);
+
+    for (path, target) in &refs {
+        let message = if target == "#" {
+            "recursive `$ref` to the root schema is not supported".to_string()
+        } else if !target.starts_with('#') {
+            format!("external `$ref` `{}` is not supported", target)
+        } else {
+            continue;
+        };
+        issues.push(SchemaIssue {
+            path: path.clone(),
+            message,
+            fixable: false,
+        });
+    }
+
+    // Definition name -> definitions it references
+    let definition = |target: &str| {
+        target
+            .strip_prefix("#/$defs/")
+            .or_else(|| target.strip_prefix("#/definitions/"))
+            .map(str::to_string)
+    };
+    let mut graph: Vec<(String, String, Vec<String>)> = Vec::new();
+    for keyword in ["$defs", "definitions"] {
+        if let Some(Value::Object(defs)) = schema.get(keyword) {
+            for (name, def) in defs {
+                let mut def_refs = Vec::new();
+                collect_refs(def, "", &mut def_refs);
+                let targets = def_refs.iter().filter_map(|(_, t)| definition(t)).collect();
+                graph.push((format!("/{}/{}", keyword, name), name.clone(), targets));
+            }
+        }
+    }
+
+    for (path, name, targets) in &graph {
+        let mut seen: Vec<&str> = Vec::new();
+        let mut stack: Vec<&str> = targets.iter().map(String::as_str).collect();
+        while let Some(next) = stack.pop() {
+            if next == name {
+                issues.push(SchemaIssue {
+                    path: path.clone(),
+                    message: format!("recursive definition `{}` is not supported", name),
+                    fixable: false,
+                });
+                break;
+            }
+            if seen.contains(&next) {
+                continue;
+            }
+            seen.push(next);
+            if let Some((_, _, more)) = graph.iter().find(|(_, n, _)| n == next) {
+                stack.extend(more.iter().map(String::as_str));
+            }
+        }
+    }
+}
+
+fn collect_refs(value: &Value, path: &str, refs: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(target) = object.get("$ref").and_then(Value::as_str) {
+                refs.push((path.to_string(), target.to_string()));
+            }
+            for (key, child) in object {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                collect_refs(child, &format!("{}/{}", path, key), refs);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                collect_refs(child, &format!("{}/{}", path, i), refs);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Log a warning for each [`lint_schema`] issue; used by the schema builders
+pub(crate) fn warn_schema_issues(context: &str, schema: &Value) {
+    for issue in lint_schema(schema) {
+        tracing::warn!(
+            "{} schema is not strict-mode compatible: {}",
+            context,
+            issue
+        );
+    }
+}
+
+/// Where the structured value is read from in a response
+enum Source {
+    /// `input` of the forced tool's `tool_use` block
+    Tool(String),
+    /// Concatenated text blocks, as produced by `output_config.format`
+    Text,
+}
+
+impl Source {
+    fn for_request(request: &MessagesRequest) -> Result<Self> {
+        if let Some(ToolChoice::Tool { name, .. }) = &request.tool_choice {
+            return Ok(Source::Tool(name.clone()));
+        }
+        if request
+            .output_config
+            .as_ref()
+            .is_some_and(|config| config.format.is_some())
+        {
+            return Ok(Source::Text);
+        }
+        Err(Error::InvalidRequest(
+            "structured requests need output_config.format or a forced tool_choice; \
+             use send_structured_with_schema to have one set"
+                .into(),
+        ))
+    }
+
+    /// Whether the request already says where the structured value comes from
+    fn is_configured(request: &MessagesRequest) -> bool {
+        Self::for_request(request).is_ok()
+    }
+
+    /// Whether a streamed content block carries the structured value
+    fn tracks(&self, block: &ContentBlock) -> bool {
+        match (self, block) {
+            (Source::Tool(name), ContentBlock::ToolUse { name: used, .. }) => used == name,
+            (Source::Text, ContentBlock::Text { .. }) => true,
+            _ => false,
+        }
+    }
+
+    /// The schema the structured value should match
+    fn schema<'a>(&self, request: &'a MessagesRequest) -> Option<&'a Value> {
+        match self {
+            Source::Tool(name) => request.tools.iter().flatten().find_map(|tool| match tool {
+                ToolDefinition::Custom(tool) if &tool.name == name => Some(&tool.input_schema),
+                _ => None,
+            }),
+            Source::Text => request
+                .output_config
+                .as_ref()
+                .and_then(|config| config.format.as_ref())
+                .map(|format| &format.schema),
+        }
+    }
+
+    /// Read the structured value from a response
+    fn extract(&self, response: &MessagesResponse) -> std::result::Result<Value, String> {
+        match self {
+            Source::Tool(name) => response
+                .content
+                .iter()
+                .find_map(|block| match block {
+                    ContentBlock::ToolUse {
+                        name: used, input, ..
+                    } if used == name => Some(input.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| format!("No call to the `{}` tool was made", name)),
+            Source::Text => {
+                let text: String = response
+                    .content
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text, .. } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                serde_json::from_str(text.trim()).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// The user turn that asks the model to correct its last response
+    fn repair_message(&self, response: &MessagesResponse, error: &str) -> Message {
+        let feedback = match self {
+            Source::Tool(name) => format!(
+                "Invalid input: {}. Call the `{}` tool again with corrected input.",
+                error, name
+            ),
+            Source::Text => format!(
+                "Your response could not be parsed: {}. Respond again with only JSON matching the schema.",
+                error
+            ),
+        };
+
+        // Every tool_use block needs a matching tool_result
+        let mut content: Vec<ContentBlock> = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, .. } => Some(ContentBlock::ToolResult {
+                    tool_use_id: id.clone(),
+                    content: Some(ToolResultContent::Text(feedback.clone())),
+                    is_error: Some(true),
+                }),
+                _ => None,
+            })
+            .collect();
+        if content.is_empty() {
+            content.push(ContentBlock::Text {
+                text: feedback,
+                cache_control: None,
+                citations: None,
+            });
+        }
 
-use crate::types::{CustomTool, ToolChoice};
-use serde_json::Value;
+        Message {
+            role: Role::User,
+            content,
+        }
+    }
+}
+
+impl ClaudeClient {
+    /// Send a request and deserialize the structured output into `T`
+    ///
+    /// The request must either set `output_config.format` (see
+    /// [`MessagesRequest::with_json_schema`]) or force a tool with
+    /// [`force_tool`]. The value is read from the response text or from the
+    /// forced tool's input respectively.
+    ///
+    /// If the value does not deserialize, the error is sent back to the
+    /// model as a follow-up turn, up to [`DEFAULT_REPAIR_ATTEMPTS`] times.
+    /// With the `validation` feature, the value is first checked against the
+    /// output (or forced tool) schema and every violation is reported.
+    /// Returns the value together with the response it was parsed from.
+    ///
+    /// To have `output_config.format` set for you, use
+    /// [`send_structured_with_schema`](Self::send_structured_with_schema)
+    /// or, with the `schemars` feature,
+    /// [`send_structured_derived`](Self::send_structured_derived).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StructuredOutput`] if no attempt produced a valid
+    /// value, and [`Error::InvalidRequest`] if the request asks for neither
+    /// structured output nor a forced tool.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use claude_sdk::{ClaudeClient, Message, MessagesRequest};
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Sentiment {
+    ///     label: String,
+    ///     score: f64,
+    /// }
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ClaudeClient::anthropic(std::env::var("ANTHROPIC_API_KEY")?);
+    ///
+    /// let request = MessagesRequest::new(
+    ///     "claude-sonnet-4-5-20250929",
+    ///     1024,
+    ///     vec![Message::user("Classify: 'I love it'")],
+    /// )
+    /// .with_json_schema(json!({
+    ///     "type": "object",
+    ///     "properties": {
+    ///         "label": {"type": "string"},
+    ///         "score": {"type": "number"}
+    ///     },
+    ///     "required": ["label", "score"],
+    ///     "additionalProperties": false
+    /// }));
+    ///
+    /// let (sentiment, _response) = client.send_structured::<Sentiment>(request).await?;
+    /// println!("{} ({})", sentiment.label, sentiment.score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_structured<T: DeserializeOwned>(
+        &self,
+        request: MessagesRequest,
+    ) -> Result<(T, MessagesResponse)> {
+        self.send_structured_with_repairs(request, DEFAULT_REPAIR_ATTEMPTS)
+            .await
+    }
+
+    /// Like [`send_structured`](Self::send_structured), setting
+    /// `output_config.format` to `schema` when the request sets neither a
+    /// format nor a forced tool
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use claude_sdk::{ClaudeClient, Message, MessagesRequest};
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Sentiment {
+    ///     label: String,
+    ///     score: f64,
+    /// }
+    ///
+    /// # async fn example(client: ClaudeClient) -> claude_sdk::Result<()> {
+    /// let request = MessagesRequest::new(
+    ///     "claude-sonnet-4-5-20250929",
+    ///     1024,
+    ///     vec![Message::user("Classify: 'I love it'")],
+    /// );
+    /// let schema = json!({
+    ///     "type": "object",
+    ///     "properties": {
+    ///         "label": {"type": "string"},
+    ///         "score": {"type": "number"}
+    ///     },
+    ///     "required": ["label", "score"],
+    ///     "additionalProperties": false
+    /// });
+    ///
+    /// let (sentiment, _response) = client
+    ///     .send_structured_with_schema::<Sentiment>(request, schema)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_structured_with_schema<T: DeserializeOwned>(
+        &self,
+        mut request: MessagesRequest,
+        schema: Value,
+    ) -> Result<(T, MessagesResponse)> {
+        if !Source::is_configured(&request) {
+            request = request.with_json_schema(schema);
+        }
+        self.send_structured(request).await
+    }
+
+    /// Like [`send_structured`](Self::send_structured), setting
+    /// `output_config.format` to the schema derived from `T` (see
+    /// [`schema_for`]) when the request sets neither a format nor a forced
+    /// tool
+    ///
+    /// Requires the `schemars` feature.
+    #[cfg(feature = "schemars")]
+    pub async fn send_structured_derived<T: DeserializeOwned + schemars::JsonSchema>(
+        &self,
+        request: MessagesRequest,
+    ) -> Result<(T, MessagesResponse)> {
+        self.send_structured_with_schema(request, schema_for::<T>())
+            .await
+    }
+
+    /// Like [`send_structured`](Self::send_structured) with an explicit
+    /// number of repair attempts
+    ///
+    /// `max_repairs = 0` sends the request once and fails on the first
+    /// parse error.
+    pub async fn send_structured_with_repairs<T: DeserializeOwned>(
+        &self,
+        mut request: MessagesRequest,
+        max_repairs: u32,
+    ) -> Result<(T, MessagesResponse)> {
+        let source = Source::for_request(&request)?;
+        let decoder = Decoder::new(source.schema(&request));
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+            let response = self.send_message(request.clone()).await?;
+            let error = match source
+                .extract(&response)
+                .and_then(|value| decoder.decode(value))
+            {
+                Ok(value) => return Ok((value, response)),
+                Err(error) => error,
+            };
+
+            if attempts > max_repairs {
+                return Err(Error::StructuredOutput {
+                    attempts,
+                    message: error,
+                });
+            }
+            tracing::debug!(attempts, %error, "repairing structured output");
+
+            let repair = source.repair_message(&response, &error);
+            // An empty assistant turn is rejected unless it is the last one
+            if !response.content.is_empty() {
+                request.messages.push(Message {
+                    role: Role::Assistant,
+                    content: response.content,
+                });
+            }
+            request.messages.push(repair);
+        }
+    }
+}
+
+/// Converts a structured value into `T`, checking it against the schema
+/// first when the `validation` feature is enabled
+struct Decoder {
+    #[cfg(feature = "validation")]
+    validator: Option<crate::validation::SchemaValidator>,
+}
+
+impl Decoder {
+    fn new(schema: Option<&Value>) -> Self {
+        #[cfg(feature = "validation")]
+        {
+            let validator = schema.and_then(|schema| {
+                crate::validation::SchemaValidator::new(schema)
+                    .map_err(|e| tracing::warn!("Not validating structured output: {}", e))
+                    .ok()
+            });
+            Self { validator }
+        }
+        #[cfg(not(feature = "validation"))]
+        {
+            let _ = schema;
+            Self {}
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(&self, value: Value) -> std::result::Result<T, String> {
+        #[cfg(feature = "validation")]
+        if let Some(validator) = &self.validator {
+            validator.validate(&value).map_err(|violations| {
+                format!(
+                    "The value does not match the schema:\n{}",
+                    crate::validation::describe(&violations)
+                )
+            })?;
+        }
+        T::deserialize(value).map_err(|e| e.to_string())
+    }
+}
+
+/// An event from [`ClaudeClient::send_structured_streaming`]
+#[derive(Debug, Clone)]
+pub enum StructuredEvent<T> {
+    /// The output received so far; sent whenever the snapshot changes
+    Partial(PartialValue),
+    /// The complete output, deserialized into `T`
+    Complete(T),
+}
+
+impl ClaudeClient {
+    /// Stream structured output as progressively filled snapshots
+    ///
+    /// The request is set up as for [`send_structured`](Self::send_structured).
+    /// Text deltas (or the forced tool's `input_json_delta` events) are parsed
+    /// incrementally with [`PartialJson`], yielding
+    /// [`StructuredEvent::Partial`] snapshots, and the stream ends with
+    /// [`StructuredEvent::Complete`] once the message stops. Short outputs
+    /// get a snapshot per delta; long ones once they have grown by about
+    /// 1.5% since the last snapshot.
+    ///
+    /// Snapshots can be deserialized into a mirror of `T` with `Option`
+    /// fields via [`PartialValue::parse`]. No repair turns are attempted.
+    ///
+    /// # Errors
+    ///
+    /// The final item is [`Error::StructuredOutput`] if the complete output
+    /// does not deserialize into `T`. Stream `error` events are surfaced as
+    /// [`Error::Api`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use claude_sdk::structured::StructuredEvent;
+    /// use claude_sdk::{ClaudeClient, MessagesRequest};
+    /// use futures::StreamExt;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Summary {
+    ///     title: String,
+    ///     points: Vec<String>,
+    /// }
+    ///
+    /// # async fn example(client: ClaudeClient, request: MessagesRequest) -> claude_sdk::Result<()> {
+    /// let mut stream = client.send_structured_streaming::<Summary>(request).await?;
+    /// while let Some(event) = stream.next().await {
+    ///     match event? {
+    ///         StructuredEvent::Partial(snapshot) => {
+    ///             println!("{} points so far", snapshot.value["points"].as_array().map_or(0, Vec::len));
+    ///         }
+    ///         StructuredEvent::Complete(summary) => println!("{}", summary.title),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_structured_streaming<T: DeserializeOwned + Send + 'static>(
+        &self,
+        request: MessagesRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StructuredEvent<T>>> + Send>>> {
+        let source = Source::for_request(&request)?;
+        let decoder = Decoder::new(source.schema(&request));
+        let mut events = self.send_streaming(request).await?;
+
+        let stream = async_stream::stream! {
+            let mut partial = PartialJson::new();
+            let mut tracked = HashSet::new();
+            let mut last: Option<PartialValue> = None;
+            let mut snapshot_len = 0;
+
+            while let Some(event) = events.next().await {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+
+                match event {
+                    StreamEvent::ContentBlockStart { index, content_block } => {
+                        // Only the first call of a forced tool is read
+                        let first_tool = matches!(source, Source::Text) || tracked.is_empty();
+                        if first_tool && source.tracks(&content_block) {
+                            tracked.insert(index);
+                        }
+                    }
+                    StreamEvent::ContentBlockDelta { index, delta } if tracked.contains(&index) => {
+                        let Some(fragment) = delta.text().or(delta.partial_json()) else {
+                            continue;
+                        };
+                        partial.push(fragment);
+                        let len = partial.buffer().len();
+                        if len - snapshot_len < len / SNAPSHOT_GROWTH_DIVISOR {
+                            continue;
+                        }
+                        snapshot_len = len;
+                        if let Some(snapshot) = partial.snapshot() {
+                            if last.as_ref() != Some(&snapshot) {
+                                last = Some(snapshot.clone());
+                                yield Ok(StructuredEvent::Partial(snapshot));
+                            }
+                        }
+                    }
+                    StreamEvent::MessageStop => break,
+                    StreamEvent::Error { error } => {
+                        yield Err(crate::client::error_from_stream(error));
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+
+            let message = match (&source, tracked.is_empty()) {
+                (Source::Tool(name), true) => format!("No call to the `{}` tool was made", name),
+                _ => match partial
+                    .finish::<Value>()
+                    .map_err(|e| e.to_string())
+                    .and_then(|value| decoder.decode(value))
+                {
+                    Ok(value) => {
+                        yield Ok(StructuredEvent::Complete(value));
+                        return;
+                    }
+                    Err(message) => message,
+                },
+            };
+            yield Err(Error::StructuredOutput { attempts: 1, message });
+        };
+
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Derive a strict-mode JSON schema from a Rust type
+///
+/// Subschemas are inlined where possible; recursive types keep `$defs`
+/// references. Fields that are not required, such as `#[serde(default)]`
+/// fields, become nullable (see [`strict_schema`]); declare them as
+/// `Option` so a `null` deserializes. Requires the `schemars` feature.
+#[cfg(feature = "schemars")]
+pub fn schema_for<T: schemars::JsonSchema + ?Sized>() -> Value {
+    let schema = schemars::generate::SchemaSettings::draft2020_12()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+    strict_schema(schema.to_value())
+}
 
 /// Create a tool for structured JSON extraction
 ///
@@ -76,6 +1093,7 @@ pub fn force_tool(tool_name: impl Into<String>) -> ToolChoice {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_json_schema_tool() {
@@ -103,4 +1121,655 @@ mod tests {
             _ => panic!("Expected Tool variant"),
         }
     }
+
+    #[test]
+    fn test_strict_schema_nested() {
+        let schema = strict_schema(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "string"}, "minItems": 3},
+                "owner": {
+                    "type": ["object", "null"],
+                    "properties": {"id": {"type": "string", "format": "uuid"}}
+                },
+                "shape": {"oneOf": [
+                    {"type": "object", "properties": {"radius": {"type": "number", "format": "double"}}},
+                    {"type": "string"}
+                ]},
+                "labels": {"type": "object", "additionalProperties": {"type": "string"}}
+            }
+        }));
+
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(
+            schema["required"],
+            json!(["labels", "owner", "shape", "tags"])
+        );
+        assert!(schema["properties"]["tags"].get("minItems").is_none());
+
+        let owner = &schema["properties"]["owner"];
+        assert_eq!(owner["additionalProperties"], false);
+        assert_eq!(owner["required"], json!(["id"]));
+        assert_eq!(owner["properties"]["id"]["format"], "uuid");
+
+        let shape = &schema["properties"]["shape"];
+        assert!(shape.get("oneOf").is_none());
+        assert_eq!(shape["anyOf"][0]["additionalProperties"], false);
+        assert!(shape["anyOf"][0]["properties"]["radius"]
+            .get("format")
+            .is_none());
+
+        // Map-like objects keep their value schema
+        assert_eq!(
+            schema["properties"]["labels"]["additionalProperties"],
+            json!({"type": "string"})
+        );
+
+        // Properties that were optional become nullable
+        assert_eq!(
+            schema["properties"]["tags"]["type"],
+            json!(["array", "null"])
+        );
+        assert_eq!(owner["type"], json!(["object", "null"]));
+        assert_eq!(shape["anyOf"][2], json!({"type": "null"}));
+        assert_eq!(
+            schema["properties"]["labels"]["type"],
+            json!(["object", "null"])
+        );
+    }
+
+    #[test]
+    fn test_strict_schema_keeps_optional_properties_optional() {
+        let schema = strict_schema(json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "kind": {"type": "string", "enum": ["a", "b"]},
+                "parent": {"$ref": "#/$defs/Node"},
+                "note": {"type": ["string", "null"]}
+            },
+            "required": ["id"]
+        }));
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["id"], json!({"type": "string"}));
+        assert_eq!(
+            properties["kind"],
+            json!({"anyOf": [{"type": "string", "enum": ["a", "b"]}, {"type": "null"}]})
+        );
+        assert_eq!(
+            properties["parent"],
+            json!({"anyOf": [{"$ref": "#/$defs/Node"}, {"type": "null"}]})
+        );
+        assert_eq!(properties["note"], json!({"type": ["string", "null"]}));
+        assert_eq!(schema["required"], json!(["id", "kind", "note", "parent"]));
+    }
+
+    #[test]
+    fn test_lint_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "format": "ipv4", "maxLength": 10},
+                "tags": {"type": "array", "items": {"type": "string"}, "minItems": 2},
+                "shape": {"oneOf": [{"type": "string"}, {"type": "integer", "format": "int64"}]},
+                "extra": {"type": "object", "additionalProperties": {"type": "string"}}
+            },
+            "required": ["name", "tags", "shape"],
+            "additionalProperties": false
+        });
+
+        let issues: Vec<String> = lint_schema(&schema)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "(root): property `extra` must be listed in `required`",
+                "/properties/extra: map-like objects (`additionalProperties` with a schema) are not supported",
+                "/properties/name: `maxLength` is not supported",
+                "/properties/shape: `oneOf` is not supported; use `anyOf`",
+                "/properties/shape/oneOf/1: format `int64` is not supported",
+                "/properties/tags: `minItems` of 2 is not supported (only 0 or 1)",
+            ]
+        );
+
+        // strict_schema keeps map-like objects, so that issue is not fixable
+        let issues = lint_schema(&strict_schema(schema));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "/properties/extra");
+        assert!(!issues[0].fixable);
+    }
+
+    #[test]
+    fn test_strict_schema_fixes_every_fixable_issue() {
+        let schemas = [
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string", "format": "ipv4", "maxLength": 10},
+                    "tags": {"type": "array", "items": {"type": "string"}, "minItems": 2},
+                    "shape": {"oneOf": [
+                        {"type": "object", "properties": {"r": {"type": "number", "minimum": 0}}},
+                        {"type": "integer", "format": "int64"}
+                    ]},
+                    "kind": {"type": "string", "enum": ["a", "b"]}
+                },
+                "required": ["name"]
+            }),
+            json!({"type": "array", "items": {"properties": {"x": {"type": "integer"}}}}),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {"at": {"$ref": "#/$defs/Time"}},
+                "$defs": {"Time": {"type": "string", "format": "date-time", "minLength": 1}}
+            }),
+        ];
+
+        for schema in schemas {
+            let issues = lint_schema(&schema);
+            assert!(!issues.is_empty());
+            assert!(issues.iter().all(|issue| issue.fixable), "{:?}", issues);
+            assert_eq!(lint_schema(&strict_schema(schema)), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_lint_schema_refs() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "root": {"$ref": "#/$defs/Node"},
+                "other": {"$ref": "https://example.com/schema.json"}
+            },
+            "required": ["root", "other"],
+            "additionalProperties": false,
+            "$defs": {
+                "Node": {
+                    "type": "object",
+                    "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/Node"}}},
+                    "required": ["children"],
+                    "additionalProperties": false
+                },
+                "Leaf": {"type": "string"}
+            }
+        });
+
+        let issues = lint_schema(&schema);
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|issue| !issue.fixable));
+        assert_eq!(
+            issues[0].to_string(),
+            "/properties/other: external `$ref` `https://example.com/schema.json` is not supported"
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            "/$defs/Node: recursive definition `Node` is not supported"
+        );
+
+        // Rewriting leaves the unfixable issues in place
+        assert_eq!(lint_schema(&strict_schema(schema)).len(), 2);
+    }
+
+    #[cfg(feature = "testing")]
+    mod send {
+        use super::*;
+        use crate::testing::{MockResponse, MockServer};
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Sentiment {
+            label: String,
+            score: f64,
+        }
+
+        fn schema_request() -> MessagesRequest {
+            MessagesRequest::new(
+                "claude-sonnet-4-5-20250929",
+                256,
+                vec![Message::user("Classify: 'I love it'")],
+            )
+            .with_json_schema(json!({"type": "object"}))
+        }
+
+        #[tokio::test]
+        async fn test_parses_text_output() {
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::text(r#"{"label": "positive", "score": 0.9}"#));
+
+            let (value, response) = server
+                .client()
+                .send_structured::<Sentiment>(schema_request())
+                .await
+                .unwrap();
+
+            assert_eq!(value.label, "positive");
+            assert_eq!(response.id, "msg_mock");
+            assert_eq!(server.message_requests().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_repairs_text_output() {
+            let server = MockServer::start().await;
+            server
+                .enqueue_message(MockResponse::text(r#"{"label": "positive"}"#))
+                .enqueue_message(MockResponse::text(r#"{"label": "positive", "score": 1.0}"#));
+
+            let (value, _) = server
+                .client()
+                .send_structured::<Sentiment>(schema_request())
+                .await
+                .unwrap();
+            assert_eq!(value.score, 1.0);
+
+            let requests = server.message_requests();
+            assert_eq!(requests.len(), 2);
+            let messages = &requests[1].messages;
+            assert_eq!(messages.len(), 3);
+            assert_eq!(messages[1].role, Role::Assistant);
+            match &messages[2].content[0] {
+                ContentBlock::Text { text, .. } => assert!(text.contains("missing field `score`")),
+                other => panic!("Expected Text, got {:?}", other),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_repairs_empty_response_without_empty_assistant_turn() {
+            let empty: MessagesResponse = serde_json::from_value(json!({
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "content": [],
+                "model": "claude-sonnet-4-5-20250929",
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 10, "output_tokens": 0}
+            }))
+            .unwrap();
+            let server = MockServer::start().await;
+            server
+                .enqueue_message(MockResponse::message(empty))
+                .enqueue_message(MockResponse::text(r#"{"label": "positive", "score": 1.0}"#));
+
+            let (value, _) = server
+                .client()
+                .send_structured::<Sentiment>(schema_request())
+                .await
+                .unwrap();
+            assert_eq!(value.score, 1.0);
+
+            let messages = &server.message_requests()[1].messages;
+            assert_eq!(messages.len(), 2);
+            assert!(messages.iter().all(|m| m.role == Role::User));
+        }
+
+        #[tokio::test]
+        async fn test_repairs_forced_tool_input() {
+            let server = MockServer::start().await;
+            server
+                .enqueue_message(MockResponse::tool_use(
+                    "toolu_1",
+                    "classify",
+                    json!({"label": "positive", "score": "high"}),
+                ))
+                .enqueue_message(MockResponse::tool_use(
+                    "toolu_2",
+                    "classify",
+                    json!({"label": "positive", "score": 0.8}),
+                ));
+
+            let mut request = schema_request();
+            request.output_config = None;
+            let request = request
+                .with_tools(vec![crate::types::ToolDefinition::Custom(
+                    json_schema_tool("classify", "Classify sentiment", json!({"type": "object"})),
+                )])
+                .with_tool_choice(force_tool("classify"));
+
+            let (value, _) = server
+                .client()
+                .send_structured::<Sentiment>(request)
+                .await
+                .unwrap();
+            assert_eq!(value.score, 0.8);
+
+            let requests = server.message_requests();
+            match &requests[1].messages[2].content[0] {
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    is_error,
+                    ..
+                } => {
+                    assert_eq!(tool_use_id, "toolu_1");
+                    assert_eq!(*is_error, Some(true));
+                }
+                other => panic!("Expected ToolResult, got {:?}", other),
+            }
+        }
+
+        #[cfg(feature = "validation")]
+        #[tokio::test]
+        async fn test_repairs_schema_violations() {
+            let server = MockServer::start().await;
+            server
+                .enqueue_message(MockResponse::text(r#"{"label": "meh", "score": 0.5}"#))
+                .enqueue_message(MockResponse::text(r#"{"label": "neutral", "score": 0.5}"#));
+
+            let mut request = schema_request();
+            request = request.with_json_schema(json!({
+                "type": "object",
+                "properties": {
+                    "label": {"enum": ["positive", "negative", "neutral"]},
+                    "score": {"type": "number"}
+                },
+                "required": ["label", "score"]
+            }));
+
+            let (value, _) = server
+                .client()
+                .send_structured::<Sentiment>(request)
+                .await
+                .unwrap();
+            assert_eq!(value.label, "neutral");
+
+            let requests = server.message_requests();
+            match &requests[1].messages[2].content[0] {
+                ContentBlock::Text { text, .. } => {
+                    assert!(text.contains("- /label: "), "{}", text)
+                }
+                other => panic!("Expected Text, got {:?}", other),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_gives_up_after_repairs() {
+            let server = MockServer::start().await;
+            server
+                .enqueue_message(MockResponse::text("not json"))
+                .enqueue_message(MockResponse::text("still not json"));
+
+            let err = server
+                .client()
+                .send_structured_with_repairs::<Sentiment>(schema_request(), 1)
+                .await
+                .unwrap_err();
+
+            assert!(matches!(err, Error::StructuredOutput { attempts: 2, .. }));
+            assert_eq!(server.message_requests().len(), 2);
+        }
+
+        #[tokio::test]
+        async fn test_streams_partial_snapshots() {
+            let mut events = vec![
+                json!({"type": "message_start", "message": {
+                    "id": "msg_1", "type": "message", "role": "assistant", "content": [],
+                    "model": "claude-sonnet-4-5-20250929",
+                    "usage": {"input_tokens": 1, "output_tokens": 1}
+                }}),
+                json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            ];
+            for chunk in [r#"{"label": "pos"#, r#"itive", "sc"#, r#"ore": 0.75}"#] {
+                events.push(json!({"type": "content_block_delta", "index": 0,
+                    "delta": {"type": "text_delta", "text": chunk}}));
+            }
+            events.push(json!({"type": "message_stop"}));
+            let events = events
+                .into_iter()
+                .map(|event| serde_json::from_value(event).unwrap())
+                .collect();
+
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::events(events));
+
+            let events: Vec<_> = server
+                .client()
+                .send_structured_streaming::<Sentiment>(schema_request())
+                .await
+                .unwrap()
+                .collect()
+                .await;
+
+            match events.last().unwrap() {
+                Ok(StructuredEvent::Complete(value)) => assert_eq!(value.score, 0.75),
+                other => panic!("Expected Complete, got {:?}", other),
+            }
+            let snapshots: Vec<_> = events
+                .iter()
+                .filter_map(|event| match event {
+                    Ok(StructuredEvent::Partial(snapshot)) => Some(snapshot),
+                    _ => None,
+                })
+                .collect();
+            let values: Vec<_> = snapshots.iter().map(|s| s.value.clone()).collect();
+            assert_eq!(
+                values,
+                vec![
+                    json!({"label": "pos"}),
+                    json!({"label": "positive"}),
+                    json!({"label": "positive", "score": 0.75}),
+                ]
+            );
+            assert!(snapshots[1].is_complete("/label"));
+            assert!(snapshots[2].is_complete(""));
+        }
+
+        #[tokio::test]
+        async fn test_throttles_snapshots_of_long_output() {
+            #[derive(Deserialize)]
+            struct Items {
+                items: Vec<String>,
+            }
+
+            let mut chunks = vec![r#"{"items": ["#.to_string()];
+            chunks.extend((0..3000).map(|i| format!(r#""item {:04}", "#, i)));
+            chunks.push(r#""last"]}"#.to_string());
+            let mut events = vec![
+                json!({"type": "message_start", "message": {
+                    "id": "msg_1", "type": "message", "role": "assistant", "content": [],
+                    "model": "claude-sonnet-4-5-20250929",
+                    "usage": {"input_tokens": 1, "output_tokens": 1}
+                }}),
+                json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            ];
+            for chunk in &chunks {
+                events.push(json!({"type": "content_block_delta", "index": 0,
+                    "delta": {"type": "text_delta", "text": chunk}}));
+            }
+            events.push(json!({"type": "message_stop"}));
+            let events = events
+                .into_iter()
+                .map(|event| serde_json::from_value(event).unwrap())
+                .collect();
+
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::events(events));
+            let events: Vec<_> = server
+                .client()
+                .send_structured_streaming::<Items>(schema_request())
+                .await
+                .unwrap()
+                .collect()
+                .await;
+
+            let snapshots = events
+                .iter()
+                .filter(|event| matches!(event, Ok(StructuredEvent::Partial(_))))
+                .count();
+            assert!(
+                snapshots > 10 && snapshots < chunks.len() / 5,
+                "{}",
+                snapshots
+            );
+            match events.last().unwrap() {
+                Ok(StructuredEvent::Complete(value)) => assert_eq!(value.items.len(), 3001),
+                other => panic!("Expected Complete, got {:?}", other.as_ref().map(|_| ())),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_streams_forced_tool_input() {
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::tool_use(
+                "toolu_1",
+                "classify",
+                json!({"label": "negative"}),
+            ));
+
+            let mut request = schema_request();
+            request.output_config = None;
+            let request = request.with_tool_choice(force_tool("classify"));
+
+            let events: Vec<_> = server
+                .client()
+                .send_structured_streaming::<Sentiment>(request)
+                .await
+                .unwrap()
+                .collect()
+                .await;
+
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Ok(StructuredEvent::Partial(snapshot)) if snapshot.value["label"] == "negative"
+            )));
+            match events.last().unwrap() {
+                Err(Error::StructuredOutput { message, .. }) => {
+                    assert!(message.contains("missing field `score`"))
+                }
+                other => panic!("Expected StructuredOutput error, got {:?}", other),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_requires_structured_request() {
+            let server = MockServer::start().await;
+            let request =
+                MessagesRequest::new("claude-sonnet-4-5-20250929", 256, vec![Message::user("Hi")]);
+
+            let err = server
+                .client()
+                .send_structured::<Sentiment>(request)
+                .await
+                .unwrap_err();
+
+            assert!(matches!(err, Error::InvalidRequest(_)));
+            assert!(server.message_requests().is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_with_schema_sets_output_format() {
+            let server = MockServer::start().await;
+            server
+                .enqueue_message(MockResponse::text(r#"{"label": "positive", "score": 0.9}"#))
+                .enqueue_message(MockResponse::text(r#"{"label": "negative", "score": 0.1}"#));
+            let client = server.client();
+            let schema = json!({"type": "object", "properties": {"label": {"type": "string"}}});
+            let request =
+                MessagesRequest::new("claude-sonnet-4-5-20250929", 256, vec![Message::user("Hi")]);
+
+            let (value, _) = client
+                .send_structured_with_schema::<Sentiment>(request, schema.clone())
+                .await
+                .unwrap();
+            assert_eq!(value.label, "positive");
+
+            // A request that already has a format keeps it
+            let (value, _) = client
+                .send_structured_with_schema::<Sentiment>(schema_request(), schema.clone())
+                .await
+                .unwrap();
+            assert_eq!(value.label, "negative");
+
+            let formats: Vec<Value> = server
+                .message_requests()
+                .into_iter()
+                .map(|request| request.output_config.unwrap().format.unwrap().schema)
+                .collect();
+            assert_eq!(formats, vec![schema, json!({"type": "object"})]);
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    mod derived {
+        use super::*;
+        use schemars::JsonSchema;
+
+        #[derive(serde::Deserialize, JsonSchema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+            zip: Option<String>,
+        }
+
+        /// A person
+        #[derive(serde::Deserialize, JsonSchema)]
+        #[allow(dead_code)]
+        struct Person {
+            /// Full name
+            name: String,
+            age: u32,
+            addresses: Vec<Address>,
+        }
+
+        #[test]
+        fn test_schema_for_is_strict() {
+            let schema = schema_for::<Person>();
+
+            assert!(schema.get("$schema").is_none());
+            assert_eq!(schema["type"], "object");
+            assert_eq!(schema["additionalProperties"], false);
+            assert_eq!(schema["required"], json!(["addresses", "age", "name"]));
+            assert_eq!(schema["properties"]["name"]["description"], "Full name");
+            assert_eq!(schema["properties"]["age"], json!({"type": "integer"}));
+
+            let address = &schema["properties"]["addresses"]["items"];
+            assert_eq!(address["additionalProperties"], false);
+            assert_eq!(address["required"], json!(["city", "zip"]));
+            assert_eq!(
+                address["properties"]["zip"]["type"],
+                json!(["string", "null"])
+            );
+        }
+
+        #[test]
+        fn test_from_type_and_with_output_type() {
+            let tool = CustomTool::from_type::<Person>("extract_person", "Extract a person");
+            assert_eq!(tool.name, "extract_person");
+            assert_eq!(tool.input_schema, schema_for::<Person>());
+
+            let request = crate::MessagesRequest::new("claude-sonnet-4-5-20250929", 256, vec![])
+                .with_output_type::<Person>();
+            let format = request.output_config.unwrap().format.unwrap();
+            assert_eq!(format.format_type, "json_schema");
+            assert_eq!(format.schema, schema_for::<Person>());
+        }
+
+        #[cfg(feature = "testing")]
+        #[tokio::test]
+        async fn test_send_structured_derived() {
+            use crate::testing::{MockResponse, MockServer};
+
+            let server = MockServer::start().await;
+            server.enqueue_message(MockResponse::text(
+                r#"{"name": "Alice", "age": 30, "addresses": [{"city": "Paris", "zip": null}]}"#,
+            ));
+            let request = crate::MessagesRequest::new(
+                "claude-sonnet-4-5-20250929",
+                256,
+                vec![Message::user("Alice, 30, Paris")],
+            );
+
+            let (person, _) = server
+                .client()
+                .send_structured_derived::<Person>(request)
+                .await
+                .unwrap();
+            assert_eq!(person.name, "Alice");
+
+            let sent = server.message_requests().remove(0);
+            let format = sent.output_config.unwrap().format.unwrap();
+            assert_eq!(format.schema, schema_for::<Person>());
+        }
+    }
 }
@@ -0,0 +1,763 @@
+//! Offline testing toolkit
+//!
+//! [`MockServer`] is a local HTTP server that speaks the parts of the Claude
+//! API this SDK uses: `/v1/messages` (JSON and SSE), `/v1/messages/count_tokens`,
+//! `/v1/messages/batches` and `/v1/files`. Each route serves a queue of
+//! scripted [`MockResponse`]s in order, so retry, rate-limit and tool-loop
+//! logic can be tested without network access or an API key.
+//!
+//! Requires the `testing` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::testing::{MockResponse, MockServer};
+//! use claude_sdk::{Message, MessagesRequest};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockServer::start().await;
+//! server.enqueue_message(MockResponse::rate_limited(1));
+//! server.enqueue_message(MockResponse::text("Hello from the mock!"));
+//!
+//! let client = server.client();
+//! let request = MessagesRequest::new(
+//!     "claude-sonnet-4-5-20250929",
+//!     1024,
+//!     vec![Message::user("Hi")],
+//! );
+//!
+//! // First call hits the scripted 429, second gets the canned reply
+//! assert!(client.send_message(request.clone()).await.is_err());
+//! let response = client.send_message(request).await?;
+//!
+//! assert_eq!(server.message_requests().len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A [`MockResponse::message`] (or [`text`](MockResponse::text) /
+//! [`tool_use`](MockResponse::tool_use)) is served as JSON to
+//! `send_message` and as a synthesized SSE stream to `send_streaming`, so the
+//! same script works for both.
+
+use crate::batch::BatchClient;
+use crate::client::ClaudeClient;
+use crate::files::FilesClient;
+use crate::streaming::StreamEvent;
+use crate::types::{MessagesRequest, MessagesResponse};
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Model reported by the canned responses
+const MOCK_MODEL: &str = "claude-sonnet-4-5-20250929";
+
+/// API route served by the mock server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockRoute {
+    /// `POST /v1/messages`
+    Messages,
+    /// `POST /v1/messages/count_tokens`
+    CountTokens,
+    /// Anything under `/v1/messages/batches`
+    Batches,
+    /// Anything under `/v1/files`
+    Files,
+}
+
+impl MockRoute {
+    /// Route for a request path, if it is one the mock server knows
+    pub fn for_path(path: &str) -> Option<Self> {
+        let path = path.split('?').next().unwrap_or(path);
+        if path == "/v1/messages" {
+            Some(Self::Messages)
+        } else if path == "/v1/messages/count_tokens" {
+            Some(Self::CountTokens)
+        } else if path.starts_with("/v1/messages/batches") {
+            Some(Self::Batches)
+        } else if path.starts_with("/v1/files") {
+            Some(Self::Files)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MockBody {
+    /// Served as JSON, or as SSE when the request asked to stream
+    Message(Box<MessagesResponse>),
+    Json(Value),
+    Events(Vec<StreamEvent>),
+    Raw {
+        content_type: String,
+        body: String,
+    },
+}
+
+/// A scripted response for the mock server
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::testing::MockResponse;
+/// use serde_json::json;
+/// use std::time::Duration;
+///
+/// let reply = MockResponse::text("Hi!");
+/// let tool_call = MockResponse::tool_use("toolu_1", "get_weather", json!({"city": "Paris"}));
+/// let slow = MockResponse::text("...").with_delay(Duration::from_millis(50));
+/// let limited = MockResponse::rate_limited(2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    delay: Duration,
+    body: MockBody,
+}
+
+impl MockResponse {
+    fn new(status: u16, body: MockBody) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+            body,
+        }
+    }
+
+    /// A complete message, served as JSON or as an SSE stream
+    pub fn message(message: MessagesResponse) -> Self {
+        Self::new(200, MockBody::Message(Box::new(message)))
+    }
+
+    /// An assistant message with a single text block and `end_turn`
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::message(mock_message(
+            json!([{"type": "text", "text": text.into()}]),
+            "end_turn",
+        ))
+    }
+
+    /// An assistant message calling one tool, with `stop_reason: tool_use`
+    pub fn tool_use(id: impl Into<String>, name: impl Into<String>, input: Value) -> Self {
+        Self::message(mock_message(
+            json!([{"type": "tool_use", "id": id.into(), "name": name.into(), "input": input}]),
+            "tool_use",
+        ))
+    }
+
+    /// An arbitrary JSON body with status 200
+    pub fn json(body: Value) -> Self {
+        Self::new(200, MockBody::Json(body))
+    }
+
+    /// A `count_tokens` response
+    pub fn token_count(input_tokens: u32) -> Self {
+        Self::json(json!({"input_tokens": input_tokens}))
+    }
+
+    /// An explicit SSE event sequence, served regardless of the request's `stream` flag
+    pub fn events(events: Vec<StreamEvent>) -> Self {
+        Self::new(200, MockBody::Events(events))
+    }
+
+    /// A raw body with the given content type (e.g. JSONL batch results)
+    pub fn raw(content_type: impl Into<String>, body: impl Into<String>) -> Self {
+        Self::new(
+            200,
+            MockBody::Raw {
+                content_type: content_type.into(),
+                body: body.into(),
+            },
+        )
+    }
+
+    /// An API error in the standard `{"type": "error", "error": {...}}` envelope
+    pub fn error(status: u16, error_type: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(
+            status,
+            MockBody::Json(json!({
+                "type": "error",
+                "error": {"type": error_type.into(), "message": message.into()}
+            })),
+        )
+    }
+
+    /// A 429 with a `retry-after` header
+    pub fn rate_limited(retry_after_secs: u64) -> Self {
+        Self::error(429, "rate_limit_error", "Rate limited")
+            .with_header("retry-after", retry_after_secs.to_string())
+    }
+
+    /// A 529 overloaded error
+    pub fn overloaded() -> Self {
+        Self::error(529, "overloaded_error", "Overloaded")
+    }
+
+    /// Override the HTTP status
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Delay the response
+    ///
+    /// For streamed responses the delay is applied before every SSE event,
+    /// which makes a slow stream; otherwise it is applied once before the
+    /// response is sent.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Build a canned message; the JSON shape is fixed, so parsing cannot fail
+fn mock_message(content: Value, stop_reason: &str) -> MessagesResponse {
+    serde_json::from_value(json!({
+        "id": "msg_mock",
+        "type": "message",
+        "role": "assistant",
+        "content": content,
+        "model": MOCK_MODEL,
+        "stop_reason": stop_reason,
+        "usage": {"input_tokens": 10, "output_tokens": 10}
+    }))
+    .expect("canned mock message is valid")
+}
+
+/// A request received by the mock server
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// HTTP method
+    pub method: String,
+    /// Path including any query string
+    pub path: String,
+    /// Request headers (lowercase names)
+    pub headers: HashMap<String, String>,
+    /// Raw request body
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    /// The route this request was sent to
+    pub fn route(&self) -> Option<MockRoute> {
+        MockRoute::for_path(&self.path)
+    }
+
+    /// The body parsed as JSON, if it is JSON
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    queues: Mutex<HashMap<MockRoute, VecDeque<MockResponse>>>,
+    requests: Mutex<Vec<ReceivedRequest>>,
+}
+
+/// A local mock of the Claude API
+///
+/// The server listens on an ephemeral `127.0.0.1` port and shuts down when
+/// dropped. Requests to a route with an empty queue get a 404
+/// `not_found_error` naming the route, so a missing script fails loudly.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Start a mock server on an ephemeral local port
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState::default());
+        let app = Router::new().fallback(handle).with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind mock server");
+        let addr = listener.local_addr().expect("mock server has no address");
+        let handle = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        Self {
+            addr,
+            state,
+            handle,
+        }
+    }
+
+    /// Base URL of the server (e.g. `http://127.0.0.1:51234`)
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A [`ClaudeClient`] pointed at this server
+    pub fn client(&self) -> ClaudeClient {
+        ClaudeClient::anthropic("test-key").with_base_url(self.url())
+    }
+
+    /// A [`FilesClient`] pointed at this server
+    pub fn files_client(&self) -> FilesClient {
+        FilesClient::new("test-key").with_base_url(self.url())
+    }
+
+    /// A [`BatchClient`] pointed at this server
+    pub fn batch_client(&self) -> BatchClient {
+        BatchClient::new("test-key").with_base_url(self.url())
+    }
+
+    /// Queue a response for a route
+    pub fn enqueue(&self, route: MockRoute, response: MockResponse) -> &Self {
+        self.state
+            .queues
+            .lock()
+            .unwrap()
+            .entry(route)
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Queue a response for `POST /v1/messages`
+    pub fn enqueue_message(&self, response: MockResponse) -> &Self {
+        self.enqueue(MockRoute::Messages, response)
+    }
+
+    /// Number of responses still queued for a route
+    pub fn pending(&self, route: MockRoute) -> usize {
+        self.state
+            .queues
+            .lock()
+            .unwrap()
+            .get(&route)
+            .map_or(0, VecDeque::len)
+    }
+
+    /// Every request received so far, in arrival order
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Bodies of the requests sent to `POST /v1/messages`
+    pub fn message_requests(&self) -> Vec<MessagesRequest> {
+        self.requests()
+            .iter()
+            .filter(|r| r.route() == Some(MockRoute::Messages))
+            .filter_map(|r| serde_json::from_slice(&r.body).ok())
+            .collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl std::fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockServer")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+async fn handle(State(state): State<Arc<MockState>>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .unwrap_or_default()
+        .to_vec();
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path().to_string(), |pq| pq.to_string());
+    let received = ReceivedRequest {
+        method: parts.method.to_string(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect(),
+        path,
+        body,
+    };
+
+    let streaming = received
+        .json()
+        .and_then(|body| body.get("stream").and_then(Value::as_bool))
+        .unwrap_or(false);
+    let route = received.route();
+    let scripted = route.and_then(|route| {
+        state
+            .queues
+            .lock()
+            .unwrap()
+            .get_mut(&route)
+            .and_then(VecDeque::pop_front)
+    });
+    let description = format!("{} {}", received.method, received.path);
+    state.requests.lock().unwrap().push(received);
+
+    let Some(scripted) = scripted else {
+        return into_response(MockResponse::error(
+            404,
+            "not_found_error",
+            format!("mock server has no response queued for {}", description),
+        ));
+    };
+
+    if !scripted.delay.is_zero() && !is_streamed(&scripted, streaming) {
+        tokio::time::sleep(scripted.delay).await;
+    }
+
+    match &scripted.body {
+        MockBody::Message(message) if streaming => {
+            let events = message_events(message);
+            sse_response(&scripted, events)
+        }
+        MockBody::Events(events) => {
+            let events = events
+                .iter()
+                .map(|e| serde_json::to_value(e).unwrap_or(Value::Null))
+                .collect();
+            sse_response(&scripted, events)
+        }
+        _ => into_response(scripted),
+    }
+}
+
+fn is_streamed(response: &MockResponse, streaming: bool) -> bool {
+    match response.body {
+        MockBody::Message(_) => streaming,
+        MockBody::Events(_) => true,
+        _ => false,
+    }
+}
+
+/// Build a complete (non-streamed) response
+fn into_response(response: MockResponse) -> Response {
+    let (content_type, body) = match response.body {
+        MockBody::Message(message) => (
+            "application/json".to_string(),
+            serde_json::to_string(&message).unwrap_or_default(),
+        ),
+        MockBody::Json(value) => ("application/json".to_string(), value.to_string()),
+        MockBody::Events(_) => unreachable!("event bodies are always streamed"),
+        MockBody::Raw { content_type, body } => (content_type, body),
+    };
+    with_headers(
+        Response::builder()
+            .status(status_code(response.status))
+            .header("content-type", content_type)
+            .body(Body::from(body)),
+        &response.headers,
+    )
+}
+
+/// Build an SSE response that emits `events`, pausing before each one
+fn sse_response(response: &MockResponse, events: Vec<Value>) -> Response {
+    let delay = response.delay;
+    let stream = async_stream::stream! {
+        for event in events {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            let name = event
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or("message")
+                .to_string();
+            let frame = format!("event: {}\ndata: {}\n\n", name, event);
+            yield Ok::<_, Infallible>(Bytes::from(frame));
+        }
+    };
+    with_headers(
+        Response::builder()
+            .status(status_code(response.status))
+            .header("content-type", "text/event-stream")
+            .header("cache-control", "no-cache")
+            .body(Body::from_stream(stream)),
+        &response.headers,
+    )
+}
+
+fn status_code(status: u16) -> StatusCode {
+    StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn with_headers(response: axum::http::Result<Response>, headers: &[(String, String)]) -> Response {
+    let mut response = response.unwrap_or_else(|_| {
+        Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::empty())
+            .unwrap()
+    });
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
+}
+
+/// Synthesize the SSE event sequence the API would send for `message`
+fn message_events(message: &MessagesResponse) -> Vec<Value> {
+    let mut events = vec![json!({
+        "type": "message_start",
+        "message": {
+            "id": message.id,
+            "type": "message",
+            "role": "assistant",
+            "content": [],
+            "model": message.model,
+            "usage": message.usage,
+        }
+    })];
+
+    for (index, block) in message.content.iter().enumerate() {
+        let block = serde_json::to_value(block).unwrap_or(Value::Null);
+        let block_type = block.get("type").and_then(Value::as_str).unwrap_or("");
+        let (start, deltas) = match block_type {
+            "text" => (
+                json!({"type": "text", "text": ""}),
+                vec![json!({"type": "text_delta", "text": block["text"]})],
+            ),
+            "thinking" => {
+                let mut deltas =
+                    vec![json!({"type": "thinking_delta", "thinking": block["thinking"]})];
+                if let Some(signature) = block.get("signature").filter(|s| !s.is_null()) {
+                    deltas.push(json!({"type": "signature_delta", "signature": signature}));
+                }
+                (json!({"type": "thinking", "thinking": ""}), deltas)
+            }
+            "tool_use" | "server_tool_use" => {
+                let mut start = block.clone();
+                start["input"] = json!({});
+                let input = block.get("input").cloned().unwrap_or_else(|| json!({}));
+                (
+                    start,
+                    vec![json!({"type": "input_json_delta", "partial_json": input.to_string()})],
+                )
+            }
+            _ => (block.clone(), Vec::new()),
+        };
+
+        events.push(json!({"type": "content_block_start", "index": index, "content_block": start}));
+        for delta in deltas {
+            events.push(json!({"type": "content_block_delta", "index": index, "delta": delta}));
+        }
+        events.push(json!({"type": "content_block_stop", "index": index}));
+    }
+
+    events.push(json!({
+        "type": "message_delta",
+        "delta": {"stop_reason": message.stop_reason, "stop_sequence": message.stop_sequence},
+        "usage": message.usage,
+    }));
+    events.push(json!({"type": "message_stop"}));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::retry::{retry_with_backoff, RetryConfig};
+    use crate::types::{ContentBlock, Message, StopReason};
+    use futures::StreamExt;
+
+    fn request() -> MessagesRequest {
+        MessagesRequest::new(MOCK_MODEL, 256, vec![Message::user("Hi")])
+    }
+
+    #[test]
+    fn test_route_for_path() {
+        assert_eq!(
+            MockRoute::for_path("/v1/messages"),
+            Some(MockRoute::Messages)
+        );
+        assert_eq!(
+            MockRoute::for_path("/v1/messages/count_tokens"),
+            Some(MockRoute::CountTokens)
+        );
+        assert_eq!(
+            MockRoute::for_path("/v1/messages/batches?limit=5"),
+            Some(MockRoute::Batches)
+        );
+        assert_eq!(
+            MockRoute::for_path("/v1/files/file_1/content"),
+            Some(MockRoute::Files)
+        );
+        assert_eq!(MockRoute::for_path("/v1/models"), None);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_messages_in_order() {
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::tool_use(
+                "toolu_1",
+                "get_weather",
+                json!({"city": "Paris"}),
+            ))
+            .enqueue_message(MockResponse::text("Sunny"));
+        let client = server.client();
+
+        let first = client.send_message(request()).await.unwrap();
+        assert_eq!(first.stop_reason, Some(StopReason::ToolUse));
+        assert!(matches!(
+            &first.content[0],
+            ContentBlock::ToolUse { name, .. } if name == "get_weather"
+        ));
+
+        let second = client.send_message(request()).await.unwrap();
+        assert!(matches!(
+            &second.content[0],
+            ContentBlock::Text { text, .. } if text == "Sunny"
+        ));
+
+        assert_eq!(server.pending(MockRoute::Messages), 0);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].headers.get("x-api-key").unwrap(), "test-key");
+        assert_eq!(server.message_requests()[0].model, MOCK_MODEL);
+    }
+
+    #[tokio::test]
+    async fn test_message_is_streamed_when_requested() {
+        let server = MockServer::start().await;
+        server.enqueue_message(
+            MockResponse::tool_use("toolu_1", "lookup", json!({"q": "rust"}))
+                .with_delay(Duration::from_millis(5)),
+        );
+
+        let events: Vec<StreamEvent> = server
+            .client()
+            .send_streaming(request())
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+        match &events[2] {
+            StreamEvent::ContentBlockDelta { delta, .. } => {
+                assert_eq!(delta.partial_json(), Some(r#"{"q":"rust"}"#))
+            }
+            other => panic!("Expected ContentBlockDelta, got {:?}", other),
+        }
+        assert!(matches!(
+            &events[4],
+            StreamEvent::MessageDelta { delta, .. } if delta.stop_reason == Some(StopReason::ToolUse)
+        ));
+        assert!(matches!(events.last(), Some(StreamEvent::MessageStop)));
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_unscripted_routes() {
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::rate_limited(3))
+            .enqueue_message(MockResponse::error(
+                400,
+                "invalid_request_error",
+                "max_tokens too large",
+            ));
+        let client = server.client();
+
+        match client.send_message(request()).await {
+            Err(Error::RateLimit { retry_after, .. }) => assert_eq!(retry_after, Some(3)),
+            other => panic!("Expected RateLimit, got {:?}", other),
+        }
+        match client.send_message(request()).await {
+            Err(Error::Api { error_type, .. }) => {
+                assert_eq!(error_type.as_deref(), Some("invalid_request_error"))
+            }
+            other => panic!("Expected Api error, got {:?}", other),
+        }
+        match client.count_tokens(request()).await {
+            Err(error) => assert!(error.to_string().contains("no response queued")),
+            Ok(_) => panic!("Expected unscripted route to fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_after_overload() {
+        let server = MockServer::start().await;
+        server
+            .enqueue_message(MockResponse::overloaded())
+            .enqueue_message(MockResponse::text("Recovered"));
+        let client = server.client();
+
+        let config = RetryConfig::new().with_initial_backoff(Duration::from_millis(1));
+        let response = retry_with_backoff(config, || client.send_message(request()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.id, "msg_mock");
+        assert_eq!(server.message_requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_token_count_files_and_batches() {
+        let server = MockServer::start().await;
+        server
+            .enqueue(MockRoute::CountTokens, MockResponse::token_count(42))
+            .enqueue(
+                MockRoute::Files,
+                MockResponse::json(json!({"data": [{
+                    "id": "file_1",
+                    "type": "file",
+                    "filename": "notes.txt",
+                    "mime_type": "text/plain",
+                    "size_bytes": 12,
+                    "created_at": "2026-01-01T00:00:00Z",
+                    "downloadable": false
+                }]})),
+            )
+            .enqueue(MockRoute::Batches, MockResponse::json(json!({"data": []})));
+
+        let count = server.client().count_tokens(request()).await.unwrap();
+        assert_eq!(count.input_tokens, 42);
+
+        let files = server.files_client().list().await.unwrap();
+        assert_eq!(files[0].filename, "notes.txt");
+
+        let batches = server.batch_client().list(Some(5)).await.unwrap();
+        assert!(batches.is_empty());
+        assert_eq!(
+            server.requests().last().unwrap().path,
+            "/v1/messages/batches?limit=5"
+        );
+    }
+}
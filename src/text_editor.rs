@@ -0,0 +1,560 @@
+//! Local executor for the text editor client tool
+//!
+//! [`TextEditorTool`] advertises Anthropic's text editor tool, whose calls
+//! come back as `tool_use` blocks for the client to run. [`TextEditorExecutor`]
+//! is a [`Tool`] that runs them against files under a root directory:
+//!
+//! - `view` shows a file with line numbers (optionally a `view_range`) or
+//!   lists a directory two levels deep
+//! - `create` writes a new file
+//! - `str_replace` replaces text that must occur exactly once
+//! - `insert` adds text after a line (`0` for the top of the file)
+//! - `undo_edit` reverts the last edit to a file
+//!
+//! Relative paths are resolved against the root; absolute paths must lie
+//! inside it. Paths that escape the root, including through `..` or
+//! symlinks, are rejected. Every edit records the previous contents, so
+//! `undo_edit` can be repeated to step back through a file's history.
+//!
+//! Requires the `local-tools` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::bash::BashExecutor;
+//! use claude_sdk::text_editor::TextEditorExecutor;
+//! use claude_sdk::ToolRegistry;
+//!
+//! let registry = ToolRegistry::new()
+//!     .with_tool(TextEditorExecutor::new("/tmp/workspace"))
+//!     .with_tool(BashExecutor::new("/tmp/workspace"));
+//! ```
+
+use crate::editing::{
+    insert_after, number_lines, replace_once, snippet, string_arg, view_range, ReplaceError,
+};
+use crate::server_tools::TextEditorTool;
+use crate::tools::{async_trait, Tool, ToolContext, ToolOutput};
+use crate::types::ToolDefinition;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Runs text editor tool calls against a directory
+#[derive(Debug)]
+pub struct TextEditorExecutor {
+    root: PathBuf,
+    definition: TextEditorTool,
+    /// Earlier contents per file, most recent last; `None` if the file did not exist
+    history: Mutex<HashMap<PathBuf, Vec<Option<String>>>>,
+}
+
+type EditResult = std::result::Result<String, String>;
+
+impl TextEditorExecutor {
+    /// Create an executor for files under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            definition: TextEditorTool::new(),
+            history: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Advertise a different tool version or name
+    ///
+    /// `max_characters` on the definition also limits `view` output.
+    pub fn with_definition(mut self, definition: TextEditorTool) -> Self {
+        self.definition = definition;
+        self
+    }
+
+    /// The directory files are confined to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve a path from Claude, rejecting anything outside the root
+    fn resolve(&self, path: &str) -> std::result::Result<PathBuf, String> {
+        let root = self.root.canonicalize().map_err(|e| {
+            format!(
+                "Root directory {} is unavailable: {}",
+                self.root.display(),
+                e
+            )
+        })?;
+        let requested = Path::new(path);
+        let joined = if requested.is_absolute() {
+            requested.to_path_buf()
+        } else {
+            root.join(requested)
+        };
+
+        // Normalize `.` and `..` without touching the filesystem
+        let mut normalized = PathBuf::new();
+        for component in joined.components() {
+            match component {
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                Component::CurDir => {}
+                other => normalized.push(other),
+            }
+        }
+
+        // Resolve symlinks in the part of the path that exists
+        let mut existing = normalized.as_path();
+        let mut missing = Vec::new();
+        while !existing.exists() {
+            match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name.to_owned());
+                    existing = parent;
+                }
+                _ => break,
+            }
+        }
+        let mut resolved = existing
+            .canonicalize()
+            .unwrap_or_else(|_| existing.to_path_buf());
+        resolved.extend(missing.iter().rev());
+
+        if resolved.starts_with(&root) {
+            Ok(resolved)
+        } else {
+            Err(format!(
+                "Path {} is outside the allowed directory {}",
+                path,
+                root.display()
+            ))
+        }
+    }
+
+    fn record(&self, path: &Path, previous: Option<String>) {
+        self.history
+            .lock()
+            .expect("history lock poisoned")
+            .entry(path.to_path_buf())
+            .or_default()
+            .push(previous);
+    }
+
+    async fn view(&self, input: &Value, display: &str, path: &Path) -> EditResult {
+        if path.is_dir() {
+            if input.get("view_range").is_some_and(|r| !r.is_null()) {
+                return Err("The `view_range` parameter is not allowed for directories".into());
+            }
+            let mut entries = Vec::new();
+            list_dir(path, path, 2, &mut entries).map_err(|e| e.to_string())?;
+            entries.sort();
+            return Ok(format!(
+                "Files and directories up to 2 levels deep in {}, excluding hidden items:\n{}",
+                display,
+                entries.join("\n")
+            ));
+        }
+
+        let content = read(path, display).await?;
+        let lines: Vec<&str> = content.lines().collect();
+        let (start, end) = match input.get("view_range").and_then(Value::as_array) {
+            Some(range) => view_range(range, lines.len())?,
+            None => (1, lines.len()),
+        };
+        let numbered = number_lines(&lines[start.saturating_sub(1)..end], start);
+
+        Ok(match self.definition.max_characters {
+            Some(max) if numbered.chars().count() > max as usize => {
+                let truncated: String = numbered.chars().take(max as usize).collect();
+                format!(
+                    "{}\n[output truncated at {} characters; use view_range to see more]",
+                    truncated, max
+                )
+            }
+            _ => numbered,
+        })
+    }
+
+    async fn create(&self, input: &Value, display: &str, path: &Path) -> EditResult {
+        let text = string_arg(input, "file_text")?;
+        if path.exists() {
+            return Err(format!(
+                "File already exists at {}; use `str_replace` to edit it",
+                display
+            ));
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create {}: {}", display, e))?;
+        }
+        write(path, display, text).await?;
+        self.record(path, None);
+        Ok(format!("File created successfully at {}", display))
+    }
+
+    async fn str_replace(&self, input: &Value, display: &str, path: &Path) -> EditResult {
+        let old = string_arg(input, "old_str")?;
+        let new = input.get("new_str").and_then(Value::as_str).unwrap_or("");
+        let content = read(path, display).await?;
+
+        let edit = replace_once(&content, old, new).map_err(|e| match e {
+            ReplaceError::NotFound => format!(
+                "No replacement was performed: `old_str` did not appear verbatim in {}",
+                display
+            ),
+            ReplaceError::Ambiguous(lines) => format!(
+                "No replacement was performed: `old_str` occurs {} times in {} (lines {}); include more context to make it unique",
+                lines.len(),
+                display,
+                lines.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")
+            ),
+        })?;
+
+        write(path, display, &edit.updated).await?;
+        self.record(path, Some(content));
+        Ok(format!(
+            "The file {} has been edited. Snippet of the result:\n{}",
+            display,
+            snippet(&edit.updated, edit.first, edit.last)
+        ))
+    }
+
+    async fn insert(&self, input: &Value, display: &str, path: &Path) -> EditResult {
+        let after = input
+            .get("insert_line")
+            .and_then(Value::as_u64)
+            .ok_or("Missing `insert_line` parameter")? as usize;
+        // `insert_text` in newer tool versions, `new_str` in older ones
+        let text = string_arg(input, "insert_text").or_else(|_| string_arg(input, "new_str"))?;
+        let content = read(path, display).await?;
+
+        let edit = insert_after(&content, after, text).ok_or_else(|| {
+            format!(
+                "Invalid `insert_line` {}: the file has {} lines",
+                after,
+                content.lines().count()
+            )
+        })?;
+
+        write(path, display, &edit.updated).await?;
+        self.record(path, Some(content));
+        Ok(format!(
+            "The file {} has been edited. Snippet of the result:\n{}",
+            display,
+            snippet(&edit.updated, edit.first, edit.last)
+        ))
+    }
+
+    async fn undo_edit(&self, display: &str, path: &Path) -> EditResult {
+        let previous = self
+            .history
+            .lock()
+            .expect("history lock poisoned")
+            .get_mut(path)
+            .and_then(Vec::pop)
+            .ok_or_else(|| format!("No edit history for {}", display))?;
+
+        match previous {
+            Some(content) => {
+                write(path, display, &content).await?;
+                Ok(format!("Last edit to {} undone", display))
+            }
+            None => {
+                tokio::fs::remove_file(path)
+                    .await
+                    .map_err(|e| format!("Failed to remove {}: {}", display, e))?;
+                Ok(format!(
+                    "Last edit to {} undone; the file was removed",
+                    display
+                ))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for TextEditorExecutor {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn description(&self) -> &str {
+        "View, create and edit files"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {"enum": ["view", "create", "str_replace", "insert", "undo_edit"]},
+                "path": {"type": "string"},
+                "view_range": {"type": "array", "items": {"type": "integer"}},
+                "file_text": {"type": "string"},
+                "old_str": {"type": "string"},
+                "new_str": {"type": "string"},
+                "insert_line": {"type": "integer"},
+                "insert_text": {"type": "string"}
+            },
+            "required": ["command", "path"]
+        })
+    }
+
+    fn tool_definition(&self) -> ToolDefinition {
+        self.definition.clone().into()
+    }
+
+    async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+        let command = input["command"].as_str().unwrap_or_default();
+        let Some(display) = input["path"].as_str() else {
+            return ToolOutput::error("Missing `path` parameter");
+        };
+        let path = match self.resolve(display) {
+            Ok(path) => path,
+            Err(e) => return ToolOutput::error(e),
+        };
+        debug!("text editor {} {}", command, path.display());
+
+        let result = match command {
+            "view" => self.view(&input, display, &path).await,
+            "create" => self.create(&input, display, &path).await,
+            "str_replace" => self.str_replace(&input, display, &path).await,
+            "insert" => self.insert(&input, display, &path).await,
+            "undo_edit" => self.undo_edit(display, &path).await,
+            other => Err(format!("Unknown command: {}", other)),
+        };
+        match result {
+            Ok(text) => ToolOutput::text(text),
+            Err(e) => ToolOutput::error(e),
+        }
+    }
+}
+
+async fn read(path: &Path, display: &str) -> EditResult {
+    if !path.exists() {
+        return Err(format!("The path {} does not exist", display));
+    }
+    tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", display, e))
+}
+
+async fn write(path: &Path, display: &str, content: &str) -> std::result::Result<(), String> {
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", display, e))
+}
+
+fn list_dir(
+    base: &Path,
+    dir: &Path,
+    depth: usize,
+    entries: &mut Vec<String>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(base)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        if path.is_dir() {
+            entries.push(format!("{}/", relative));
+            if depth > 1 {
+                list_dir(base, &path, depth - 1, entries)?;
+            }
+        } else {
+            entries.push(relative);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolResultContent;
+
+    fn root(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("claude_sdk_editor_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn call(editor: &TextEditorExecutor, input: Value) -> (String, bool) {
+        let output = editor
+            .call(input, &ToolContext::new("toolu_1", "str_replace_editor"))
+            .await;
+        match output.content {
+            ToolResultContent::Text(text) => (text, output.is_error),
+            other => panic!("Expected text, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_view_and_undo() {
+        let dir = root("create");
+        let editor = TextEditorExecutor::new(&dir);
+
+        let (text, is_error) = call(
+            &editor,
+            json!({"command": "create", "path": "src/lib.rs", "file_text": "one\ntwo\nthree\n"}),
+        )
+        .await;
+        assert!(!is_error, "{}", text);
+
+        let (text, _) = call(&editor, json!({"command": "view", "path": "src/lib.rs"})).await;
+        assert_eq!(text, "     1\tone\n     2\ttwo\n     3\tthree");
+        let (text, _) = call(
+            &editor,
+            json!({"command": "view", "path": "src/lib.rs", "view_range": [2, -1]}),
+        )
+        .await;
+        assert_eq!(text, "     2\ttwo\n     3\tthree");
+        let (_, is_error) = call(
+            &editor,
+            json!({"command": "view", "path": "src/lib.rs", "view_range": [3, 9]}),
+        )
+        .await;
+        assert!(is_error);
+
+        let (text, _) = call(&editor, json!({"command": "view", "path": "."})).await;
+        assert!(text.ends_with("\nsrc/\nsrc/lib.rs"), "{}", text);
+
+        let (_, is_error) = call(
+            &editor,
+            json!({"command": "create", "path": "src/lib.rs", "file_text": "again"}),
+        )
+        .await;
+        assert!(is_error);
+
+        call(
+            &editor,
+            json!({"command": "undo_edit", "path": "src/lib.rs"}),
+        )
+        .await;
+        assert!(!dir.join("src/lib.rs").exists());
+    }
+
+    #[tokio::test]
+    async fn test_str_replace_requires_unique_match() {
+        let dir = root("replace");
+        std::fs::write(dir.join("a.txt"), "let x = 1;\nlet y = 1;\n").unwrap();
+        let editor = TextEditorExecutor::new(&dir);
+
+        let (text, is_error) = call(
+            &editor,
+            json!({"command": "str_replace", "path": "a.txt", "old_str": "= 1", "new_str": "= 2"}),
+        )
+        .await;
+        assert!(is_error);
+        assert!(
+            text.contains("occurs 2 times in a.txt (lines 1, 2)"),
+            "{}",
+            text
+        );
+
+        let (text, is_error) = call(
+            &editor,
+            json!({"command": "str_replace", "path": "a.txt", "old_str": "missing", "new_str": ""}),
+        )
+        .await;
+        assert!(is_error);
+        assert!(text.contains("did not appear verbatim"));
+
+        let (text, is_error) = call(
+            &editor,
+            json!({"command": "str_replace", "path": "a.txt", "old_str": "y = 1", "new_str": "y = 2"}),
+        )
+        .await;
+        assert!(!is_error);
+        assert!(
+            text.ends_with("     1\tlet x = 1;\n     2\tlet y = 2;"),
+            "{}",
+            text
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("a.txt")).unwrap(),
+            "let x = 1;\nlet y = 2;\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_history() {
+        let dir = root("insert");
+        std::fs::write(dir.join("b.txt"), "a\nc\n").unwrap();
+        let editor = TextEditorExecutor::new(&dir);
+
+        call(
+            &editor,
+            json!({"command": "insert", "path": "b.txt", "insert_line": 1, "insert_text": "b"}),
+        )
+        .await;
+        call(
+            &editor,
+            json!({"command": "insert", "path": "b.txt", "insert_line": 0, "new_str": "start"}),
+        )
+        .await;
+        let contents = || std::fs::read_to_string(dir.join("b.txt")).unwrap();
+        assert_eq!(contents(), "start\na\nb\nc\n");
+
+        let (_, is_error) = call(
+            &editor,
+            json!({"command": "insert", "path": "b.txt", "insert_line": 9, "insert_text": "x"}),
+        )
+        .await;
+        assert!(is_error);
+
+        call(&editor, json!({"command": "undo_edit", "path": "b.txt"})).await;
+        assert_eq!(contents(), "a\nb\nc\n");
+        call(&editor, json!({"command": "undo_edit", "path": "b.txt"})).await;
+        assert_eq!(contents(), "a\nc\n");
+        let (text, is_error) =
+            call(&editor, json!({"command": "undo_edit", "path": "b.txt"})).await;
+        assert!(is_error);
+        assert_eq!(text, "No edit history for b.txt");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_paths_outside_root() {
+        let dir = root("escape");
+        std::fs::create_dir_all(dir.join("inner")).unwrap();
+        let outside = root("escape_outside");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside, dir.join("inner/link")).unwrap();
+        let editor = TextEditorExecutor::new(dir.join("inner"));
+
+        for path in ["../secret.txt", "/etc/passwd", "link/file.txt"] {
+            let (text, is_error) = call(
+                &editor,
+                json!({"command": "create", "path": path, "file_text": "x"}),
+            )
+            .await;
+            assert!(is_error, "{} was allowed", path);
+            assert!(text.contains("outside the allowed directory"), "{}", text);
+        }
+
+        let inside = dir.canonicalize().unwrap().join("inner/ok.txt");
+        let (_, is_error) = call(
+            &editor,
+            json!({"command": "create", "path": inside.to_string_lossy(), "file_text": "x"}),
+        )
+        .await;
+        assert!(!is_error);
+    }
+
+    #[test]
+    fn test_advertises_text_editor_tool() {
+        let editor = TextEditorExecutor::new(".");
+        assert_eq!(editor.name(), "str_replace_editor");
+        let definition = serde_json::to_value(editor.tool_definition()).unwrap();
+        assert_eq!(definition["type"], "text_editor_20250728");
+    }
+}
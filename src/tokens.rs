@@ -99,7 +99,8 @@ impl TokenCounter {
                 // This is just the metadata overhead
                 total
             }
-            ContentBlock::ToolUse { name, input, .. } => {
+            ContentBlock::ToolUse { name, input, .. }
+            | ContentBlock::McpToolUse { name, input, .. } => {
                 let mut total = 4; // Type and structure overhead
                 total += self.count_text(name);
                 total += self.count_text(&input.to_string());
@@ -109,6 +110,11 @@ impl TokenCounter {
                 tool_use_id,
                 content,
                 ..
+            }
+            | ContentBlock::McpToolResult {
+                tool_use_id,
+                content,
+                ..
             } => {
                 let mut total = 4; // Type and structure overhead
                 total += self.count_text(tool_use_id);
@@ -152,9 +158,13 @@ impl TokenCounter {
 
                 total
             }
+            ContentBlock::ToolReference { tool_name } => 2 + self.count_text(tool_name),
             ContentBlock::ServerToolUse { .. } => 0,
             ContentBlock::WebSearchToolResult { .. } => 0,
+            ContentBlock::WebFetchToolResult { .. } => 0,
             ContentBlock::CodeExecutionToolResult { .. } => 0,
+            ContentBlock::BashCodeExecutionToolResult { .. } => 0,
+            ContentBlock::TextEditorCodeExecutionToolResult { .. } => 0,
             ContentBlock::ContainerUpload { .. } => 0,
             ContentBlock::MidConvSystem { .. } => 0,
             ContentBlock::Unknown { .. } => 0,
@@ -203,7 +213,11 @@ impl TokenCounter {
 
     /// Count total tokens in a request
     ///
-    /// This estimates the input tokens that will be charged.
+    /// This estimates the input tokens that will be charged. Tools Claude
+    /// loads from [`mcp_servers`](MessagesRequest::mcp_servers) are not
+    /// known locally and are not included; use
+    /// [`ClaudeClient::count_tokens`](crate::ClaudeClient::count_tokens) for
+    /// an exact count.
     ///
     /// # Example
     ///
@@ -373,6 +387,38 @@ mod tests {
         assert!(tokens2 > 10);
     }
 
+    #[test]
+    fn test_count_mcp_connector_blocks() {
+        let counter = TokenCounter::new();
+        let input = json!({"query": "rust"});
+        let tool_use = ContentBlock::ToolUse {
+            id: "toolu_1".into(),
+            name: "search".into(),
+            input: input.clone(),
+            caller: None,
+            cache_control: None,
+        };
+        let mcp_tool_use = ContentBlock::McpToolUse {
+            id: "mcptoolu_1".into(),
+            name: "search".into(),
+            server_name: "docs".into(),
+            input,
+            cache_control: None,
+        };
+        assert_eq!(
+            counter.count_content_block(&mcp_tool_use),
+            counter.count_content_block(&tool_use)
+        );
+
+        let mcp_result = ContentBlock::McpToolResult {
+            tool_use_id: "mcptoolu_1".into(),
+            content: Some(ToolResultContent::Text("Rust is a language".into())),
+            is_error: None,
+            cache_control: None,
+        };
+        assert!(counter.count_content_block(&mcp_result) > 4);
+    }
+
     #[test]
     fn test_count_request() {
         let counter = TokenCounter::new();
@@ -0,0 +1,722 @@
+//! Client-side tool search
+//!
+//! With hundreds of tools, sending every definition on every request wastes
+//! context and hurts tool selection. The server-side
+//! [`ToolSearchBm25`](crate::server_tools::ToolSearchBm25) and
+//! [`ToolSearchRegex`](crate::server_tools::ToolSearchRegex) tools solve this
+//! on the Anthropic API; [`ToolSearch`] does the same locally, so ranking is
+//! under your control and it also works on Bedrock.
+//!
+//! [`ToolIndex`] is a BM25 and regex index over [`CustomTool`] names,
+//! descriptions and parameters. [`ToolSearch`] exposes it to Claude as an
+//! ordinary custom tool and delivers matches in one of two ways:
+//!
+//! - [`SearchDelivery::References`] answers with `tool_reference` blocks;
+//!   the catalog is sent with `defer_loading` so the API loads referenced
+//!   tools itself (Anthropic API only)
+//! - [`SearchDelivery::Inject`] answers with a text summary and adds the
+//!   matched definitions to later requests through [`ToolSearch::apply`]
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::agent::Agent;
+//! use claude_sdk::tool_search::{ToolIndex, ToolSearch};
+//! use claude_sdk::{ClaudeClient, ToolRegistry};
+//!
+//! # async fn example(client: ClaudeClient, catalog: ToolRegistry) -> claude_sdk::Result<()> {
+//! let search = ToolSearch::new(ToolIndex::from_registry(&catalog)).injecting();
+//!
+//! let mut tools = catalog;
+//! tools.register(std::sync::Arc::new(search.clone()));
+//!
+//! let agent = Agent::new(client, "claude-sonnet-4-5-20250929")
+//!     .with_tools(tools)
+//!     .with_request_hook(move |request| search.apply(request));
+//! let run = agent.run("File a ticket for the flaky deploy").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, Result};
+use crate::tools::{async_trait, Tool, ToolContext, ToolOutput, ToolRegistry};
+use crate::types::{ContentBlock, CustomTool, MessagesRequest, ToolDefinition};
+use regex::RegexBuilder;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// Default number of tools returned per search
+pub const DEFAULT_SEARCH_LIMIT: usize = 5;
+
+/// Longest regex pattern accepted from Claude
+const MAX_PATTERN_LEN: usize = 200;
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+
+/// BM25 document length normalization
+const B: f64 = 0.75;
+
+/// A tool matched by a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolMatch {
+    /// Tool name
+    pub name: String,
+    /// Relevance; higher is better
+    pub score: f64,
+}
+
+struct IndexedTool {
+    definition: CustomTool,
+    /// Name, description and parameter names and descriptions
+    text: String,
+    terms: HashMap<String, usize>,
+    len: usize,
+}
+
+/// Search index over tool definitions
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::tool_search::ToolIndex;
+/// use claude_sdk::CustomTool;
+/// use serde_json::json;
+///
+/// let index = ToolIndex::new([
+///     CustomTool::new("get_weather", "Current weather for a city", json!({"type": "object"})),
+///     CustomTool::new("create_ticket", "Open a support ticket", json!({"type": "object"})),
+/// ]);
+///
+/// assert_eq!(index.bm25("weather forecast", 5)[0].name, "get_weather");
+/// assert_eq!(index.regex("ticket$", 5).unwrap()[0].name, "create_ticket");
+/// ```
+#[derive(Default)]
+pub struct ToolIndex {
+    tools: Vec<IndexedTool>,
+    document_frequency: HashMap<String, usize>,
+    total_len: usize,
+}
+
+impl ToolIndex {
+    /// Index the given tools
+    pub fn new(tools: impl IntoIterator<Item = CustomTool>) -> Self {
+        let mut index = Self::default();
+        for tool in tools {
+            index.add(tool);
+        }
+        index
+    }
+
+    /// Index every custom tool definition in a registry
+    pub fn from_registry(registry: &ToolRegistry) -> Self {
+        Self::new(
+            registry
+                .definitions()
+                .into_iter()
+                .filter_map(|definition| match definition {
+                    ToolDefinition::Custom(tool) => Some(tool),
+                    ToolDefinition::Server(_) => None,
+                }),
+        )
+    }
+
+    /// Add a tool, replacing any tool with the same name
+    pub fn add(&mut self, tool: CustomTool) -> &mut Self {
+        if let Some(position) = self
+            .tools
+            .iter()
+            .position(|t| t.definition.name == tool.name)
+        {
+            let old = self.tools.remove(position);
+            self.total_len -= old.len;
+            for term in old.terms.keys() {
+                if let Some(count) = self.document_frequency.get_mut(term) {
+                    *count -= 1;
+                }
+            }
+        }
+
+        let text = searchable_text(&tool);
+        // The text starts with the name too, so the name is counted twice and
+        // name matches outrank description matches
+        let tokens: Vec<String> = tokenize(&tool.name)
+            .into_iter()
+            .chain(tokenize(&text))
+            .collect();
+        let mut terms = HashMap::new();
+        for token in &tokens {
+            *terms.entry(token.clone()).or_insert(0) += 1;
+        }
+        for term in terms.keys() {
+            *self.document_frequency.entry(term.clone()).or_insert(0) += 1;
+        }
+        self.total_len += tokens.len();
+        self.tools.push(IndexedTool {
+            definition: tool,
+            text,
+            terms,
+            len: tokens.len(),
+        });
+        self
+    }
+
+    /// Number of indexed tools
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Definition of an indexed tool
+    pub fn get(&self, name: &str) -> Option<&CustomTool> {
+        self.tools
+            .iter()
+            .map(|t| &t.definition)
+            .find(|t| t.name == name)
+    }
+
+    /// Every indexed definition, in insertion order
+    pub fn definitions(&self) -> impl Iterator<Item = &CustomTool> {
+        self.tools.iter().map(|t| &t.definition)
+    }
+
+    /// Rank tools against a natural-language query with BM25
+    ///
+    /// Returns at most `limit` tools with a positive score, best first.
+    pub fn bm25(&self, query: &str, limit: usize) -> Vec<ToolMatch> {
+        let query = tokenize(query);
+        let count = self.tools.len() as f64;
+        let average_len = self.total_len as f64 / count.max(1.0);
+
+        let mut matches: Vec<ToolMatch> = self
+            .tools
+            .iter()
+            .map(|tool| {
+                let score = query
+                    .iter()
+                    .map(|term| {
+                        let tf = *tool.terms.get(term).unwrap_or(&0) as f64;
+                        if tf == 0.0 {
+                            return 0.0;
+                        }
+                        let df = self.document_frequency[term] as f64;
+                        let idf = (1.0 + (count - df + 0.5) / (df + 0.5)).ln();
+                        let norm = K1 * (1.0 - B + B * tool.len as f64 / average_len);
+                        idf * tf * (K1 + 1.0) / (tf + norm)
+                    })
+                    .sum();
+                ToolMatch {
+                    name: tool.definition.name.clone(),
+                    score,
+                }
+            })
+            .filter(|m| m.score > 0.0)
+            .collect();
+
+        // Stable sort keeps insertion order for ties
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Find tools whose name, description or parameters match a regex
+    ///
+    /// Matching is case-insensitive. Name matches rank above the rest.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRequest`] if the pattern is invalid or longer
+    /// than 200 characters.
+    pub fn regex(&self, pattern: &str, limit: usize) -> Result<Vec<ToolMatch>> {
+        if pattern.len() > MAX_PATTERN_LEN {
+            return Err(Error::InvalidRequest(format!(
+                "Search pattern is longer than {} characters",
+                MAX_PATTERN_LEN
+            )));
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| Error::InvalidRequest(format!("Invalid search pattern: {}", e)))?;
+
+        let mut matches: Vec<ToolMatch> = self
+            .tools
+            .iter()
+            .filter_map(|tool| {
+                let score = if regex.is_match(&tool.definition.name) {
+                    2.0
+                } else if regex.is_match(&tool.text) {
+                    1.0
+                } else {
+                    return None;
+                };
+                Some(ToolMatch {
+                    name: tool.definition.name.clone(),
+                    score,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+}
+
+impl std::fmt::Debug for ToolIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolIndex")
+            .field(
+                "tools",
+                &self.definitions().map(|t| &t.name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Description, parameter names and parameter descriptions, one per line
+fn searchable_text(tool: &CustomTool) -> String {
+    fn walk(schema: &Value, out: &mut Vec<String>) {
+        if let Some(description) = schema.get("description").and_then(Value::as_str) {
+            out.push(description.to_string());
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (name, property) in properties {
+                out.push(name.clone());
+                walk(property, out);
+            }
+        }
+        if let Some(items) = schema.get("items") {
+            walk(items, out);
+        }
+    }
+
+    let mut parts = vec![tool.name.clone(), tool.description.clone()];
+    walk(&tool.input_schema, &mut parts);
+    parts.join("\n")
+}
+
+/// Lowercase words, splitting `snake_case`, `kebab-case` and `camelCase`
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+
+    for c in text.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// How search matches reach Claude
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Natural-language query ranked with BM25
+    Bm25,
+    /// Case-insensitive regex over names, descriptions and parameters
+    Regex,
+}
+
+/// How matched tools are made available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDelivery {
+    /// Answer with `tool_reference` blocks; the catalog is sent deferred
+    References,
+    /// Answer with text and add the matched definitions to later requests
+    Inject,
+}
+
+/// Tool search exposed to Claude as a custom tool
+///
+/// Clones share the index and the set of loaded tools, so one copy can be
+/// registered as a tool while another rewrites requests.
+#[derive(Debug, Clone)]
+pub struct ToolSearch {
+    index: Arc<ToolIndex>,
+    name: String,
+    mode: SearchMode,
+    delivery: SearchDelivery,
+    limit: usize,
+    loaded: Arc<Mutex<Vec<String>>>,
+}
+
+impl ToolSearch {
+    /// BM25 search named `tool_search`, answering with `tool_reference` blocks
+    pub fn new(index: ToolIndex) -> Self {
+        Self {
+            index: Arc::new(index),
+            name: "tool_search".into(),
+            mode: SearchMode::Bm25,
+            delivery: SearchDelivery::References,
+            limit: DEFAULT_SEARCH_LIMIT,
+            loaded: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Set the tool name Claude sees
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the search mode
+    pub fn with_mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Search with regex patterns instead of BM25
+    pub fn with_regex(self) -> Self {
+        self.with_mode(SearchMode::Regex)
+    }
+
+    /// Set how many tools a search returns at most
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// Set how matched tools are delivered
+    pub fn with_delivery(mut self, delivery: SearchDelivery) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// Add matched definitions to later requests instead of returning references
+    ///
+    /// Works with any backend, including Bedrock.
+    pub fn injecting(self) -> Self {
+        self.with_delivery(SearchDelivery::Inject)
+    }
+
+    /// The searched index
+    pub fn index(&self) -> &ToolIndex {
+        &self.index
+    }
+
+    /// Names of the tools found so far, in discovery order
+    pub fn loaded(&self) -> Vec<String> {
+        self.loaded
+            .lock()
+            .expect("tool search lock poisoned")
+            .clone()
+    }
+
+    /// Run a search as Claude would
+    pub fn search(&self, query: &str) -> Result<Vec<ToolMatch>> {
+        match self.mode {
+            SearchMode::Bm25 => Ok(self.index.bm25(query, self.limit)),
+            SearchMode::Regex => self.index.regex(query, self.limit),
+        }
+    }
+
+    /// Tool definitions for a request: the search tool plus the catalog
+    ///
+    /// With [`SearchDelivery::References`] the whole catalog is included
+    /// with `defer_loading`; with [`SearchDelivery::Inject`] only the tools
+    /// found so far are.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let mut definitions = vec![ToolDefinition::Custom(self.definition())];
+        match self.delivery {
+            SearchDelivery::References => {
+                definitions.extend(self.index.definitions().map(|tool| {
+                    let mut tool = tool.clone();
+                    tool.defer_loading = Some(true);
+                    ToolDefinition::Custom(tool)
+                }));
+            }
+            SearchDelivery::Inject => {
+                let loaded = self.loaded.lock().expect("tool search lock poisoned");
+                definitions.extend(
+                    loaded
+                        .iter()
+                        .filter_map(|name| self.index.get(name))
+                        .map(|tool| ToolDefinition::Custom(tool.clone())),
+                );
+            }
+        }
+        definitions
+    }
+
+    /// Replace the catalog tools in a request with [`definitions`](Self::definitions)
+    ///
+    /// Tools outside the catalog are kept. Suitable as an
+    /// [`Agent::with_request_hook`](crate::agent::Agent::with_request_hook).
+    pub fn apply(&self, mut request: MessagesRequest) -> MessagesRequest {
+        let mut tools: Vec<ToolDefinition> = request
+            .tools
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|definition| match definition {
+                ToolDefinition::Custom(tool) => {
+                    tool.name != self.name && self.index.get(&tool.name).is_none()
+                }
+                ToolDefinition::Server(_) => true,
+            })
+            .collect();
+        tools.extend(self.definitions());
+        request.tools = Some(tools);
+        request
+    }
+}
+
+#[async_trait]
+impl Tool for ToolSearch {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        match self.mode {
+            SearchMode::Bm25 => {
+                "Search the available tools by describing what you need in natural language. \
+                 Matching tools become available to call."
+            }
+            SearchMode::Regex => {
+                "Search the available tools with a case-insensitive regular expression over \
+                 tool names, descriptions and parameters. Matching tools become available to call."
+            }
+        }
+    }
+
+    fn input_schema(&self) -> Value {
+        let query = match self.mode {
+            SearchMode::Bm25 => "What the tool should do",
+            SearchMode::Regex => "Regular expression, at most 200 characters",
+        };
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": query}
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+        let Some(query) = input.get("query").and_then(Value::as_str) else {
+            return ToolOutput::error("Missing required string parameter `query`");
+        };
+        let matches = match self.search(query) {
+            Ok(matches) => matches,
+            Err(e) => return ToolOutput::error(e.to_string()),
+        };
+        debug!("Tool search {:?} matched {} tools", query, matches.len());
+        if matches.is_empty() {
+            return ToolOutput::text(format!("No tools matched `{}`", query));
+        }
+
+        {
+            let mut loaded = self.loaded.lock().expect("tool search lock poisoned");
+            for m in &matches {
+                if !loaded.contains(&m.name) {
+                    loaded.push(m.name.clone());
+                }
+            }
+        }
+
+        match self.delivery {
+            SearchDelivery::References => ToolOutput::blocks(
+                matches
+                    .into_iter()
+                    .map(|m| ContentBlock::ToolReference { tool_name: m.name })
+                    .collect(),
+            ),
+            SearchDelivery::Inject => {
+                let lines: Vec<String> = matches
+                    .iter()
+                    .filter_map(|m| self.index.get(&m.name))
+                    .map(|tool| format!("- {}: {}", tool.name, tool.description))
+                    .collect();
+                ToolOutput::text(format!(
+                    "These tools are now available:\n{}",
+                    lines.join("\n")
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Message, ToolResultContent};
+
+    fn catalog() -> ToolIndex {
+        ToolIndex::new([
+            CustomTool::new(
+                "get_weather",
+                "Get the current weather for a city",
+                json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string", "description": "City name"}}
+                }),
+            ),
+            CustomTool::new(
+                "createTicket",
+                "Open a ticket in the issue tracker",
+                json!({
+                    "type": "object",
+                    "properties": {"priority": {"type": "string", "description": "Urgency"}}
+                }),
+            ),
+            CustomTool::new(
+                "send_email",
+                "Send an email to a recipient",
+                json!({"type": "object", "properties": {"to": {"type": "string"}}}),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("createTicket get_weather HTTPClient v2"),
+            ["create", "ticket", "get", "weather", "httpclient", "v2"]
+        );
+    }
+
+    #[test]
+    fn test_bm25_ranking() {
+        let index = catalog();
+
+        let matches = index.bm25("weather in a city", 5);
+        assert_eq!(matches[0].name, "get_weather");
+        // Parameter descriptions are indexed too
+        assert_eq!(index.bm25("urgency", 5)[0].name, "createTicket");
+        assert_eq!(index.bm25("ticket", 5)[0].name, "createTicket");
+        assert!(index.bm25("spreadsheet", 5).is_empty());
+        assert_eq!(index.bm25("send a ticket email", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_name_matches_outrank_description_matches() {
+        let index = ToolIndex::new([
+            CustomTool::new("cleanup", "Archive old records", json!({"type": "object"})),
+            CustomTool::new(
+                "archive_notes",
+                "Store old records",
+                json!({"type": "object"}),
+            ),
+        ]);
+
+        let matches = index.bm25("archive", 5);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].name, "archive_notes");
+        assert!(matches[0].score > matches[1].score);
+    }
+
+    #[test]
+    fn test_regex_search() {
+        let index = catalog();
+
+        let names: Vec<String> = index
+            .regex("email|weather", 5)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(names, ["get_weather", "send_email"]);
+        // Case-insensitive, name matches first
+        assert_eq!(index.regex("TICKET", 5).unwrap()[0].name, "createTicket");
+        assert!(index.regex("(", 5).is_err());
+        assert!(index.regex(&"a".repeat(201), 5).is_err());
+    }
+
+    #[test]
+    fn test_add_replaces_tool() {
+        let mut index = catalog();
+        index.add(CustomTool::new(
+            "get_weather",
+            "Look up stock prices",
+            json!({"type": "object"}),
+        ));
+
+        assert_eq!(index.len(), 3);
+        assert!(index.bm25("city", 5).is_empty());
+        assert_eq!(index.bm25("stock prices", 5)[0].name, "get_weather");
+    }
+
+    #[tokio::test]
+    async fn test_references_delivery() {
+        let search = ToolSearch::new(catalog());
+        let ctx = ToolContext::new("toolu_1", "tool_search");
+        let output = search.call(json!({"query": "weather"}), &ctx).await;
+
+        match output.content {
+            ToolResultContent::Blocks(blocks) => match &blocks[..] {
+                [ContentBlock::ToolReference { tool_name }] => assert_eq!(tool_name, "get_weather"),
+                other => panic!("Expected one tool_reference, got {:?}", other),
+            },
+            other => panic!("Expected blocks, got {:?}", other),
+        }
+
+        // The whole catalog is sent deferred, next to the search tool
+        let definitions = search.definitions();
+        assert_eq!(definitions.len(), 4);
+        let json = serde_json::to_value(&definitions).unwrap();
+        assert_eq!(json[0]["name"], "tool_search");
+        assert!(json[0].get("defer_loading").is_none());
+        assert_eq!(json[1]["defer_loading"], true);
+    }
+
+    #[tokio::test]
+    async fn test_inject_delivery() {
+        let search = ToolSearch::new(catalog()).injecting().with_regex();
+        let request = MessagesRequest::new(
+            "claude-sonnet-4-5-20250929",
+            1024,
+            vec![Message::user("Hi")],
+        )
+        .with_tools(vec![CustomTool::new(
+            "local",
+            "Always loaded",
+            json!({"type": "object"}),
+        )
+        .into()]);
+
+        let before = search.apply(request.clone());
+        assert_eq!(before.tools.as_ref().unwrap().len(), 2);
+
+        let ctx = ToolContext::new("toolu_1", "tool_search");
+        let output = search.call(json!({"query": "^send_"}), &ctx).await;
+        assert!(!output.is_error);
+        assert_eq!(search.loaded(), ["send_email"]);
+
+        let after = search.apply(before);
+        let names: Vec<String> = serde_json::to_value(after.tools.unwrap())
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["local", "tool_search", "send_email"]);
+
+        let output = search.call(json!({"query": "("}), &ctx).await;
+        assert!(output.is_error);
+        let output = search.call(json!({}), &ctx).await;
+        assert!(output.is_error);
+    }
+}
@@ -0,0 +1,1316 @@
+//! Executable tools and automatic `tool_use` dispatch
+//!
+//! [`CustomTool`] only describes a tool's schema. This module adds the
+//! [`Tool`] trait, which pairs that schema with an async implementation,
+//! and [`ToolRegistry`], which advertises registered tools to Claude and
+//! runs every `tool_use` block in a response.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use claude_sdk::tools::{Tool, ToolContext, ToolOutput, ToolRegistry};
+//! use claude_sdk::{ClaudeClient, ConversationBuilder, StopReason};
+//! use serde_json::{json, Value};
+//!
+//! struct GetWeather;
+//!
+//! #[claude_sdk::tools::async_trait]
+//! impl Tool for GetWeather {
+//!     fn name(&self) -> &str {
+//!         "get_weather"
+//!     }
+//!
+//!     fn description(&self) -> &str {
+//!         "Get the current weather for a city"
+//!     }
+//!
+//!     fn input_schema(&self) -> Value {
+//!         json!({
+//!             "type": "object",
+//!             "properties": {"city": {"type": "string"}},
+//!             "required": ["city"]
+//!         })
+//!     }
+//!
+//!     async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+//!         match input["city"].as_str() {
+//!             Some(city) => ToolOutput::text(format!("Sunny in {}", city)),
+//!             None => ToolOutput::error("missing city"),
+//!         }
+//!     }
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ClaudeClient::anthropic(std::env::var("ANTHROPIC_API_KEY")?);
+//! let registry = ToolRegistry::new().with_tool(GetWeather);
+//!
+//! let mut conversation = ConversationBuilder::new().with_tools(registry.definitions());
+//! conversation.add_user_message("What's the weather in Paris?");
+//!
+//! let response = client
+//!     .send_message(conversation.build("claude-sonnet-4-5-20250929", 1024))
+//!     .await?;
+//!
+//! if response.stop_reason == Some(StopReason::ToolUse) {
+//!     conversation.add_assistant_with_blocks(response.content.clone());
+//!     conversation.add_tool_results(registry.execute(&response).await);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Defining Tools with `#[tool]`
+//!
+//! With the `macros` feature, [`tool`](crate::tool) generates the `Tool`
+//! impl from a function, keeping the schema in sync with the handler:
+//!
+//! ```rust,ignore
+//! use claude_sdk::{tool, ToolRegistry};
+//!
+//! /// Get the current weather for a city
+//! ///
+//! /// # Arguments
+//! /// * `city` - City name, e.g. "Paris"
+//! #[tool]
+//! async fn get_weather(city: String) -> Result<String, String> {
+//!     Ok(format!("Sunny in {}", city))
+//! }
+//!
+//! let registry = ToolRegistry::new().with_tool(GetWeather);
+//! ```
+
+use crate::error::Error;
+use crate::output_policy::OutputPolicy;
+use crate::permissions::{PermissionPolicy, Verdict};
+use crate::types::{
+    CitationConfig, ContentBlock, CustomTool, DocumentSource, ImageSource, MessagesResponse,
+    TextBlock, ToolDefinition, ToolResultContent,
+};
+use base64::Engine;
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Re-exported so implementors don't need their own `async-trait` dependency
+pub use async_trait::async_trait;
+
+/// An executable tool
+///
+/// Implementations report failures by returning [`ToolOutput::error`];
+/// the error text is sent back to Claude with `is_error: true` so it can
+/// adjust and retry.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// Tool name, unique within a registry
+    fn name(&self) -> &str;
+
+    /// Description shown to Claude
+    fn description(&self) -> &str;
+
+    /// JSON schema for the tool input
+    fn input_schema(&self) -> Value;
+
+    /// Definition sent with the request
+    ///
+    /// Override to set options such as `strict` or `input_examples`.
+    fn definition(&self) -> CustomTool {
+        CustomTool::new(self.name(), self.description(), self.input_schema())
+    }
+
+    /// Definition advertised by [`ToolRegistry::definitions`]
+    ///
+    /// Defaults to [`definition`](Self::definition). Override for
+    /// Anthropic-defined client tools such as `bash`, whose definition is a
+    /// versioned tool type rather than a schema.
+    fn tool_definition(&self) -> ToolDefinition {
+        ToolDefinition::Custom(self.definition())
+    }
+
+    /// Run the tool with the input Claude provided
+    async fn call(&self, input: Value, ctx: &ToolContext) -> ToolOutput;
+}
+
+/// Information about the tool call being executed
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ToolContext {
+    /// ID of the `tool_use` block being answered
+    pub tool_use_id: String,
+
+    /// Name of the tool Claude called
+    pub tool_name: String,
+}
+
+impl ToolContext {
+    /// Create a context for one tool call
+    pub fn new(tool_use_id: impl Into<String>, tool_name: impl Into<String>) -> Self {
+        Self {
+            tool_use_id: tool_use_id.into(),
+            tool_name: tool_name.into(),
+        }
+    }
+}
+
+/// Result of a tool call
+///
+/// Constructors cover text, JSON, images, documents and search results;
+/// [`with`](Self::with) combines them into one result.
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::tools::{SearchResult, ToolOutput};
+/// use serde_json::json;
+///
+/// let output = ToolOutput::text("Top matches:")
+///     .with(ToolOutput::search_result(
+///         SearchResult::new("https://docs.example.com/auth", "Authentication")
+///             .with_text("Tokens expire after one hour.")
+///             .with_citations(),
+///     ))
+///     .with(ToolOutput::json_pretty(&json!({"total": 1})));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ToolOutput {
+    /// Content returned to Claude
+    pub content: ToolResultContent,
+
+    /// Whether the call failed
+    pub is_error: bool,
+}
+
+impl ToolOutput {
+    /// An empty successful result, to be extended with [`with`](Self::with)
+    pub fn new() -> Self {
+        Self::blocks(Vec::new())
+    }
+
+    /// A successful plain-text result
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: ToolResultContent::Text(text.into()),
+            is_error: false,
+        }
+    }
+
+    /// A successful result serialized as compact JSON text
+    pub fn json(value: &Value) -> Self {
+        Self::text(value.to_string())
+    }
+
+    /// A successful result serialized as indented JSON text
+    pub fn json_pretty(value: &Value) -> Self {
+        Self::text(serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()))
+    }
+
+    /// An image from raw bytes with a known media type
+    pub fn image(media_type: impl Into<String>, bytes: impl AsRef<[u8]>) -> Self {
+        Self::block(ContentBlock::Image {
+            source: ImageSource::Base64 {
+                media_type: media_type.into(),
+                data: base64::engine::general_purpose::STANDARD.encode(bytes),
+            },
+            cache_control: None,
+        })
+    }
+
+    /// An image from raw bytes, detecting PNG, JPEG, GIF or WebP
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRequest`] if the format is not recognized.
+    pub fn image_bytes(bytes: impl AsRef<[u8]>) -> crate::Result<Self> {
+        let bytes = bytes.as_ref();
+        let media_type = image_media_type(bytes).ok_or_else(|| {
+            Error::InvalidRequest(
+                "Unsupported image format (expected PNG, JPEG, GIF or WebP)".into(),
+            )
+        })?;
+        Ok(Self::image(media_type, bytes))
+    }
+
+    /// An image read from a file, detecting its format from the contents
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRequest`] if the file cannot be read or is not
+    /// a PNG, JPEG, GIF or WebP image.
+    pub async fn image_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::image_bytes(read_file(path.as_ref()).await?)
+    }
+
+    /// An image Claude fetches from a URL
+    pub fn image_url(url: impl Into<String>) -> Self {
+        Self::block(ContentBlock::Image {
+            source: ImageSource::Url { url: url.into() },
+            cache_control: None,
+        })
+    }
+
+    /// A PDF document from raw bytes
+    pub fn pdf(bytes: impl AsRef<[u8]>) -> Self {
+        Self::document(DocumentSource::Base64 {
+            media_type: "application/pdf".into(),
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        })
+    }
+
+    /// A document read from a file: PDFs are sent as PDFs, anything else as UTF-8 text
+    ///
+    /// The file name becomes the document title.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRequest`] if the file cannot be read, or is
+    /// neither a PDF nor valid UTF-8.
+    pub async fn document_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let bytes = read_file(path).await?;
+        let output = if bytes.starts_with(b"%PDF-") {
+            Self::pdf(bytes)
+        } else {
+            let text = String::from_utf8(bytes).map_err(|_| {
+                Error::InvalidRequest(format!(
+                    "{} is neither a PDF nor UTF-8 text",
+                    path.display()
+                ))
+            })?;
+            Self::document_text(text)
+        };
+        Ok(match path.file_name() {
+            Some(name) => output.with_title(name.to_string_lossy()),
+            None => output,
+        })
+    }
+
+    /// A plain-text document
+    pub fn document_text(text: impl Into<String>) -> Self {
+        Self::document(DocumentSource::Text {
+            media_type: "text/plain".into(),
+            data: text.into(),
+        })
+    }
+
+    /// A PDF document Claude fetches from a URL
+    pub fn document_url(url: impl Into<String>) -> Self {
+        Self::document(DocumentSource::Url { url: url.into() })
+    }
+
+    /// A search result Claude can cite
+    pub fn search_result(result: SearchResult) -> Self {
+        Self::block(result.into_block())
+    }
+
+    /// Several search results, in ranking order
+    pub fn search_results(results: impl IntoIterator<Item = SearchResult>) -> Self {
+        Self::blocks(results.into_iter().map(SearchResult::into_block).collect())
+    }
+
+    fn block(block: ContentBlock) -> Self {
+        Self::blocks(vec![block])
+    }
+
+    fn document(source: DocumentSource) -> Self {
+        Self::block(ContentBlock::Document {
+            source,
+            title: None,
+            context: None,
+            citations: None,
+            cache_control: None,
+        })
+    }
+
+    /// Set the title of every document in this output
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        let title = title.into();
+        if let ToolResultContent::Blocks(blocks) = &mut self.content {
+            for block in blocks {
+                if let ContentBlock::Document { title: t, .. } = block {
+                    *t = Some(title.clone());
+                }
+            }
+        }
+        self
+    }
+
+    /// Append another output's content
+    ///
+    /// The result is an error if either part is.
+    pub fn with(mut self, mut other: ToolOutput) -> Self {
+        let mut blocks = self.take_blocks();
+        blocks.extend(other.take_blocks());
+        self.content = ToolResultContent::Blocks(blocks);
+        self.is_error |= other.is_error;
+        self
+    }
+
+    /// Append one content block
+    pub fn with_block(self, block: ContentBlock) -> Self {
+        self.with(Self::block(block))
+    }
+
+    fn take_blocks(&mut self) -> Vec<ContentBlock> {
+        match std::mem::replace(&mut self.content, ToolResultContent::Blocks(Vec::new())) {
+            ToolResultContent::Text(text) => vec![ContentBlock::Text {
+                text,
+                cache_control: None,
+                citations: None,
+            }],
+            ToolResultContent::Blocks(blocks) => blocks,
+        }
+    }
+
+    /// A successful result made of content blocks (text, images, documents)
+    pub fn blocks(blocks: Vec<ContentBlock>) -> Self {
+        Self {
+            content: ToolResultContent::Blocks(blocks),
+            is_error: false,
+        }
+    }
+
+    /// A failed call; the message is shown to Claude
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            content: ToolResultContent::Text(message.into()),
+            is_error: true,
+        }
+    }
+
+    /// A failed call described by JSON, e.g. a structured error payload
+    pub fn error_json(value: &Value) -> Self {
+        Self::json(value).into_error()
+    }
+
+    /// Mark this output as a failed call, keeping its content
+    ///
+    /// Useful for errors that carry more than text, such as a screenshot of
+    /// the failure.
+    pub fn into_error(mut self) -> Self {
+        self.is_error = true;
+        self
+    }
+
+    /// Convert into the `tool_result` block answering `tool_use_id`
+    pub fn into_block(self, tool_use_id: impl Into<String>) -> ContentBlock {
+        ContentBlock::ToolResult {
+            tool_use_id: tool_use_id.into(),
+            content: Some(self.content),
+            is_error: self.is_error.then_some(true),
+        }
+    }
+}
+
+impl Default for ToolOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ToolOutput> for ToolResultContent {
+    fn from(output: ToolOutput) -> Self {
+        output.content
+    }
+}
+
+/// Media type of PNG, JPEG, GIF or WebP image bytes
+pub fn image_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+async fn read_file(path: &Path) -> crate::Result<Vec<u8>> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| Error::InvalidRequest(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+/// A search result for [`ToolOutput::search_result`]
+///
+/// Lets Claude cite retrieved passages (RAG) the same way it cites web
+/// search results.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    source: String,
+    title: String,
+    content: Vec<TextBlock>,
+    citations: bool,
+}
+
+impl SearchResult {
+    /// A result from `source` (a URL or document id) titled `title`
+    pub fn new(source: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            title: title.into(),
+            content: Vec::new(),
+            citations: false,
+        }
+    }
+
+    /// Append a passage
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.content.push(TextBlock::new(text));
+        self
+    }
+
+    /// Enable citations for this result
+    pub fn with_citations(mut self) -> Self {
+        self.citations = true;
+        self
+    }
+
+    /// Convert into a `search_result` content block
+    pub fn into_block(self) -> ContentBlock {
+        ContentBlock::SearchResult {
+            source: self.source,
+            title: self.title,
+            content: self.content,
+            citations: self.citations.then_some(CitationConfig { enabled: true }),
+            cache_control: None,
+        }
+    }
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<&str> for ToolOutput {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+/// A set of tools that can be advertised to Claude and executed
+///
+/// Tools are kept in registration order; registering a second tool with
+/// the same name replaces the first.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<dyn Tool>>,
+    index: HashMap<String, usize>,
+    permissions: Option<Arc<PermissionPolicy>>,
+    output_policy: Option<Arc<OutputPolicy>>,
+    /// Compiled input schemas, present once validation is enabled
+    #[cfg(feature = "validation")]
+    validators: Option<HashMap<String, Arc<crate::validation::SchemaValidator>>>,
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool
+    pub fn with_tool(mut self, tool: impl Tool + 'static) -> Self {
+        self.register(Arc::new(tool));
+        self
+    }
+
+    /// Check every call against a permission policy before running it
+    ///
+    /// Denied calls are answered with an `is_error` result carrying the
+    /// reason. See the [`permissions`](crate::permissions) module.
+    pub fn with_permissions(mut self, policy: PermissionPolicy) -> Self {
+        self.permissions = Some(Arc::new(policy));
+        self
+    }
+
+    /// Limit the size of every tool result
+    ///
+    /// Results over the policy's token limit are truncated, summarized or
+    /// uploaded. See the [`output_policy`](crate::output_policy) module.
+    pub fn with_output_policy(mut self, policy: OutputPolicy) -> Self {
+        self.output_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Validate tool inputs against each tool's `input_schema` before calling it
+    ///
+    /// Inputs that do not match are answered with an `is_error` result
+    /// listing every violation by path, and the tool is not called. Tools
+    /// whose schema fails to compile are called without validation.
+    /// Requires the `validation` feature.
+    #[cfg(feature = "validation")]
+    pub fn with_input_validation(mut self) -> Self {
+        let mut validators = HashMap::new();
+        for tool in &self.tools {
+            if let Some(validator) = compile_input_schema(tool.as_ref()) {
+                validators.insert(tool.name().to_string(), validator);
+            }
+        }
+        self.validators = Some(validators);
+        self
+    }
+
+    /// Add a shared tool
+    pub fn register(&mut self, tool: Arc<dyn Tool>) -> &mut Self {
+        let name = tool.name().to_string();
+        #[cfg(feature = "validation")]
+        if let Some(validators) = &mut self.validators {
+            match compile_input_schema(tool.as_ref()) {
+                Some(validator) => validators.insert(name.clone(), validator),
+                None => validators.remove(&name),
+            };
+        }
+        match self.index.get(&name) {
+            Some(&i) => self.tools[i] = tool,
+            None => {
+                self.index.insert(name, self.tools.len());
+                self.tools.push(tool);
+            }
+        }
+        self
+    }
+
+    /// Add every tool from `other`, replacing tools with the same name
+    ///
+    /// `other`'s permission and output policies, and input validation, are
+    /// adopted only where this registry has none of its own.
+    pub fn merge(&mut self, other: &ToolRegistry) -> &mut Self {
+        for tool in &other.tools {
+            self.register(tool.clone());
+        }
+        if self.permissions.is_none() {
+            self.permissions = other.permissions.clone();
+        }
+        if self.output_policy.is_none() {
+            self.output_policy = other.output_policy.clone();
+        }
+        #[cfg(feature = "validation")]
+        if self.validators.is_none() && other.validators.is_some() {
+            *self = std::mem::take(self).with_input_validation();
+        }
+        self
+    }
+
+    /// Look up a tool by name
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.index.get(name).map(|&i| &self.tools[i])
+    }
+
+    /// Names of the registered tools, in registration order
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(|t| t.name()).collect()
+    }
+
+    /// Number of registered tools
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Whether the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Tool definitions for [`MessagesRequest::with_tools`](crate::MessagesRequest::with_tools)
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.iter().map(|t| t.tool_definition()).collect()
+    }
+
+    /// Call one tool by name
+    ///
+    /// Unknown tools, denied calls and panicking tools or approvers produce
+    /// an error output rather than failing the whole turn.
+    pub async fn call(&self, tool_use_id: &str, name: &str, input: Value) -> ToolOutput {
+        match self.authorize(tool_use_id, name, input).await {
+            Ok(input) => {
+                let output = self.call_authorized(tool_use_id, name, input).await;
+                self.finish_output(tool_use_id, name, output).await
+            }
+            Err(output) => output,
+        }
+    }
+
+    /// Check a call against the permission policy, waiting for the approver
+    ///
+    /// Returns the input to run the tool with (the approver may have edited
+    /// it), or the error output answering the call.
+    pub(crate) async fn authorize(
+        &self,
+        tool_use_id: &str,
+        name: &str,
+        input: Value,
+    ) -> Result<Value, ToolOutput> {
+        if self.get(name).is_none() {
+            warn!("Claude called unknown tool: {}", name);
+            return Err(ToolOutput::error(format!("Unknown tool: {}", name)));
+        }
+        let Some(policy) = &self.permissions else {
+            return Ok(input);
+        };
+        // Approvers are user code, so a panic is contained like a tool's
+        let verdict = match AssertUnwindSafe(policy.check(tool_use_id, name, input))
+            .catch_unwind()
+            .await
+        {
+            Ok(verdict) => verdict,
+            Err(_) => {
+                warn!("Permission check for tool {} panicked", name);
+                return Err(ToolOutput::error(format!(
+                    "Permission check for tool '{}' panicked",
+                    name
+                )));
+            }
+        };
+        match verdict {
+            Verdict::Allowed(input) => Ok(input),
+            Verdict::Denied(reason) => Err(ToolOutput::error(format!(
+                "Permission denied for tool '{}': {}",
+                name, reason
+            ))),
+        }
+    }
+
+    /// Run a call that [`authorize`](Self::authorize) allowed
+    ///
+    /// The output policy is not applied; see
+    /// [`finish_output`](Self::finish_output).
+    pub(crate) async fn call_authorized(
+        &self,
+        tool_use_id: &str,
+        name: &str,
+        input: Value,
+    ) -> ToolOutput {
+        let Some(tool) = self.get(name) else {
+            return ToolOutput::error(format!("Unknown tool: {}", name));
+        };
+
+        #[cfg(feature = "validation")]
+        if let Some(validator) = self.validators.as_ref().and_then(|v| v.get(name)) {
+            if let Err(violations) = validator.validate(&input) {
+                debug!("Rejected input for tool {} ({})", name, tool_use_id);
+                return ToolOutput::error(format!(
+                    "Invalid input for tool '{}':\n{}",
+                    name,
+                    crate::validation::describe(&violations)
+                ));
+            }
+        }
+
+        debug!("Executing tool {} ({})", name, tool_use_id);
+        let ctx = ToolContext::new(tool_use_id, name);
+        match AssertUnwindSafe(tool.call(input, &ctx))
+            .catch_unwind()
+            .await
+        {
+            Ok(output) => output,
+            Err(_) => {
+                warn!("Tool {} panicked", name);
+                ToolOutput::error(format!("Tool '{}' panicked", name))
+            }
+        }
+    }
+
+    /// Apply the output policy to a call's output
+    ///
+    /// Summarizing or uploading an oversized output calls the API, so
+    /// [`ToolExecutor`](crate::ToolExecutor) runs this outside the tool's
+    /// timeout and concurrency limit.
+    pub(crate) async fn finish_output(
+        &self,
+        tool_use_id: &str,
+        name: &str,
+        output: ToolOutput,
+    ) -> ToolOutput {
+        match &self.output_policy {
+            Some(policy) => {
+                policy
+                    .apply(&ToolContext::new(tool_use_id, name), output)
+                    .await
+            }
+            None => output,
+        }
+    }
+
+    /// Execute every `tool_use` block in a response
+    ///
+    /// Returns one `tool_result` block per `tool_use`, in the same order,
+    /// ready for [`ConversationBuilder::add_tool_results`](crate::ConversationBuilder::add_tool_results).
+    pub async fn execute(&self, response: &MessagesResponse) -> Vec<ContentBlock> {
+        let mut results = Vec::new();
+        for block in &response.content {
+            if let ContentBlock::ToolUse {
+                id, name, input, ..
+            } = block
+            {
+                let output = self.call(id, name, input.clone()).await;
+                results.push(output.into_block(id.clone()));
+            }
+        }
+        results
+    }
+}
+
+#[cfg(feature = "validation")]
+fn compile_input_schema(tool: &dyn Tool) -> Option<Arc<crate::validation::SchemaValidator>> {
+    match crate::validation::SchemaValidator::new(&tool.input_schema()) {
+        Ok(validator) => Some(Arc::new(validator)),
+        Err(e) => {
+            warn!("Not validating input for tool {}: {}", tool.name(), e);
+            None
+        }
+    }
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.names())
+            .finish()
+    }
+}
+
+/// Rust types that can appear as `#[tool]` function arguments
+///
+/// Provides the JSON schema for the argument. Implemented for strings,
+/// numbers, booleans, `serde_json::Value`, `Vec<T>`, string-keyed maps and
+/// `Option<T>` (which makes the argument optional). Implement it for your
+/// own types to use them as arguments.
+///
+/// # Example
+///
+/// ```rust
+/// use claude_sdk::tools::ToolParam;
+/// use serde_json::{json, Value};
+///
+/// #[derive(serde::Deserialize)]
+/// enum Unit {
+///     Celsius,
+///     Fahrenheit,
+/// }
+///
+/// impl ToolParam for Unit {
+///     fn schema() -> Value {
+///         json!({"type": "string", "enum": ["Celsius", "Fahrenheit"]})
+///     }
+/// }
+/// ```
+pub trait ToolParam {
+    /// JSON schema for this type
+    fn schema() -> Value;
+
+    /// Whether the argument must be present
+    const REQUIRED: bool = true;
+}
+
+macro_rules! tool_param {
+    ($schema:tt => $($ty:ty),+) => {
+        $(
+            impl ToolParam for $ty {
+                fn schema() -> Value {
+                    serde_json::json!($schema)
+                }
+            }
+        )+
+    };
+}
+
+tool_param!({"type": "string"} => String, std::path::PathBuf, char);
+tool_param!({"type": "boolean"} => bool);
+tool_param!({"type": "integer"} => i8, i16, i32, i64, isize);
+tool_param!({"type": "integer", "minimum": 0} => u8, u16, u32, u64, usize);
+tool_param!({"type": "number"} => f32, f64);
+tool_param!({} => Value);
+
+impl<T: ToolParam> ToolParam for Option<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    const REQUIRED: bool = false;
+}
+
+impl<T: ToolParam> ToolParam for Vec<T> {
+    fn schema() -> Value {
+        serde_json::json!({"type": "array", "items": T::schema()})
+    }
+}
+
+impl<T: ToolParam> ToolParam for HashMap<String, T> {
+    fn schema() -> Value {
+        serde_json::json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+impl<T: ToolParam> ToolParam for std::collections::BTreeMap<String, T> {
+    fn schema() -> Value {
+        serde_json::json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+/// Support code for `#[tool]`; not public API
+#[doc(hidden)]
+pub mod __private {
+    use super::ToolOutput;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::Value;
+
+    pub use serde_json;
+
+    /// Deserialize one argument from the tool input
+    pub fn argument<T: DeserializeOwned>(input: &Value, name: &str) -> Result<T, ToolOutput> {
+        let value = input.get(name).cloned().unwrap_or(Value::Null);
+        serde_json::from_value(value)
+            .map_err(|e| ToolOutput::error(format!("Invalid argument `{}`: {}", name, e)))
+    }
+
+    /// Serialize a return value; strings are sent as-is, anything else as JSON
+    pub fn serialize_output<T: Serialize + ?Sized>(value: &T) -> ToolOutput {
+        match serde_json::to_value(value) {
+            Ok(Value::String(text)) => ToolOutput::text(text),
+            Ok(value) => ToolOutput::json(&value),
+            Err(e) => ToolOutput::error(format!("Failed to serialize tool output: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Add;
+
+    #[async_trait]
+    impl Tool for Add {
+        fn name(&self) -> &str {
+            "add"
+        }
+
+        fn description(&self) -> &str {
+            "Add two numbers"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": {"a": {"type": "number"}, "b": {"type": "number"}},
+                "required": ["a", "b"]
+            })
+        }
+
+        async fn call(&self, input: Value, _ctx: &ToolContext) -> ToolOutput {
+            match (input["a"].as_f64(), input["b"].as_f64()) {
+                (Some(a), Some(b)) => ToolOutput::text((a + b).to_string()),
+                _ => ToolOutput::error("a and b must be numbers"),
+            }
+        }
+    }
+
+    struct Panics;
+
+    #[async_trait]
+    impl Tool for Panics {
+        fn name(&self) -> &str {
+            "panics"
+        }
+
+        fn description(&self) -> &str {
+            "Always panics"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn call(&self, _input: Value, _ctx: &ToolContext) -> ToolOutput {
+            panic!("boom")
+        }
+    }
+
+    fn response_with(content: Value) -> MessagesResponse {
+        serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": content,
+            "model": "claude-sonnet-4-5-20250929",
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 1, "output_tokens": 1}
+        }))
+        .unwrap()
+    }
+
+    fn result_parts(block: &ContentBlock) -> (&str, String, Option<bool>) {
+        match block {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content: Some(ToolResultContent::Text(text)),
+                is_error,
+            } => (tool_use_id.as_str(), text.clone(), *is_error),
+            other => panic!("Expected text ToolResult, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_definitions() {
+        let registry = ToolRegistry::new().with_tool(Add).with_tool(Panics);
+        let definitions = registry.definitions();
+
+        assert_eq!(definitions.len(), 2);
+        let json = serde_json::to_value(&definitions[0]).unwrap();
+        assert_eq!(json["name"], "add");
+        assert_eq!(json["description"], "Add two numbers");
+        assert_eq!(json["input_schema"]["required"], json!(["a", "b"]));
+        assert_eq!(registry.names(), vec!["add", "panics"]);
+    }
+
+    #[test]
+    fn test_register_replaces_same_name() {
+        let mut registry = ToolRegistry::new().with_tool(Add);
+        registry.register(Arc::new(Add));
+        assert_eq!(registry.len(), 1);
+        assert!(registry.get("add").is_some());
+        assert!(registry.get("missing").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_every_tool_use_in_order() {
+        let registry = ToolRegistry::new().with_tool(Add).with_tool(Panics);
+        let response = response_with(json!([
+            {"type": "text", "text": "Let me calculate."},
+            {"type": "tool_use", "id": "toolu_1", "name": "add", "input": {"a": 2, "b": 3}},
+            {"type": "tool_use", "id": "toolu_2", "name": "add", "input": {"a": "x"}},
+            {"type": "tool_use", "id": "toolu_3", "name": "nope", "input": {}},
+            {"type": "tool_use", "id": "toolu_4", "name": "panics", "input": {}}
+        ]));
+
+        let results = registry.execute(&response).await;
+        assert_eq!(results.len(), 4);
+
+        assert_eq!(result_parts(&results[0]), ("toolu_1", "5".into(), None));
+        let (id, text, is_error) = result_parts(&results[1]);
+        assert_eq!((id, is_error), ("toolu_2", Some(true)));
+        assert!(text.contains("must be numbers"));
+        assert_eq!(
+            result_parts(&results[2]),
+            ("toolu_3", "Unknown tool: nope".into(), Some(true))
+        );
+        assert_eq!(
+            result_parts(&results[3]),
+            ("toolu_4", "Tool 'panics' panicked".into(), Some(true))
+        );
+    }
+
+    #[cfg(feature = "validation")]
+    #[tokio::test]
+    async fn test_input_validation_rejects_before_calling() {
+        let registry = ToolRegistry::new()
+            .with_input_validation()
+            .with_tool(Add)
+            .with_tool(Panics);
+
+        let output = registry.call("toolu_1", "add", json!({"a": "x"})).await;
+        assert!(output.is_error);
+        let ToolResultContent::Text(text) = output.content else {
+            panic!("Expected text content");
+        };
+        assert!(text.starts_with("Invalid input for tool 'add':\n"));
+        assert!(text.contains(r#"- /a: "x" is not of type "number""#));
+        assert!(text.contains(r#"- (root): "b" is a required property"#));
+
+        let output = registry
+            .call("toolu_2", "add", json!({"a": 1, "b": 2}))
+            .await;
+        assert!(!output.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_permissions_deny_before_calling() {
+        use crate::permissions::{InputPattern, PermissionRule};
+
+        let registry = ToolRegistry::new()
+            .with_tool(Add)
+            .with_tool(Panics)
+            .with_permissions(
+                PermissionPolicy::new()
+                    .with_rule(PermissionRule::deny("panics").with_reason("Too risky"))
+                    .with_rule(
+                        PermissionRule::deny("add").when(InputPattern::field("/b", "^0$").unwrap()),
+                    ),
+            );
+        let response = response_with(json!([
+            {"type": "tool_use", "id": "toolu_1", "name": "panics", "input": {}},
+            {"type": "tool_use", "id": "toolu_2", "name": "add", "input": {"a": 1, "b": 0}},
+            {"type": "tool_use", "id": "toolu_3", "name": "add", "input": {"a": 1, "b": 2}}
+        ]));
+
+        let results = registry.execute(&response).await;
+        assert_eq!(
+            result_parts(&results[0]),
+            (
+                "toolu_1",
+                "Permission denied for tool 'panics': Too risky".into(),
+                Some(true)
+            )
+        );
+        assert_eq!(
+            result_parts(&results[1]),
+            (
+                "toolu_2",
+                "Permission denied for tool 'add': Denied by policy".into(),
+                Some(true)
+            )
+        );
+        assert_eq!(result_parts(&results[2]), ("toolu_3", "3".into(), None));
+    }
+
+    #[test]
+    fn test_tool_param_schemas() {
+        assert_eq!(
+            <Vec<u32>>::schema(),
+            json!({"type": "array", "items": {"type": "integer", "minimum": 0}})
+        );
+        assert_eq!(<Option<String>>::schema(), json!({"type": "string"}));
+        assert_eq!((<Option<String>>::REQUIRED, <f64>::REQUIRED), (false, true));
+        assert_eq!(
+            <HashMap<String, bool>>::schema(),
+            json!({"type": "object", "additionalProperties": {"type": "boolean"}})
+        );
+    }
+
+    #[test]
+    fn test_tool_output_into_block_serialization() {
+        let json = serde_json::to_value(ToolOutput::text("ok").into_block("toolu_1")).unwrap();
+        assert_eq!(
+            json,
+            json!({"type": "tool_result", "tool_use_id": "toolu_1", "content": "ok"})
+        );
+
+        let json = serde_json::to_value(ToolOutput::error("bad").into_block("toolu_2")).unwrap();
+        assert_eq!(json["is_error"], true);
+    }
+
+    #[test]
+    fn test_rich_tool_output() {
+        const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0";
+        assert_eq!(image_media_type(PNG), Some("image/png"));
+        assert_eq!(
+            image_media_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(image_media_type(b"GIF89a"), Some("image/gif"));
+        assert_eq!(
+            image_media_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert!(ToolOutput::image_bytes(b"plain text").is_err());
+
+        let output = ToolOutput::text("Screenshot:")
+            .with(ToolOutput::image_bytes(PNG).unwrap())
+            .with(ToolOutput::search_result(
+                SearchResult::new("doc://1", "Guide")
+                    .with_text("First")
+                    .with_citations(),
+            ))
+            .with(ToolOutput::json_pretty(&json!({"a": 1})));
+        assert!(!output.is_error);
+
+        let json = serde_json::to_value(output.into_block("toolu_1")).unwrap();
+        let content = &json["content"];
+        assert_eq!(content[0], json!({"type": "text", "text": "Screenshot:"}));
+        assert_eq!(
+            content[1]["source"],
+            json!({"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgoAAA=="})
+        );
+        assert_eq!(
+            content[2],
+            json!({
+                "type": "search_result",
+                "source": "doc://1",
+                "title": "Guide",
+                "content": [{"type": "text", "text": "First"}],
+                "citations": {"enabled": true}
+            })
+        );
+        assert_eq!(content[3]["text"], "{\n  \"a\": 1\n}");
+
+        let json =
+            serde_json::to_value(ToolOutput::pdf(b"%PDF-1.7").into_block("toolu_2")).unwrap();
+        assert_eq!(
+            json["content"][0]["source"]["media_type"],
+            "application/pdf"
+        );
+
+        // Errors can carry rich content too
+        let output =
+            ToolOutput::text("Login failed").with(ToolOutput::image("image/png", PNG).into_error());
+        assert!(output.is_error);
+        assert!(ToolOutput::error_json(&json!({"code": 7})).is_error);
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_from_files() {
+        let dir =
+            std::env::temp_dir().join(format!("claude_sdk_tool_output_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.md"), "# Notes").unwrap();
+        std::fs::write(dir.join("shot.gif"), b"GIF89a....").unwrap();
+
+        let json = serde_json::to_value(
+            ToolOutput::document_file(dir.join("notes.md"))
+                .await
+                .unwrap()
+                .into_block("toolu_1"),
+        )
+        .unwrap();
+        assert_eq!(
+            json["content"][0],
+            json!({
+                "type": "document",
+                "source": {"type": "text", "media_type": "text/plain", "data": "# Notes"},
+                "title": "notes.md"
+            })
+        );
+
+        let output = ToolOutput::image_file(dir.join("shot.gif")).await.unwrap();
+        match &output.content {
+            ToolResultContent::Blocks(blocks) => match &blocks[0] {
+                ContentBlock::Image {
+                    source: ImageSource::Base64 { media_type, .. },
+                    ..
+                } => assert_eq!(media_type, "image/gif"),
+                other => panic!("Expected image, got {:?}", other),
+            },
+            other => panic!("Expected blocks, got {:?}", other),
+        }
+
+        assert!(ToolOutput::image_file(dir.join("missing.png"))
+            .await
+            .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "macros")]
+    mod macros {
+        use super::*;
+        use crate::tool;
+
+        /// Get the weather for a city
+        ///
+        /// # Arguments
+        /// * `city` - City name
+        /// * `days` - Forecast length
+        #[tool]
+        async fn get_weather(city: String, days: Option<u32>) -> Result<Value, String> {
+            if city.is_empty() {
+                return Err("city must not be empty".into());
+            }
+            Ok(json!({"city": city, "days": days.unwrap_or(1)}))
+        }
+
+        /// Echo the caller's tool_use id
+        #[tool(name = "whoami")]
+        fn context_id(ctx: &ToolContext) -> String {
+            ctx.tool_use_id.clone()
+        }
+
+        /// Join arguments whose names match the generated locals
+        #[tool]
+        fn shadowing(input: String, ctx: u32, context: &ToolContext) -> String {
+            format!("{} {} {}", input, ctx, context.tool_name)
+        }
+
+        #[test]
+        fn test_generated_definition() {
+            let definition = GetWeather.definition();
+            assert_eq!(definition.name, "get_weather");
+            assert_eq!(definition.description, "Get the weather for a city");
+            assert_eq!(
+                definition.input_schema,
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "city": {"type": "string", "description": "City name"},
+                        "days": {"type": "integer", "minimum": 0, "description": "Forecast length"}
+                    },
+                    "required": ["city"]
+                })
+            );
+
+            assert_eq!(ContextId.name(), "whoami");
+            assert_eq!(
+                ContextId.input_schema(),
+                json!({"type": "object", "properties": {}, "required": []})
+            );
+        }
+
+        #[tokio::test]
+        async fn test_generated_call() {
+            let registry = ToolRegistry::new()
+                .with_tool(GetWeather)
+                .with_tool(ContextId);
+
+            let output = registry
+                .call(
+                    "toolu_1",
+                    "get_weather",
+                    json!({"city": "Paris", "days": 3}),
+                )
+                .await;
+            assert!(!output.is_error);
+            match output.content {
+                ToolResultContent::Text(text) => {
+                    assert_eq!(
+                        serde_json::from_str::<Value>(&text).unwrap(),
+                        json!({"city": "Paris", "days": 3})
+                    )
+                }
+                other => panic!("Expected text, got {:?}", other),
+            }
+
+            let output = registry
+                .call("toolu_2", "get_weather", json!({"city": ""}))
+                .await;
+            assert!(output.is_error);
+
+            let output = registry
+                .call("toolu_3", "get_weather", json!({"days": 2}))
+                .await;
+            assert!(output.is_error);
+            match output.content {
+                ToolResultContent::Text(text) => assert!(text.contains("Invalid argument `city`")),
+                other => panic!("Expected text, got {:?}", other),
+            }
+
+            let output = registry.call("toolu_4", "whoami", json!({})).await;
+            match output.content {
+                ToolResultContent::Text(text) => assert_eq!(text, "toolu_4"),
+                other => panic!("Expected text, got {:?}", other),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_arguments_named_like_generated_locals() {
+            let output = Shadowing
+                .call(
+                    json!({"input": "a", "ctx": 2}),
+                    &ToolContext::new("toolu_1", "shadowing"),
+                )
+                .await;
+            match output.content {
+                ToolResultContent::Text(text) => assert_eq!(text, "a 2 shadowing"),
+                other => panic!("Expected text, got {:?}", other),
+            }
+        }
+    }
+}
@@ -26,6 +26,10 @@
 //! .with_temperature(0.7);
 //! ```
 
+use crate::server_tools::{
+    CodeExecutionToolResultContent, TextEditorCodeExecutionToolResultContent,
+    WebFetchToolResultContent, WebSearchToolResultContent,
+};
 use serde::{Deserialize, Serialize};
 
 /// Container metadata in API response
@@ -87,9 +91,9 @@ pub enum ContentBlock {
         id: String,
         name: String,
         input: serde_json::Value,
-        /// Which system invoked this tool ("direct", "code_execution_20250825", etc.)
+        /// Which system invoked this tool (a direct call or code execution)
         #[serde(skip_serializing_if = "Option::is_none")]
-        caller: Option<String>,
+        caller: Option<ToolCaller>,
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
@@ -138,14 +142,35 @@ pub enum ContentBlock {
     /// Web search tool result
     WebSearchToolResult {
         tool_use_id: String,
-        content: serde_json::Value,
+        content: WebSearchToolResultContent,
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
-    /// Code execution tool result
+    /// Web fetch tool result
+    WebFetchToolResult {
+        tool_use_id: String,
+        content: WebFetchToolResultContent,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    /// Code execution tool result (`code_execution_20250522`)
     CodeExecutionToolResult {
         tool_use_id: String,
-        content: serde_json::Value,
+        content: CodeExecutionToolResultContent,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    /// Bash command result from code execution (`code_execution_20250825`)
+    BashCodeExecutionToolResult {
+        tool_use_id: String,
+        content: CodeExecutionToolResultContent,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    /// File operation result from code execution (`code_execution_20250825`)
+    TextEditorCodeExecutionToolResult {
+        tool_use_id: String,
+        content: TextEditorCodeExecutionToolResultContent,
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
@@ -161,6 +186,33 @@ pub enum ContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    /// Tool call Claude made on a remote server through the MCP connector
+    ///
+    /// Requires beta header: `anthropic-beta: mcp-client-2025-04-04`
+    McpToolUse {
+        id: String,
+        name: String,
+        /// Name of the server in [`MessagesRequest::mcp_servers`]
+        server_name: String,
+        input: serde_json::Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    /// Result of an MCP connector tool call
+    McpToolResult {
+        tool_use_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content: Option<ToolResultContent>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        is_error: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    /// Reference to a deferred tool, returned inside a tool search result
+    ///
+    /// Loads the named tool (declared with `defer_loading`) for the rest of
+    /// the conversation.
+    ToolReference { tool_name: String },
     /// Unknown content block type (forward compatibility)
     ///
     /// When the API returns a content block type this SDK doesn't
@@ -213,7 +265,7 @@ enum ContentBlockHelper {
         id: String,
         name: String,
         input: serde_json::Value,
-        caller: Option<String>,
+        caller: Option<ToolCaller>,
         cache_control: Option<CacheControl>,
     },
     ToolResult {
@@ -243,12 +295,27 @@ enum ContentBlockHelper {
     },
     WebSearchToolResult {
         tool_use_id: String,
-        content: serde_json::Value,
+        content: WebSearchToolResultContent,
+        cache_control: Option<CacheControl>,
+    },
+    WebFetchToolResult {
+        tool_use_id: String,
+        content: WebFetchToolResultContent,
         cache_control: Option<CacheControl>,
     },
     CodeExecutionToolResult {
         tool_use_id: String,
-        content: serde_json::Value,
+        content: CodeExecutionToolResultContent,
+        cache_control: Option<CacheControl>,
+    },
+    BashCodeExecutionToolResult {
+        tool_use_id: String,
+        content: CodeExecutionToolResultContent,
+        cache_control: Option<CacheControl>,
+    },
+    TextEditorCodeExecutionToolResult {
+        tool_use_id: String,
+        content: TextEditorCodeExecutionToolResultContent,
         cache_control: Option<CacheControl>,
     },
     ContainerUpload {
@@ -259,6 +326,22 @@ enum ContentBlockHelper {
         content: Vec<TextBlock>,
         cache_control: Option<CacheControl>,
     },
+    McpToolUse {
+        id: String,
+        name: String,
+        server_name: String,
+        input: serde_json::Value,
+        cache_control: Option<CacheControl>,
+    },
+    McpToolResult {
+        tool_use_id: String,
+        content: Option<ToolResultContent>,
+        is_error: Option<bool>,
+        cache_control: Option<CacheControl>,
+    },
+    ToolReference {
+        tool_name: String,
+    },
 }
 
 impl<'de> serde::Deserialize<'de> for ContentBlock {
@@ -365,6 +448,15 @@ impl<'de> serde::Deserialize<'de> for ContentBlock {
                     content,
                     cache_control,
                 },
+                ContentBlockHelper::WebFetchToolResult {
+                    tool_use_id,
+                    content,
+                    cache_control,
+                } => ContentBlock::WebFetchToolResult {
+                    tool_use_id,
+                    content,
+                    cache_control,
+                },
                 ContentBlockHelper::CodeExecutionToolResult {
                     tool_use_id,
                     content,
@@ -374,6 +466,24 @@ impl<'de> serde::Deserialize<'de> for ContentBlock {
                     content,
                     cache_control,
                 },
+                ContentBlockHelper::BashCodeExecutionToolResult {
+                    tool_use_id,
+                    content,
+                    cache_control,
+                } => ContentBlock::BashCodeExecutionToolResult {
+                    tool_use_id,
+                    content,
+                    cache_control,
+                },
+                ContentBlockHelper::TextEditorCodeExecutionToolResult {
+                    tool_use_id,
+                    content,
+                    cache_control,
+                } => ContentBlock::TextEditorCodeExecutionToolResult {
+                    tool_use_id,
+                    content,
+                    cache_control,
+                },
                 ContentBlockHelper::ContainerUpload {
                     file_id,
                     cache_control,
@@ -388,6 +498,33 @@ impl<'de> serde::Deserialize<'de> for ContentBlock {
                     content,
                     cache_control,
                 },
+                ContentBlockHelper::McpToolUse {
+                    id,
+                    name,
+                    server_name,
+                    input,
+                    cache_control,
+                } => ContentBlock::McpToolUse {
+                    id,
+                    name,
+                    server_name,
+                    input,
+                    cache_control,
+                },
+                ContentBlockHelper::McpToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                    cache_control,
+                } => ContentBlock::McpToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                    cache_control,
+                },
+                ContentBlockHelper::ToolReference { tool_name } => {
+                    ContentBlock::ToolReference { tool_name }
+                }
             }),
             Err(_) => {
                 // Unknown type -- extract the type field and capture the raw data
@@ -413,6 +550,16 @@ pub struct TextBlock {
     pub text: String,
 }
 
+impl TextBlock {
+    /// Create a text block
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            block_type: "text".into(),
+            text: text.into(),
+        }
+    }
+}
+
 /// Image source for vision
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -435,6 +582,10 @@ pub enum DocumentSource {
     File { file_id: String },
     /// Inline text document
     Text { media_type: String, data: String },
+    /// Base64-encoded PDF
+    Base64 { media_type: String, data: String },
+    /// PDF URL
+    Url { url: String },
 }
 
 /// Citation configuration for documents and search results
@@ -554,6 +705,17 @@ impl Message {
             }],
         }
     }
+
+    /// Create a tool result message from a [`ToolOutput`](crate::tools::ToolOutput)
+    ///
+    /// Unlike [`tool_result`](Self::tool_result) this keeps images,
+    /// documents, search results and the error flag.
+    pub fn tool_output(tool_use_id: impl Into<String>, output: crate::tools::ToolOutput) -> Self {
+        Self {
+            role: Role::User,
+            content: vec![output.into_block(tool_use_id)],
+        }
+    }
 }
 
 /// System prompt format
@@ -573,6 +735,92 @@ pub struct SystemBlock {
     pub cache_control: Option<CacheControl>,
 }
 
+/// Caller type for tools Claude calls itself
+pub const DIRECT_CALLER: &str = "direct";
+
+/// Caller type for tools called from code running in `code_execution`
+pub const CODE_EXECUTION_CALLER: &str = "code_execution_20250825";
+
+/// Whether a caller type names any version of the code execution tool
+fn is_code_execution_caller(caller_type: &str) -> bool {
+    caller_type.starts_with("code_execution")
+}
+
+/// Which system invoked a tool, from the `caller` of a `tool_use` block
+///
+/// Calls made from code execution (programmatic tool calling) carry the id
+/// of the `server_tool_use` block whose code made the call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "ToolCallerRepr")]
+pub struct ToolCaller {
+    /// `"direct"` or a code execution tool type such as `"code_execution_20250825"`
+    #[serde(rename = "type")]
+    pub caller_type: String,
+
+    /// Id of the code execution call that made this call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_id: Option<String>,
+}
+
+impl ToolCaller {
+    /// A direct call by Claude
+    pub fn direct() -> Self {
+        Self {
+            caller_type: DIRECT_CALLER.into(),
+            tool_id: None,
+        }
+    }
+
+    /// A call from the code execution call `tool_id`
+    pub fn code_execution(tool_id: impl Into<String>) -> Self {
+        Self {
+            caller_type: CODE_EXECUTION_CALLER.into(),
+            tool_id: Some(tool_id.into()),
+        }
+    }
+
+    /// Whether Claude called the tool itself
+    pub fn is_direct(&self) -> bool {
+        self.caller_type == DIRECT_CALLER
+    }
+
+    /// Whether the tool was called from code execution
+    pub fn is_code_execution(&self) -> bool {
+        is_code_execution_caller(&self.caller_type)
+    }
+}
+
+/// Older responses sent the caller type as a bare string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ToolCallerRepr {
+    Type(String),
+    Object {
+        #[serde(rename = "type")]
+        caller_type: String,
+        #[serde(default)]
+        tool_id: Option<String>,
+    },
+}
+
+impl From<ToolCallerRepr> for ToolCaller {
+    fn from(repr: ToolCallerRepr) -> Self {
+        match repr {
+            ToolCallerRepr::Type(caller_type) => Self {
+                caller_type,
+                tool_id: None,
+            },
+            ToolCallerRepr::Object {
+                caller_type,
+                tool_id,
+            } => Self {
+                caller_type,
+                tool_id,
+            },
+        }
+    }
+}
+
 /// Custom client-side tool definition
 ///
 /// Defines a tool with a name, description, and JSON Schema for its inputs.
@@ -607,6 +855,13 @@ pub struct CustomTool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub disable_user_input: Option<bool>,
 
+    /// Which callers may invoke this tool (`"direct"`, `"code_execution_20250825"`)
+    ///
+    /// Requires beta header: `anthropic-beta: advanced-tool-use-2025-11-20`
+    /// (sent automatically). Unset means direct calls only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_callers: Option<Vec<String>>,
+
     /// Example inputs for the tool (beta feature)
     ///
     /// Requires beta header: `anthropic-beta: advanced-tool-use-2025-11-20`
@@ -643,6 +898,7 @@ impl CustomTool {
             description: description.into(),
             input_schema,
             disable_user_input: None,
+            allowed_callers: None,
             input_examples: None,
             cache_control: None,
             defer_loading: None,
@@ -657,11 +913,64 @@ impl CustomTool {
         self
     }
 
+    /// Set which callers may invoke this tool
+    ///
+    /// ```rust
+    /// use claude_sdk::types::{CustomTool, CODE_EXECUTION_CALLER};
+    /// use serde_json::json;
+    ///
+    /// // Only callable from code Claude writes in the code execution tool
+    /// let tool = CustomTool::new("query_db", "Run a SQL query", json!({"type": "object"}))
+    ///     .with_allowed_callers([CODE_EXECUTION_CALLER]);
+    /// ```
+    pub fn with_allowed_callers<I, S>(mut self, callers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_callers = Some(callers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Whether a call from `caller` is allowed by [`allowed_callers`](Self::allowed_callers)
+    ///
+    /// A missing caller counts as a direct call. Code execution callers
+    /// match whatever their tool version, as in
+    /// [`ToolCaller::is_code_execution`].
+    pub fn allows_caller(&self, caller: Option<&ToolCaller>) -> bool {
+        let caller_type = caller.map_or(DIRECT_CALLER, |c| c.caller_type.as_str());
+        let matches = |allowed: &str| {
+            allowed == caller_type
+                || (is_code_execution_caller(allowed) && is_code_execution_caller(caller_type))
+        };
+        match &self.allowed_callers {
+            Some(allowed) => allowed.iter().any(|a| matches(a)),
+            None => caller_type == DIRECT_CALLER,
+        }
+    }
+
     /// Enable strict JSON schema validation
+    ///
+    /// Logs a warning for each construct strict mode rejects; see
+    /// [`lint_schema`](crate::structured::lint_schema).
     pub fn with_strict(mut self) -> Self {
+        crate::structured::warn_schema_issues(&format!("Tool `{}`", self.name), &self.input_schema);
         self.strict = Some(true);
         self
     }
+
+    /// Create a tool whose input schema is derived from `T`
+    ///
+    /// The schema is rewritten by [`strict_schema`](crate::structured::strict_schema),
+    /// so the tool can be combined with [`with_strict`](Self::with_strict).
+    /// Requires the `schemars` feature.
+    #[cfg(feature = "schemars")]
+    pub fn from_type<T: schemars::JsonSchema + ?Sized>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self::new(name, description, crate::structured::schema_for::<T>())
+    }
 }
 
 /// Renamed to [`CustomTool`] in v2.0. Use `CustomTool` directly.
@@ -767,7 +1076,7 @@ impl ToolChoice {
 }
 
 /// Detailed output token breakdown
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct OutputTokensDetails {
     /// Tokens used for thinking
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -775,7 +1084,7 @@ pub struct OutputTokensDetails {
 }
 
 /// Server tool usage counts
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct ServerToolUsage {
     /// Number of web search requests made
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -786,7 +1095,7 @@ pub struct ServerToolUsage {
 }
 
 /// Token usage information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
     pub input_tokens: u32,
     pub output_tokens: u32,
@@ -816,6 +1125,53 @@ pub struct Usage {
     pub inference_geo: Option<String>,
 }
 
+impl Usage {
+    /// Input plus output tokens
+    pub fn total_tokens(&self) -> u64 {
+        u64::from(self.input_tokens) + u64::from(self.output_tokens)
+    }
+
+    /// Add another response's usage to this one
+    ///
+    /// Counters are summed; `service_tier` and `inference_geo` keep the
+    /// most recent value.
+    pub fn accumulate(&mut self, other: &Usage) {
+        fn add(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+            match (a, b) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+            }
+        }
+
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens = add(
+            self.cache_creation_input_tokens,
+            other.cache_creation_input_tokens,
+        );
+        self.cache_read_input_tokens =
+            add(self.cache_read_input_tokens, other.cache_read_input_tokens);
+
+        if let Some(details) = other.output_tokens_details {
+            let mine = self
+                .output_tokens_details
+                .get_or_insert_with(Default::default);
+            mine.thinking_tokens = add(mine.thinking_tokens, details.thinking_tokens);
+        }
+        if let Some(server) = other.server_tool_use {
+            let mine = self.server_tool_use.get_or_insert_with(Default::default);
+            mine.web_search_requests = add(mine.web_search_requests, server.web_search_requests);
+            mine.web_fetch_requests = add(mine.web_fetch_requests, server.web_fetch_requests);
+        }
+        if other.service_tier.is_some() {
+            self.service_tier = other.service_tier.clone();
+        }
+        if other.inference_geo.is_some() {
+            self.inference_geo = other.inference_geo.clone();
+        }
+    }
+}
+
 /// Extended usage information for responses with thinking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtendedUsage {
@@ -909,6 +1265,13 @@ pub struct MessagesRequest {
     /// Container ID for persistent code execution
     #[serde(skip_serializing_if = "Option::is_none")]
     pub container: Option<String>,
+
+    /// Remote MCP servers Claude can call through the MCP connector
+    ///
+    /// The client adds the required `anthropic-beta: mcp-client-2025-04-04`
+    /// header when this is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcp_servers: Option<Vec<McpServerDefinition>>,
 }
 
 /// Extended thinking configuration
@@ -1037,6 +1400,7 @@ impl MessagesRequest {
             service_tier: None,
             inference_geo: None,
             container: None,
+            mcp_servers: None,
         }
     }
 
@@ -1220,7 +1584,11 @@ impl MessagesRequest {
     }
 
     /// Set JSON schema for structured output
+    ///
+    /// Logs a warning for each construct structured output rejects; see
+    /// [`lint_schema`](crate::structured::lint_schema).
     pub fn with_json_schema(mut self, schema: serde_json::Value) -> Self {
+        crate::structured::warn_schema_issues("Output", &schema);
         let config = self.output_config.get_or_insert(OutputConfig {
             effort: None,
             format: None,
@@ -1232,6 +1600,15 @@ impl MessagesRequest {
         self
     }
 
+    /// Set structured output to a JSON schema derived from `T`
+    ///
+    /// See [`structured::schema_for`](crate::structured::schema_for).
+    /// Requires the `schemars` feature.
+    #[cfg(feature = "schemars")]
+    pub fn with_output_type<T: schemars::JsonSchema + ?Sized>(self) -> Self {
+        self.with_json_schema(crate::structured::schema_for::<T>())
+    }
+
     /// Enable extended thinking with a token budget.
     ///
     /// Extended thinking allows Claude to reason through complex problems
@@ -1292,6 +1669,129 @@ impl MessagesRequest {
         self.container = Some(container_id.into());
         self
     }
+
+    /// Let Claude call tools on a remote MCP server
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use claude_sdk::{McpServerDefinition, Message, MessagesRequest};
+    ///
+    /// let request = MessagesRequest::new(
+    ///     "claude-sonnet-4-5-20250929",
+    ///     1024,
+    ///     vec![Message::user("What's on my calendar today?")],
+    /// )
+    /// .with_mcp_server(
+    ///     McpServerDefinition::new("calendar", "https://mcp.example.com/sse")
+    ///         .with_authorization_token("secret")
+    ///         .with_allowed_tools(vec!["list_events".into()]),
+    /// );
+    ///
+    /// assert_eq!(request.beta_header().as_deref(), Some("mcp-client-2025-04-04"));
+    /// ```
+    pub fn with_mcp_server(mut self, server: McpServerDefinition) -> Self {
+        self.mcp_servers.get_or_insert_with(Vec::new).push(server);
+        self
+    }
+
+    /// Set the MCP servers available through the MCP connector
+    pub fn with_mcp_servers(mut self, servers: Vec<McpServerDefinition>) -> Self {
+        self.mcp_servers = Some(servers);
+        self
+    }
+
+    /// Value of the `anthropic-beta` header this request needs, if any
+    pub fn beta_header(&self) -> Option<String> {
+        let mut betas = Vec::new();
+        if self.mcp_servers.as_ref().is_some_and(|s| !s.is_empty()) {
+            betas.push(MCP_CLIENT_BETA);
+        }
+        let advanced = self.tools.iter().flatten().any(|tool| {
+            matches!(tool, ToolDefinition::Custom(custom)
+                if custom.allowed_callers.is_some() || custom.defer_loading == Some(true))
+        });
+        if advanced {
+            betas.push(ADVANCED_TOOL_USE_BETA);
+        }
+        (!betas.is_empty()).then(|| betas.join(","))
+    }
+}
+
+/// Beta header value enabling the MCP connector
+pub const MCP_CLIENT_BETA: &str = "mcp-client-2025-04-04";
+
+/// Beta header value enabling programmatic tool calling (`allowed_callers`)
+/// and deferred tool loading (`defer_loading`)
+pub const ADVANCED_TOOL_USE_BETA: &str = "advanced-tool-use-2025-11-20";
+
+/// Remote MCP server reachable by URL, for [`MessagesRequest::mcp_servers`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServerDefinition {
+    /// Always `"url"`
+    #[serde(rename = "type")]
+    pub server_type: String,
+
+    /// Server URL (streamable HTTP or SSE)
+    pub url: String,
+
+    /// Name used in `mcp_tool_use` blocks
+    pub name: String,
+
+    /// OAuth bearer token sent to the server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_token: Option<String>,
+
+    /// Which of the server's tools Claude may use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_configuration: Option<McpToolConfiguration>,
+}
+
+impl McpServerDefinition {
+    /// A server named `name` at `url`
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            server_type: "url".into(),
+            url: url.into(),
+            name: name.into(),
+            authorization_token: None,
+            tool_configuration: None,
+        }
+    }
+
+    /// Authenticate to the server with an OAuth bearer token
+    pub fn with_authorization_token(mut self, token: impl Into<String>) -> Self {
+        self.authorization_token = Some(token.into());
+        self
+    }
+
+    /// Only expose these tools to Claude
+    pub fn with_allowed_tools(mut self, tools: Vec<String>) -> Self {
+        self.tool_configuration
+            .get_or_insert_with(McpToolConfiguration::default)
+            .allowed_tools = Some(tools);
+        self
+    }
+
+    /// Enable or disable all of the server's tools
+    pub fn with_tools_enabled(mut self, enabled: bool) -> Self {
+        self.tool_configuration
+            .get_or_insert_with(McpToolConfiguration::default)
+            .enabled = Some(enabled);
+        self
+    }
+}
+
+/// Tool settings for an [`McpServerDefinition`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpToolConfiguration {
+    /// Whether the server's tools are available (default `true`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+
+    /// Allowlist of tool names; all tools when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
 }
 
 /// Response from the token counting endpoint
@@ -1406,6 +1906,7 @@ pub struct RateLimitInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::server_tools::{FetchedDocumentSource, ServerToolErrorCode};
 
     #[test]
     fn test_cache_control_ephemeral() {
@@ -1637,6 +2138,36 @@ mod tests {
         assert!(usage.server_tool_use.is_none());
     }
 
+    #[test]
+    fn test_usage_accumulate() {
+        let mut total = Usage::default();
+        let first: Usage = serde_json::from_str(
+            r#"{"input_tokens": 10, "output_tokens": 5, "cache_read_input_tokens": 3,
+                "server_tool_use": {"web_search_requests": 1}}"#,
+        )
+        .unwrap();
+        let second: Usage = serde_json::from_str(
+            r#"{"input_tokens": 20, "output_tokens": 7, "service_tier": "standard",
+                "output_tokens_details": {"thinking_tokens": 4}}"#,
+        )
+        .unwrap();
+
+        total.accumulate(&first);
+        total.accumulate(&second);
+
+        assert_eq!(total.input_tokens, 30);
+        assert_eq!(total.output_tokens, 12);
+        assert_eq!(total.total_tokens(), 42);
+        assert_eq!(total.cache_read_input_tokens, Some(3));
+        assert_eq!(total.cache_creation_input_tokens, None);
+        assert_eq!(total.server_tool_use.unwrap().web_search_requests, Some(1));
+        assert_eq!(
+            total.output_tokens_details.unwrap().thinking_tokens,
+            Some(4)
+        );
+        assert_eq!(total.service_tier.as_deref(), Some("standard"));
+    }
+
     // Task 6: TokenCount tests
 
     #[test]
@@ -1773,6 +2304,89 @@ mod tests {
 
     // Task 3: Server tool content block tests
 
+    #[test]
+    fn test_mcp_connector_blocks_deserialization() {
+        let response = r#"[
+            {"type": "mcp_tool_use", "id": "mcptoolu_1", "name": "echo", "server_name": "example", "input": {"param1": "value1"}},
+            {"type": "mcp_tool_result", "tool_use_id": "mcptoolu_1", "is_error": false, "content": [{"type": "text", "text": "Hello"}]}
+        ]"#;
+        let blocks: Vec<ContentBlock> = serde_json::from_str(response).unwrap();
+        match &blocks[0] {
+            ContentBlock::McpToolUse {
+                id,
+                name,
+                server_name,
+                input,
+                ..
+            } => {
+                assert_eq!(id, "mcptoolu_1");
+                assert_eq!(name, "echo");
+                assert_eq!(server_name, "example");
+                assert_eq!(input["param1"], "value1");
+            }
+            other => panic!("Expected McpToolUse, got {:?}", other),
+        }
+        match &blocks[1] {
+            ContentBlock::McpToolResult {
+                tool_use_id,
+                content: Some(ToolResultContent::Blocks(content)),
+                is_error,
+                ..
+            } => {
+                assert_eq!(tool_use_id, "mcptoolu_1");
+                assert_eq!(*is_error, Some(false));
+                assert!(matches!(&content[0], ContentBlock::Text { text, .. } if text == "Hello"));
+            }
+            other => panic!("Expected McpToolResult, got {:?}", other),
+        }
+
+        // Blocks round-trip so they can be sent back in the next turn
+        let json = serde_json::to_value(&blocks).unwrap();
+        assert_eq!(json[0]["type"], "mcp_tool_use");
+        assert_eq!(json[1]["type"], "mcp_tool_result");
+    }
+
+    #[test]
+    fn test_mcp_servers_serialization() {
+        let request = MessagesRequest::new("model", 100, vec![Message::user("Hi")])
+            .with_mcp_server(
+                McpServerDefinition::new("calendar", "https://mcp.example.com/sse")
+                    .with_authorization_token("token")
+                    .with_allowed_tools(vec!["list_events".into()]),
+            )
+            .with_mcp_server(
+                McpServerDefinition::new("disabled", "https://other.example.com")
+                    .with_tools_enabled(false),
+            );
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["mcp_servers"],
+            serde_json::json!([
+                {
+                    "type": "url",
+                    "url": "https://mcp.example.com/sse",
+                    "name": "calendar",
+                    "authorization_token": "token",
+                    "tool_configuration": {"allowed_tools": ["list_events"]}
+                },
+                {
+                    "type": "url",
+                    "url": "https://other.example.com",
+                    "name": "disabled",
+                    "tool_configuration": {"enabled": false}
+                }
+            ])
+        );
+        assert_eq!(request.beta_header().as_deref(), Some(MCP_CLIENT_BETA));
+
+        let plain = MessagesRequest::new("model", 100, vec![Message::user("Hi")]);
+        assert!(serde_json::to_value(&plain)
+            .unwrap()
+            .get("mcp_servers")
+            .is_none());
+        assert_eq!(plain.beta_header(), None);
+    }
+
     #[test]
     fn test_server_tool_use_deserialization() {
         let json = r#"{"type": "server_tool_use", "id": "stu_123", "name": "web_search", "input": {"query": "rust"}}"#;
@@ -1791,35 +2405,158 @@ mod tests {
 
     #[test]
     fn test_web_search_tool_result_deserialization() {
-        let json = r#"{"type": "web_search_tool_result", "tool_use_id": "stu_123", "content": [{"type": "web_page", "url": "https://example.com"}]}"#;
+        let json = r#"{"type": "web_search_tool_result", "tool_use_id": "stu_123", "content": [{"type": "web_search_result", "url": "https://example.com", "title": "Example", "encrypted_content": "abc", "page_age": "April 30, 2025"}]}"#;
         let block: ContentBlock = serde_json::from_str(json).unwrap();
         match block {
             ContentBlock::WebSearchToolResult {
                 tool_use_id,
-                content,
+                content: WebSearchToolResultContent::Results(results),
                 ..
             } => {
                 assert_eq!(tool_use_id, "stu_123");
-                assert!(content.is_array());
+                assert_eq!(results[0].url, "https://example.com");
+                assert_eq!(results[0].title, "Example");
+                assert_eq!(results[0].encrypted_content, "abc");
+                assert_eq!(results[0].page_age.as_deref(), Some("April 30, 2025"));
             }
-            _ => panic!("Expected WebSearchToolResult variant"),
+            other => panic!("Expected WebSearchToolResult results, got {:?}", other),
+        }
+
+        let json = r#"{"type": "web_search_tool_result", "tool_use_id": "stu_124", "content": {"type": "web_search_tool_result_error", "error_code": "max_uses_exceeded"}}"#;
+        match serde_json::from_str::<ContentBlock>(json).unwrap() {
+            ContentBlock::WebSearchToolResult {
+                content: WebSearchToolResultContent::Error(error),
+                ..
+            } => assert_eq!(error.error_code, ServerToolErrorCode::MaxUsesExceeded),
+            other => panic!("Expected WebSearchToolResult error, got {:?}", other),
         }
     }
 
     #[test]
     fn test_code_execution_tool_result_deserialization() {
-        let json = r#"{"type": "code_execution_tool_result", "tool_use_id": "ce_123", "content": {"stdout": "hello"}}"#;
+        let json = r#"{"type": "code_execution_tool_result", "tool_use_id": "ce_123", "content": {"type": "code_execution_result", "stdout": "hello", "stderr": "", "return_code": 0, "content": [{"type": "code_execution_output", "file_id": "file_1"}]}}"#;
         let block: ContentBlock = serde_json::from_str(json).unwrap();
         match block {
             ContentBlock::CodeExecutionToolResult {
                 tool_use_id,
-                content,
+                content: CodeExecutionToolResultContent::Result(result),
                 ..
             } => {
                 assert_eq!(tool_use_id, "ce_123");
-                assert_eq!(content["stdout"], "hello");
+                assert_eq!(result.stdout, "hello");
+                assert_eq!(result.return_code, 0);
+                assert_eq!(result.file_ids(), ["file_1"]);
+            }
+            other => panic!("Expected CodeExecutionToolResult, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bash_code_execution_tool_result_deserialization() {
+        let json = r#"{"type": "bash_code_execution_tool_result", "tool_use_id": "srvtoolu_1", "content": {"type": "bash_code_execution_result", "stdout": "", "stderr": "ls: missing", "return_code": 2, "content": []}}"#;
+        match serde_json::from_str::<ContentBlock>(json).unwrap() {
+            ContentBlock::BashCodeExecutionToolResult {
+                content: CodeExecutionToolResultContent::Result(result),
+                ..
+            } => {
+                assert_eq!(result.stderr, "ls: missing");
+                assert_eq!(result.return_code, 2);
+            }
+            other => panic!("Expected BashCodeExecutionToolResult, got {:?}", other),
+        }
+
+        let json = r#"{"type": "bash_code_execution_tool_result", "tool_use_id": "srvtoolu_2", "content": {"type": "bash_code_execution_tool_result_error", "error_code": "too_many_requests"}}"#;
+        match serde_json::from_str::<ContentBlock>(json).unwrap() {
+            ContentBlock::BashCodeExecutionToolResult {
+                content: CodeExecutionToolResultContent::Error(error),
+                ..
+            } => assert_eq!(error.error_code, ServerToolErrorCode::TooManyRequests),
+            other => panic!(
+                "Expected BashCodeExecutionToolResult error, got {:?}",
+                other
+            ),
+        }
+    }
+
+    #[test]
+    fn test_text_editor_code_execution_tool_result_deserialization() {
+        let blocks: Vec<ContentBlock> = serde_json::from_str(
+            r#"[
+                {"type": "text_editor_code_execution_tool_result", "tool_use_id": "a", "content": {"type": "text_editor_code_execution_view_result", "file_type": "text", "content": "x = 1", "num_lines": 1, "start_line": 1, "total_lines": 1}},
+                {"type": "text_editor_code_execution_tool_result", "tool_use_id": "b", "content": {"type": "text_editor_code_execution_create_result", "is_file_update": false}},
+                {"type": "text_editor_code_execution_tool_result", "tool_use_id": "c", "content": {"type": "text_editor_code_execution_str_replace_result", "old_start": 1, "old_lines": 1, "new_start": 1, "new_lines": 1, "lines": ["-x = 1", "+x = 2"]}},
+                {"type": "text_editor_code_execution_tool_result", "tool_use_id": "d", "content": {"type": "text_editor_code_execution_tool_result_error", "error_code": "file_not_found", "error_message": "No such file"}}
+            ]"#,
+        )
+        .unwrap();
+        let contents: Vec<&TextEditorCodeExecutionToolResultContent> = blocks
+            .iter()
+            .map(|block| match block {
+                ContentBlock::TextEditorCodeExecutionToolResult { content, .. } => content,
+                other => panic!(
+                    "Expected TextEditorCodeExecutionToolResult, got {:?}",
+                    other
+                ),
+            })
+            .collect();
+        assert!(
+            matches!(contents[0], TextEditorCodeExecutionToolResultContent::View(view) if view.content == "x = 1")
+        );
+        assert!(
+            matches!(contents[1], TextEditorCodeExecutionToolResultContent::Create(create) if !create.is_file_update)
+        );
+        assert!(
+            matches!(contents[2], TextEditorCodeExecutionToolResultContent::StrReplace(diff) if diff.lines.len() == 2)
+        );
+        match contents[3] {
+            TextEditorCodeExecutionToolResultContent::Error(error) => {
+                assert_eq!(error.error_code, ServerToolErrorCode::FileNotFound);
+                assert_eq!(error.error_message.as_deref(), Some("No such file"));
+            }
+            other => panic!("Expected error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_web_fetch_tool_result_deserialization() {
+        let json = r#"{"type": "web_fetch_tool_result", "tool_use_id": "srvtoolu_1", "content": {"type": "web_fetch_result", "url": "https://example.com/a", "retrieved_at": "2025-08-25T10:30:00Z", "content": {"type": "document", "source": {"type": "text", "media_type": "text/plain", "data": "Page text"}, "title": "A", "citations": {"enabled": true}}}}"#;
+        let block: ContentBlock = serde_json::from_str(json).unwrap();
+        match &block {
+            ContentBlock::WebFetchToolResult {
+                content: WebFetchToolResultContent::Result(result),
+                ..
+            } => {
+                assert_eq!(result.url, "https://example.com/a");
+                assert_eq!(result.content.title.as_deref(), Some("A"));
+                assert!(matches!(
+                    &result.content.source,
+                    FetchedDocumentSource::Text { data, .. } if data == "Page text"
+                ));
+            }
+            other => panic!("Expected WebFetchToolResult, got {:?}", other),
+        }
+        // Round-trips unchanged so it can be sent back in the next turn
+        assert_eq!(
+            serde_json::to_value(&block).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+
+        let json = r#"{"type": "web_fetch_tool_result", "tool_use_id": "srvtoolu_2", "content": {"type": "web_fetch_tool_result_error", "error_code": "brand_new_code"}}"#;
+        match serde_json::from_str::<ContentBlock>(json).unwrap() {
+            ContentBlock::WebFetchToolResult {
+                content: WebFetchToolResultContent::Error(error),
+                ..
+            } => {
+                assert_eq!(
+                    error.error_code,
+                    ServerToolErrorCode::Other("brand_new_code".into())
+                );
+                assert_eq!(
+                    serde_json::to_value(&error.error_code).unwrap(),
+                    "brand_new_code"
+                );
             }
-            _ => panic!("Expected CodeExecutionToolResult variant"),
+            other => panic!("Expected WebFetchToolResult error, got {:?}", other),
         }
     }
 
@@ -1941,11 +2678,60 @@ mod tests {
             id: "tu_123".into(),
             name: "my_tool".into(),
             input: serde_json::json!({}),
-            caller: Some("code_execution_20250825".into()),
+            caller: Some(ToolCaller::code_execution("srvtoolu_1")),
             cache_control: None,
         };
         let json = serde_json::to_value(&block).unwrap();
-        assert_eq!(json["caller"], "code_execution_20250825");
+        assert_eq!(json["caller"]["type"], "code_execution_20250825");
+        assert_eq!(json["caller"]["tool_id"], "srvtoolu_1");
+    }
+
+    #[test]
+    fn test_tool_caller_deserialization() {
+        let block: ContentBlock = serde_json::from_value(serde_json::json!({
+            "type": "tool_use",
+            "id": "tu_1",
+            "name": "query_db",
+            "input": {},
+            "caller": {"type": "code_execution_20250825", "tool_id": "srvtoolu_1"}
+        }))
+        .unwrap();
+        let ContentBlock::ToolUse { caller, .. } = block else {
+            panic!("Expected ToolUse");
+        };
+        let caller = caller.unwrap();
+        assert!(caller.is_code_execution());
+        assert_eq!(caller.tool_id.as_deref(), Some("srvtoolu_1"));
+
+        // The bare string form is still accepted
+        let caller: ToolCaller = serde_json::from_value(serde_json::json!("direct")).unwrap();
+        assert_eq!(caller, ToolCaller::direct());
+    }
+
+    #[test]
+    fn test_allowed_callers() {
+        let tool = CustomTool::new("query_db", "Query", serde_json::json!({"type": "object"}));
+        assert!(tool.allows_caller(None));
+        assert!(!tool.allows_caller(Some(&ToolCaller::code_execution("srvtoolu_1"))));
+
+        let tool = tool.with_allowed_callers([CODE_EXECUTION_CALLER]);
+        assert!(!tool.allows_caller(Some(&ToolCaller::direct())));
+        assert!(tool.allows_caller(Some(&ToolCaller::code_execution("srvtoolu_1"))));
+
+        // Other code execution versions match too, as in `is_code_execution`
+        let newer: ToolCaller = serde_json::from_value(
+            serde_json::json!({"type": "code_execution_20260120", "tool_id": "srvtoolu_2"}),
+        )
+        .unwrap();
+        assert!(newer.is_code_execution());
+        assert!(tool.allows_caller(Some(&newer)));
+
+        let request = MessagesRequest::new("claude-sonnet-4-5-20250929", 1024, vec![])
+            .with_tools(vec![tool.into()]);
+        assert_eq!(
+            request.beta_header().as_deref(),
+            Some(ADVANCED_TOOL_USE_BETA)
+        );
     }
 
     #[test]
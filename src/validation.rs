@@ -0,0 +1,180 @@
+//! Local JSON Schema validation
+//!
+//! Strict mode is not available for every model or schema, so tool inputs
+//! and structured outputs can still arrive in the wrong shape. A
+//! [`SchemaValidator`] checks a value against a schema locally and reports
+//! each [`Violation`] with the JSON pointer of the offending value, in a form
+//! that can be sent back to Claude as an `is_error` tool result.
+//!
+//! Validation is wired into:
+//!
+//! - [`ToolRegistry::with_input_validation`](crate::ToolRegistry::with_input_validation),
+//!   which rejects tool inputs that do not match the tool's `input_schema`
+//! - [`ClaudeClient::send_structured`](crate::ClaudeClient::send_structured),
+//!   which checks the output against `OutputFormat.schema` (or the forced
+//!   tool's schema) before deserializing, so violations drive the repair turn
+//!
+//! Requires the `validation` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use claude_sdk::validation::SchemaValidator;
+//! use serde_json::json;
+//!
+//! let validator = SchemaValidator::new(&json!({
+//!     "type": "object",
+//!     "properties": {"city": {"type": "string"}},
+//!     "required": ["city"]
+//! }))?;
+//!
+//! let violations = validator.validate(&json!({"city": 42})).unwrap_err();
+//! assert_eq!(violations[0].path, "/city");
+//! assert_eq!(violations[0].to_string(), r#"/city: 42 is not of type "string""#);
+//! # Ok::<(), claude_sdk::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use crate::types::CustomTool;
+use serde_json::Value;
+use std::fmt;
+
+/// A compiled JSON schema
+#[derive(Debug, Clone)]
+pub struct SchemaValidator {
+    validator: jsonschema::Validator,
+}
+
+impl SchemaValidator {
+    /// Compile a schema
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRequest`] if the schema itself is invalid or
+    /// references an external document.
+    pub fn new(schema: &Value) -> Result<Self> {
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|e| Error::InvalidRequest(format!("Invalid JSON schema: {}", e)))?;
+        Ok(Self { validator })
+    }
+
+    /// Compile a tool's `input_schema`
+    pub fn for_tool(tool: &CustomTool) -> Result<Self> {
+        Self::new(&tool.input_schema)
+    }
+
+    /// Whether `instance` matches the schema
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.validator.is_valid(instance)
+    }
+
+    /// Check `instance`, returning every violation
+    pub fn validate(&self, instance: &Value) -> std::result::Result<(), Vec<Violation>> {
+        let violations: Vec<Violation> = self
+            .validator
+            .iter_errors(instance)
+            .map(|error| Violation {
+                path: error.instance_path().as_str().to_string(),
+                message: error.to_string(),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// A value that does not match its schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer of the offending value; empty for the root
+    pub path: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root): {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Render violations as a bulleted list, one per line
+pub fn describe(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("- {}", v))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn address_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "zip": {"type": "string"},
+                        "tags": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "required": ["zip"],
+                    "additionalProperties": false
+                }
+            },
+            "required": ["name", "address"]
+        })
+    }
+
+    #[test]
+    fn test_reports_paths() {
+        let validator = SchemaValidator::new(&address_schema()).unwrap();
+
+        assert!(validator.is_valid(&json!({"name": "a", "address": {"zip": "1"}})));
+
+        let violations = validator
+            .validate(&json!({"address": {"tags": ["x", 2], "extra": true}}))
+            .unwrap_err();
+        let rendered: Vec<String> = violations.iter().map(ToString::to_string).collect();
+
+        assert!(rendered.contains(&r#"(root): "name" is a required property"#.to_string()));
+        assert!(rendered.contains(&r#"/address: "zip" is a required property"#.to_string()));
+        assert!(rendered.contains(&r#"/address/tags/1: 2 is not of type "string""#.to_string()));
+        assert!(violations
+            .iter()
+            .any(|v| v.path == "/address" && v.message.contains("extra")));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        let err = SchemaValidator::new(&json!({"type": "nope"})).unwrap_err();
+        assert!(matches!(err, Error::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn test_describe() {
+        let violations = vec![
+            Violation {
+                path: String::new(),
+                message: "bad".into(),
+            },
+            Violation {
+                path: "/a".into(),
+                message: "worse".into(),
+            },
+        ];
+        assert_eq!(describe(&violations), "- (root): bad\n- /a: worse");
+    }
+}